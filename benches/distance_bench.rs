@@ -0,0 +1,35 @@
+//! Scalar vs SIMD distance kernels (run with `--features simd`)
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use vectordb_from_scratch::distance::{
+    dot_product_scalar, dot_product_simd, euclidean_distance_scalar, euclidean_distance_simd,
+};
+use vectordb_from_scratch::Vector;
+
+fn random_vector(dim: usize) -> Vector {
+    Vector::new((0..dim).map(|_| rand::random::<f32>()).collect())
+}
+
+fn benchmark_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("distance_dim128");
+    let a = random_vector(128);
+    let b = random_vector(128);
+
+    group.bench_function("euclidean_scalar", |bench| {
+        bench.iter(|| euclidean_distance_scalar(black_box(&a), black_box(&b)))
+    });
+    group.bench_function("euclidean_simd", |bench| {
+        bench.iter(|| euclidean_distance_simd(black_box(&a), black_box(&b)))
+    });
+    group.bench_function("dot_scalar", |bench| {
+        bench.iter(|| dot_product_scalar(black_box(&a), black_box(&b)))
+    });
+    group.bench_function("dot_simd", |bench| {
+        bench.iter(|| dot_product_simd(black_box(&a), black_box(&b)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_distance);
+criterion_main!(benches);
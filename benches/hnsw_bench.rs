@@ -31,7 +31,7 @@ fn benchmark_hnsw_vs_flat(c: &mut Criterion) {
 
         // Build HNSW index
         let params = HnswParams::new(16, 200, 50);
-        let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params);
+        let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
         for (i, v) in vectors.iter().enumerate() {
             hnsw.add(i, v.clone()).unwrap();
         }
@@ -66,7 +66,7 @@ fn benchmark_hnsw_insert(c: &mut Criterion) {
     group.bench_function("insert_1000_128d", |b| {
         b.iter(|| {
             let params = HnswParams::new(16, 200, 50);
-            let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params);
+            let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
             for (i, v) in vectors.iter().enumerate() {
                 hnsw.add(i, v.clone()).unwrap();
             }
@@ -76,5 +76,39 @@ fn benchmark_hnsw_insert(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, benchmark_hnsw_vs_flat, benchmark_hnsw_insert);
+fn benchmark_hnsw_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hnsw_build");
+    group.sample_size(10);
+
+    let dim = 128;
+    let vectors = create_random_vectors(5_000, dim);
+
+    group.bench_function("serial_5000_128d", |b| {
+        b.iter(|| {
+            let params = HnswParams::new(16, 200, 50);
+            let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
+            for (i, v) in vectors.iter().enumerate() {
+                hnsw.add(i, v.clone()).unwrap();
+            }
+        });
+    });
+
+    // Neighbor searches run on the rayon pool; speedup scales with core count
+    group.bench_function("parallel_5000_128d", |b| {
+        b.iter(|| {
+            let params = HnswParams::new(16, 200, 50);
+            let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
+            hnsw.add_batch(vectors.iter().cloned().enumerate().collect()).unwrap();
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_hnsw_vs_flat,
+    benchmark_hnsw_insert,
+    benchmark_hnsw_build
+);
 criterion_main!(benches);
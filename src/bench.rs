@@ -0,0 +1,186 @@
+//! In-process benchmarking: build a throwaway index on random data and time queries.
+
+use std::time::Instant;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::distance::DistanceMetric;
+use crate::error::Result;
+use crate::eval::recall_at_k;
+use crate::flat_index::FlatIndex;
+use crate::hnsw::HnswIndex;
+use crate::index::Index;
+use crate::metrics::MetricsCollector;
+use crate::vector::Vector;
+
+/// Which index implementation to benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchIndexKind {
+    #[default]
+    Flat,
+    Hnsw,
+}
+
+/// Parameters for a self-benchmark run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchConfig {
+    /// Number of random vectors to index.
+    pub n: usize,
+    /// Vector dimension.
+    pub dim: usize,
+    /// Number of neighbors per query.
+    pub k: usize,
+    /// Number of random queries to run.
+    pub queries: usize,
+    /// Index implementation to build.
+    #[serde(default)]
+    pub index: BenchIndexKind,
+}
+
+/// Timing results of a self-benchmark run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub n: usize,
+    pub dim: usize,
+    pub k: usize,
+    pub queries: usize,
+    pub index: BenchIndexKind,
+    pub build_time_ms: f64,
+    pub qps: f64,
+    pub avg_latency_us: f64,
+    pub p50_latency_us: f64,
+    pub p95_latency_us: f64,
+    pub p99_latency_us: f64,
+}
+
+/// Timings for one index in a flat-vs-HNSW comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexTiming {
+    pub build_time_ms: f64,
+    pub avg_latency_us: f64,
+}
+
+/// Result of `compare_flat_hnsw`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexComparison {
+    pub flat: IndexTiming,
+    pub hnsw: IndexTiming,
+    /// Mean recall@k of HNSW against the exact flat results.
+    pub hnsw_recall: f64,
+}
+
+/// Generate `n` random vectors with components uniformly drawn from [0, 1).
+pub fn random_vectors(n: usize, dim: usize) -> Vec<Vector> {
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|_| Vector::new((0..dim).map(|_| rng.gen::<f32>()).collect()))
+        .collect()
+}
+
+/// Build a temporary index of random vectors and time `config.queries` searches against it.
+pub fn run(config: &BenchConfig, metric: DistanceMetric) -> Result<BenchReport> {
+    match config.index {
+        BenchIndexKind::Flat => run_with_index(config, FlatIndex::new(metric)),
+        BenchIndexKind::Hnsw => run_with_index(config, HnswIndex::new(metric)),
+    }
+}
+
+fn run_with_index<I: Index>(config: &BenchConfig, mut index: I) -> Result<BenchReport> {
+    let vectors = random_vectors(config.n, config.dim);
+    let queries = random_vectors(config.queries, config.dim);
+
+    let build_start = Instant::now();
+    for (i, v) in vectors.into_iter().enumerate() {
+        index.add(i, v)?;
+    }
+    let build_time = build_start.elapsed();
+
+    let mut metrics = MetricsCollector::new();
+    let search_start = Instant::now();
+    for query in &queries {
+        let start = Instant::now();
+        index.search(query, config.k)?;
+        metrics.record_query(start.elapsed());
+    }
+    let search_time = search_start.elapsed().as_secs_f64();
+
+    let qps = if search_time > 0.0 {
+        config.queries as f64 / search_time
+    } else {
+        0.0
+    };
+
+    Ok(BenchReport {
+        n: config.n,
+        dim: config.dim,
+        k: config.k,
+        queries: config.queries,
+        index: config.index,
+        build_time_ms: build_time.as_secs_f64() * 1000.0,
+        qps,
+        avg_latency_us: metrics.avg_query_latency_us(),
+        p50_latency_us: metrics.percentile_query_latency_us(50.0),
+        p95_latency_us: metrics.percentile_query_latency_us(95.0),
+        p99_latency_us: metrics.percentile_query_latency_us(99.0),
+    })
+}
+
+/// Index the same random vectors in a flat and an HNSW index, run the same
+/// queries against both, and report latency alongside HNSW recall@k.
+pub fn compare_flat_hnsw(
+    n: usize,
+    dim: usize,
+    k: usize,
+    queries: usize,
+    metric: DistanceMetric,
+) -> Result<IndexComparison> {
+    let vectors = random_vectors(n, dim);
+    let queries = random_vectors(queries, dim);
+
+    let mut flat = FlatIndex::new(metric);
+    let mut hnsw = HnswIndex::new(metric);
+    let flat_build = time_build(&mut flat, &vectors)?;
+    let hnsw_build = time_build(&mut hnsw, &vectors)?;
+
+    let mut flat_metrics = MetricsCollector::new();
+    let mut hnsw_metrics = MetricsCollector::new();
+    let mut total_recall = 0.0;
+    for query in &queries {
+        let start = Instant::now();
+        let truth = flat.search(query, k)?;
+        flat_metrics.record_query(start.elapsed());
+
+        let start = Instant::now();
+        let found = hnsw.search(query, k)?;
+        hnsw_metrics.record_query(start.elapsed());
+
+        total_recall += recall_at_k(&truth, &found);
+    }
+
+    Ok(IndexComparison {
+        flat: IndexTiming {
+            build_time_ms: flat_build,
+            avg_latency_us: flat_metrics.avg_query_latency_us(),
+        },
+        hnsw: IndexTiming {
+            build_time_ms: hnsw_build,
+            avg_latency_us: hnsw_metrics.avg_query_latency_us(),
+        },
+        hnsw_recall: if queries.is_empty() {
+            0.0
+        } else {
+            total_recall / queries.len() as f64
+        },
+    })
+}
+
+/// Add `vectors` to `index`, returning the elapsed time in milliseconds.
+fn time_build<I: Index>(index: &mut I, vectors: &[Vector]) -> Result<f64> {
+    let start = Instant::now();
+    for (i, v) in vectors.iter().enumerate() {
+        index.add(i, v.clone())?;
+    }
+    Ok(start.elapsed().as_secs_f64() * 1000.0)
+}
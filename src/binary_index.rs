@@ -0,0 +1,266 @@
+//! Flat index over bit-packed binary vectors, searched by Hamming distance.
+//! One bit per component instead of 32, and XOR + popcount per 64 components.
+
+use std::collections::HashMap;
+
+use crate::distance::{hamming_distance, DistanceMetric};
+use crate::error::Result;
+use crate::index::{self, Index};
+use crate::vector::Vector;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A binary vector, packed 64 components to a `u64` word. Bit `i` lives in
+/// word `i / 64` at position `i % 64`; unused high bits of the last word are
+/// always zero, so Hamming distance can XOR whole words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BinaryVector {
+    words: Vec<u64>,
+    dimension: usize,
+}
+
+impl BinaryVector {
+    /// Pack one bit per `bool`.
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut words = vec![0u64; bits.len().div_ceil(WORD_BITS)];
+        for (i, _) in bits.iter().enumerate().filter(|(_, &bit)| bit) {
+            words[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+        }
+        Self {
+            words,
+            dimension: bits.len(),
+        }
+    }
+
+    /// Binarize a full-precision vector: a component is set when it's
+    /// positive, as with sign-based hash codes.
+    pub fn from_vector(vector: &Vector) -> Self {
+        let bits: Vec<bool> = vector.as_slice().iter().map(|&x| x > 0.0).collect();
+        Self::from_bools(&bits)
+    }
+
+    /// Whether component `i` is set. Panics if `i` is out of range.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.dimension, "bit {} out of range for dimension {}", i, self.dimension);
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+
+    /// The packed words.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Number of components (bits).
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Unpack to a full-precision vector of `1.0` for set bits and `-1.0` for
+    /// clear ones, which `from_vector` packs back to the same bits.
+    pub fn to_vector(&self) -> Vector {
+        Vector::new(
+            (0..self.dimension)
+                .map(|i| if self.get(i) { 1.0 } else { -1.0 })
+                .collect(),
+        )
+    }
+}
+
+/// A brute-force index that binarizes vectors on insert (see
+/// [`BinaryVector::from_vector`]) and ranks by Hamming distance.
+///
+/// Only the packed bits are kept, so `Index::get_vector` returns `None`, and
+/// `Index::reconstruct_vector` gives the ±1 vector of [`BinaryVector::to_vector`].
+/// That is all a snapshot needs to rebuild the same index, but the original
+/// magnitudes are gone.
+#[derive(Debug, Default)]
+pub struct BinaryFlatIndex {
+    vectors: HashMap<usize, BinaryVector>,
+    /// Vector dimension, fixed by the first insert.
+    dimension: Option<usize>,
+}
+
+impl BinaryFlatIndex {
+    /// Create a new empty binary index. The metric is always `Hamming`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the packed form of a vector by internal ID.
+    pub fn get_binary(&self, id: usize) -> Option<&BinaryVector> {
+        self.vectors.get(&id)
+    }
+
+    /// Search with an already-packed query, e.g. a hash code computed elsewhere.
+    pub fn search_binary(&self, query: &BinaryVector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.scan(query, k, &|_| true)
+    }
+
+    fn scan(
+        &self,
+        query: &BinaryVector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+    ) -> Result<Vec<(usize, f32)>> {
+        let mut results: Vec<(usize, u32)> = self
+            .vectors
+            .iter()
+            .filter(|(&id, _)| predicate(id))
+            .map(|(&id, bv)| Ok((id, hamming_distance(query, bv)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Integer distances tie often, so break ties by ID
+        results.sort_unstable_by_key(|&(id, distance)| (distance, id));
+        results.truncate(k);
+        Ok(results.into_iter().map(|(id, d)| (id, d as f32)).collect())
+    }
+}
+
+impl Index for BinaryFlatIndex {
+    fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        index::check_dimension(self.dimension, &vector)?;
+        self.dimension = Some(vector.dimension());
+        self.vectors.insert(id, BinaryVector::from_vector(&vector));
+        Ok(())
+    }
+
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        self.dimension = index::check_batch_dimension(self.dimension, &items)?;
+        for (id, vector) in items {
+            self.vectors.insert(id, BinaryVector::from_vector(&vector));
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: usize) -> Result<()> {
+        self.vectors.remove(&id);
+        Ok(())
+    }
+
+    fn get_vector(&self, _id: usize) -> Option<&Vector> {
+        None
+    }
+
+    fn reconstruct_vector(&self, id: usize) -> Option<Vector> {
+        self.vectors.get(&id).map(BinaryVector::to_vector)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.scan(&BinaryVector::from_vector(query), k, &|_| true)
+    }
+
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        self.scan(&BinaryVector::from_vector(query), k, predicate)
+    }
+
+    fn prefilters(&self) -> bool {
+        true
+    }
+
+    fn metric(&self) -> DistanceMetric {
+        DistanceMetric::Hamming
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        let table = self.vectors.capacity()
+            * (std::mem::size_of::<usize>() + std::mem::size_of::<BinaryVector>() + 1);
+        let words: usize = self
+            .vectors
+            .values()
+            .map(|v| std::mem::size_of_val(v.as_words()))
+            .sum();
+        table + words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bools_packs_across_words() {
+        let mut bits = vec![false; 70];
+        bits[0] = true;
+        bits[63] = true;
+        bits[64] = true;
+        bits[69] = true;
+        let bv = BinaryVector::from_bools(&bits);
+
+        assert_eq!(bv.dimension(), 70);
+        assert_eq!(bv.as_words(), &[1 | 1 << 63, 1 | 1 << 5]);
+        assert_eq!(bv.count_ones(), 4);
+        assert!(bv.get(63) && bv.get(64) && !bv.get(65));
+        assert_eq!(
+            BinaryVector::from_vector(&Vector::new(vec![0.5, 0.0, -1.0, 2.0])),
+            BinaryVector::from_bools(&[true, false, false, true])
+        );
+    }
+
+    #[test]
+    fn test_binary_index_search() {
+        let mut index = BinaryFlatIndex::new();
+        index.add(0, Vector::new(vec![1.0, 1.0, 1.0, 1.0])).unwrap();
+        index.add(1, Vector::new(vec![1.0, -1.0, -1.0, -1.0])).unwrap();
+        index.add(2, Vector::new(vec![1.0, 1.0, -1.0, 1.0])).unwrap();
+        index.add(3, Vector::new(vec![-1.0, -1.0, -1.0, -1.0])).unwrap();
+
+        let results = index.search(&Vector::new(vec![0.9, 0.2, 0.3, 0.7]), 3).unwrap();
+        assert_eq!(results, vec![(0, 0.0), (2, 1.0), (1, 3.0)]);
+
+        let query = BinaryVector::from_bools(&[false, false, false, true]);
+        let results = index.search_binary(&query, 2).unwrap();
+        assert_eq!(results, vec![(3, 1.0), (1, 2.0)]);
+
+        let results = index
+            .search_filtered(&Vector::new(vec![1.0; 4]), 2, &|id| id != 0, 1)
+            .unwrap();
+        assert_eq!(results[0], (2, 1.0));
+        assert!(index.get_vector(0).is_none());
+        assert_eq!(index.metric(), DistanceMetric::Hamming);
+    }
+
+    #[test]
+    fn test_reconstruct_packs_to_same_bits() {
+        let mut index = BinaryFlatIndex::new();
+        let v = Vector::new(vec![0.5, 0.0, -2.0, 3.0]);
+        index.add(7, v.clone()).unwrap();
+
+        let restored = index.reconstruct_vector(7).unwrap();
+        assert_eq!(restored.as_slice(), &[1.0, -1.0, -1.0, 1.0]);
+        assert_eq!(BinaryVector::from_vector(&restored), BinaryVector::from_vector(&v));
+        assert!(index.reconstruct_vector(8).is_none());
+    }
+
+    #[test]
+    fn test_binary_index_rejects_mismatched_dimension() {
+        use crate::error::VectorDbError;
+
+        let mut index = BinaryFlatIndex::new();
+        index.add(0, Vector::new(vec![1.0, -1.0, 1.0])).unwrap();
+        assert!(matches!(
+            index.add(1, Vector::new(vec![1.0, -1.0])),
+            Err(VectorDbError::DimensionMismatch { expected: 3, actual: 2 })
+        ));
+        let batch = vec![
+            (1, Vector::new(vec![-1.0, 1.0, 1.0])),
+            (2, Vector::new(vec![1.0, -1.0])),
+        ];
+        assert!(index.add_batch(batch).is_err());
+        assert_eq!(index.len(), 1);
+    }
+}
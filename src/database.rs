@@ -0,0 +1,198 @@
+//! Named collections of independent vector stores.
+//!
+//! Each collection is a full `VectorStore` with its own metric, dimension and
+//! ID space, so unrelated datasets can be served from one process.
+
+use std::collections::HashMap;
+
+use crate::distance::DistanceMetric;
+use crate::error::{Result, VectorDbError};
+use crate::flat_index::FlatIndex;
+use crate::hnsw::HnswIndex;
+use crate::index::Index;
+use crate::ivf::IvfIndex;
+use crate::lsh::LshIndex;
+use crate::quantized_index::QuantizedFlatIndex;
+use crate::storage::VectorStore;
+
+/// A set of named `VectorStore`s sharing one index type.
+#[derive(Debug)]
+pub struct Database<I: Index> {
+    collections: HashMap<String, VectorStore<I>>,
+    /// Builds the index for a new collection.
+    make_index: fn(DistanceMetric) -> I,
+}
+
+/// Index types that can be built empty from just a metric, with default
+/// parameters. Lets a `Database` create collections on demand.
+pub trait FromMetric: Index {
+    fn from_metric(metric: DistanceMetric) -> Self;
+}
+
+impl FromMetric for FlatIndex {
+    fn from_metric(metric: DistanceMetric) -> Self {
+        FlatIndex::new(metric)
+    }
+}
+
+impl FromMetric for QuantizedFlatIndex {
+    fn from_metric(metric: DistanceMetric) -> Self {
+        QuantizedFlatIndex::new(metric)
+    }
+}
+
+impl FromMetric for HnswIndex {
+    fn from_metric(metric: DistanceMetric) -> Self {
+        HnswIndex::new(metric)
+    }
+}
+
+impl FromMetric for IvfIndex {
+    fn from_metric(metric: DistanceMetric) -> Self {
+        IvfIndex::new(metric)
+    }
+}
+
+impl FromMetric for LshIndex {
+    fn from_metric(metric: DistanceMetric) -> Self {
+        LshIndex::new(metric)
+    }
+}
+
+impl<I: FromMetric> Database<I> {
+    /// Create an empty database whose collections use `I` with default parameters.
+    pub fn new() -> Self {
+        Self::with_index_factory(I::from_metric)
+    }
+}
+
+impl<I: FromMetric> Default for Database<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Index> Database<I> {
+    /// Create an empty database that builds each collection's index with
+    /// `make_index`, e.g. a function applying custom `HnswParams`.
+    pub fn with_index_factory(make_index: fn(DistanceMetric) -> I) -> Self {
+        Self {
+            collections: HashMap::new(),
+            make_index,
+        }
+    }
+
+    /// Create a new empty collection. Fails if the name is already taken.
+    pub fn create_collection(
+        &mut self,
+        name: impl Into<String>,
+        metric: DistanceMetric,
+    ) -> Result<&mut VectorStore<I>> {
+        let name = name.into();
+        if self.collections.contains_key(&name) {
+            return Err(VectorDbError::CollectionExists { name });
+        }
+        let store = VectorStore::with_index((self.make_index)(metric));
+        Ok(self.collections.entry(name).or_insert(store))
+    }
+
+    /// Get a collection by name.
+    pub fn collection(&self, name: &str) -> Option<&VectorStore<I>> {
+        self.collections.get(name)
+    }
+
+    /// Get a mutable reference to a collection by name.
+    pub fn collection_mut(&mut self, name: &str) -> Option<&mut VectorStore<I>> {
+        self.collections.get_mut(name)
+    }
+
+    /// Remove a collection, returning its store.
+    pub fn drop_collection(&mut self, name: &str) -> Result<VectorStore<I>> {
+        self.collections
+            .remove(name)
+            .ok_or_else(|| VectorDbError::CollectionNotFound {
+                name: name.to_string(),
+            })
+    }
+
+    /// Names of all collections, sorted.
+    pub fn list_collections(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.collections.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Delete expired vectors from every collection, returning how many were removed.
+    pub fn expire_now(&mut self) -> usize {
+        self.collections.values_mut().map(VectorStore::expire_now).sum()
+    }
+
+    /// Number of collections.
+    pub fn len(&self) -> usize {
+        self.collections.len()
+    }
+
+    /// Check if there are no collections.
+    pub fn is_empty(&self) -> bool {
+        self.collections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vector;
+
+    #[test]
+    fn test_collections_are_isolated() {
+        let mut db = Database::<FlatIndex>::new();
+        db.create_collection("images", DistanceMetric::Euclidean)
+            .unwrap();
+        db.create_collection("text", DistanceMetric::Cosine).unwrap();
+
+        db.collection_mut("images")
+            .unwrap()
+            .insert("a", Vector::new(vec![1.0, 2.0]))
+            .unwrap();
+        db.collection_mut("text")
+            .unwrap()
+            .insert("a", Vector::new(vec![1.0, 0.0, 0.0, 0.0]))
+            .unwrap();
+
+        let images = db.collection("images").unwrap();
+        let text = db.collection("text").unwrap();
+        assert_eq!(images.dimension(), Some(2));
+        assert_eq!(text.dimension(), Some(4));
+        assert_eq!(images.metric(), DistanceMetric::Euclidean);
+        assert_eq!(text.metric(), DistanceMetric::Cosine);
+        assert_eq!(images.get("a"), Some(&Vector::new(vec![1.0, 2.0])));
+
+        // Each collection enforces its own dimension
+        assert!(db
+            .collection_mut("images")
+            .unwrap()
+            .insert("b", Vector::new(vec![1.0, 0.0, 0.0, 0.0]))
+            .is_err());
+        assert_eq!(db.collection("text").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_create_list_drop() {
+        let mut db = Database::<HnswIndex>::new();
+        db.create_collection("b", DistanceMetric::Euclidean).unwrap();
+        db.create_collection("a", DistanceMetric::Euclidean).unwrap();
+        assert!(matches!(
+            db.create_collection("a", DistanceMetric::Cosine),
+            Err(VectorDbError::CollectionExists { .. })
+        ));
+        assert_eq!(db.list_collections(), vec!["a", "b"]);
+
+        db.drop_collection("a").unwrap();
+        assert_eq!(db.list_collections(), vec!["b"]);
+        assert!(db.collection("a").is_none());
+        assert!(matches!(
+            db.drop_collection("a"),
+            Err(VectorDbError::CollectionNotFound { .. })
+        ));
+    }
+}
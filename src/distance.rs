@@ -1,8 +1,16 @@
 //! Distance metrics for vector similarity
 
+use crate::binary_index::BinaryVector;
 use crate::error::{Result, VectorDbError};
 use crate::vector::Vector;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// `distance_batch` splits inputs at least this long across the rayon pool.
+const PARALLEL_BATCH_MIN: usize = 1024;
 
 /// Distance metrics for measuring vector similarity
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,9 +21,33 @@ pub enum DistanceMetric {
     Cosine,
     /// Dot product (negated for minimum distance)
     DotProduct,
+    /// Inner product, reported as-is for maximum inner product search:
+    /// unlike every other metric, higher is closer (see `higher_is_better`).
+    InnerProduct,
+    /// Jensen–Shannon divergence between probability distributions (natural
+    /// log, so in `[0, ln 2]`). Both vectors must be non-negative and sum to 1;
+    /// `distance` doesn't check this, so validate inputs once with `validate`
+    /// (as `VectorStore` does for inserts and queries).
+    JensenShannon,
+    /// Hamming distance between sign bits: the number of components that are
+    /// positive in one vector but not the other. `BinaryFlatIndex` stores
+    /// vectors bit-packed and computes the same thing with popcounts.
+    Hamming,
+    /// Canberra distance, `sum |a - b| / (|a| + |b|)`: each component
+    /// contributes its relative difference, so in `[0, dimension]`.
+    Canberra,
 }
 
 impl DistanceMetric {
+    /// Check that `v` is something this metric can measure: a probability
+    /// distribution for `JensenShannon`. Every other metric accepts any vector.
+    pub fn validate(&self, v: &Vector) -> Result<()> {
+        match self {
+            DistanceMetric::JensenShannon => check_probability(v),
+            _ => Ok(()),
+        }
+    }
+
     /// Compute the distance between two vectors using this metric
     pub fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32> {
         if !v1.has_same_dimension(v2) {
@@ -24,23 +56,162 @@ impl DistanceMetric {
                 actual: v2.dimension(),
             });
         }
+        self.distance_unchecked(v1, v2)
+    }
+
+    /// Distances from `query` to each of `vectors`, in order.
+    ///
+    /// Every dimension is checked before any distance is computed, so a
+    /// mismatch fails fast with the first offending vector's dimension.
+    /// Batches of `PARALLEL_BATCH_MIN` (1024) or more are spread over the
+    /// rayon pool.
+    pub fn distance_batch(&self, query: &Vector, vectors: &[Vector]) -> Result<Vec<f32>> {
+        if let Some(v) = vectors.iter().find(|v| !query.has_same_dimension(v)) {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: query.dimension(),
+                actual: v.dimension(),
+            });
+        }
+        if vectors.len() >= PARALLEL_BATCH_MIN {
+            vectors
+                .par_iter()
+                .map(|v| self.distance_unchecked(query, v))
+                .collect()
+        } else {
+            vectors
+                .iter()
+                .map(|v| self.distance_unchecked(query, v))
+                .collect()
+        }
+    }
 
+    /// `distance` for vectors already known to have the same dimension.
+    fn distance_unchecked(&self, v1: &Vector, v2: &Vector) -> Result<f32> {
         match self {
             DistanceMetric::Euclidean => Ok(euclidean_distance(v1, v2)),
             DistanceMetric::Cosine => cosine_distance(v1, v2),
             DistanceMetric::DotProduct => Ok(-dot_product(v1, v2)),
+            DistanceMetric::InnerProduct => Ok(dot_product(v1, v2)),
+            DistanceMetric::JensenShannon => Ok(jensen_shannon_unchecked(v1, v2) as f32),
+            DistanceMetric::Hamming => Ok(sign_hamming_distance(v1, v2) as f32),
+            DistanceMetric::Canberra => Ok(canberra_distance(v1, v2) as f32),
+        }
+    }
+
+    /// Whether a larger value from `distance` means a closer match. Only
+    /// true for `InnerProduct`.
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, DistanceMetric::InnerProduct)
+    }
+
+    /// `distance`, negated where needed so that lower is closer for every
+    /// metric. For code that ranks internally and doesn't report the value.
+    pub fn rank_distance(&self, v1: &Vector, v2: &Vector) -> Result<f32> {
+        let d = self.distance(v1, v2)?;
+        Ok(if self.higher_is_better() { -d } else { d })
+    }
+
+    /// Whether distance `a` ranks strictly ahead of `b` under this metric.
+    /// NaN is never better than anything.
+    pub fn is_better_than(&self, a: f32, b: f32) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+
+    /// Order two values from `distance` closest first, with NaN last.
+    pub fn compare(&self, a: f32, b: f32) -> Ordering {
+        if self.higher_is_better() {
+            cmp_distance(-a, -b)
+        } else {
+            cmp_distance(a, b)
+        }
+    }
+
+    /// Like `distance`, but accumulated in f64 (see `euclidean_distance_f64`).
+    pub fn distance_f64(&self, v1: &Vector, v2: &Vector) -> Result<f64> {
+        if !v1.has_same_dimension(v2) {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: v1.dimension(),
+                actual: v2.dimension(),
+            });
+        }
+
+        match self {
+            DistanceMetric::Euclidean => Ok(euclidean_distance_f64(v1, v2)),
+            DistanceMetric::Cosine => cosine_distance_f64(v1, v2),
+            DistanceMetric::DotProduct => Ok(-dot_product_f64(v1, v2)),
+            DistanceMetric::InnerProduct => Ok(dot_product_f64(v1, v2)),
+            DistanceMetric::JensenShannon => Ok(jensen_shannon_unchecked(v1, v2)),
+            DistanceMetric::Hamming => Ok(f64::from(sign_hamming_distance(v1, v2))),
+            DistanceMetric::Canberra => Ok(canberra_distance(v1, v2)),
+        }
+    }
+}
+
+/// Lowercase names, as accepted by `FromStr` and the CLI's `--metric`.
+impl fmt::Display for DistanceMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DistanceMetric::Euclidean => "euclidean",
+            DistanceMetric::Cosine => "cosine",
+            DistanceMetric::DotProduct => "dot",
+            DistanceMetric::InnerProduct => "inner-product",
+            DistanceMetric::JensenShannon => "jensen-shannon",
+            DistanceMetric::Hamming => "hamming",
+            DistanceMetric::Canberra => "canberra",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Case-insensitive, ignoring `-` and `_`, so "dot", "DotProduct" and
+/// "dot_product" all parse.
+impl FromStr for DistanceMetric {
+    type Err = VectorDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match normalized.as_str() {
+            "euclidean" => Ok(DistanceMetric::Euclidean),
+            "cosine" => Ok(DistanceMetric::Cosine),
+            "dot" | "dotproduct" => Ok(DistanceMetric::DotProduct),
+            "innerproduct" => Ok(DistanceMetric::InnerProduct),
+            "jensenshannon" | "js" => Ok(DistanceMetric::JensenShannon),
+            "hamming" => Ok(DistanceMetric::Hamming),
+            "canberra" => Ok(DistanceMetric::Canberra),
+            _ => Err(VectorDbError::UnknownMetric {
+                name: s.to_string(),
+            }),
         }
     }
 }
 
-/// Compute Euclidean (L2) distance between two vectors
+/// Total order on distances for sorting results: ascending, with NaN (of
+/// either sign) after every real distance.
+pub fn cmp_distance(a: f32, b: f32) -> Ordering {
+    a.is_nan().cmp(&b.is_nan()).then(a.total_cmp(&b))
+}
+
+/// Compute Euclidean (L2) distance between two vectors.
+///
+/// Uses the SIMD kernel when built with the `simd` feature.
 pub fn euclidean_distance(v1: &Vector, v2: &Vector) -> f32 {
-    v1.as_slice()
-        .iter()
-        .zip(v2.as_slice().iter())
-        .map(|(a, b)| (a - b).powi(2))
-        .sum::<f32>()
-        .sqrt()
+    #[cfg(feature = "simd")]
+    {
+        euclidean_distance_simd(v1, v2)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        euclidean_distance_scalar(v1, v2)
+    }
+}
+
+/// Scalar Euclidean distance, one component at a time.
+pub fn euclidean_distance_scalar(v1: &Vector, v2: &Vector) -> f32 {
+    pairwise_sum(v1.as_slice(), v2.as_slice(), |a, b| (a - b).powi(2)).sqrt()
 }
 
 /// Compute cosine distance between two vectors (1 - cosine similarity)
@@ -63,12 +234,229 @@ pub fn cosine_distance(v1: &Vector, v2: &Vector) -> Result<f32> {
     Ok(1.0 - similarity)
 }
 
-/// Compute dot product of two vectors
+/// Compute dot product of two vectors.
+///
+/// Uses the SIMD kernel when built with the `simd` feature.
 pub fn dot_product(v1: &Vector, v2: &Vector) -> f32 {
+    #[cfg(feature = "simd")]
+    {
+        dot_product_simd(v1, v2)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        dot_product_scalar(v1, v2)
+    }
+}
+
+/// Scalar dot product, one component at a time.
+pub fn dot_product_scalar(v1: &Vector, v2: &Vector) -> f32 {
+    pairwise_sum(v1.as_slice(), v2.as_slice(), |a, b| a * b)
+}
+
+/// Terms summed left to right at the leaves of `pairwise_sum`.
+const PAIRWISE_BLOCK: usize = 32;
+
+/// Sum `term(a[i], b[i])` over both slices by recursively halving them, so
+/// rounding error grows with log(len) rather than len as in a running sum.
+/// Leaves of `PAIRWISE_BLOCK` terms are summed directly.
+pub(crate) fn pairwise_sum(a: &[f32], b: &[f32], term: impl Fn(f32, f32) -> f32) -> f32 {
+    fn sum_halves(a: &[f32], b: &[f32], term: &impl Fn(f32, f32) -> f32) -> f32 {
+        let len = a.len().min(b.len());
+        if len <= PAIRWISE_BLOCK {
+            return a.iter().zip(b).map(|(&x, &y)| term(x, y)).sum();
+        }
+        let (a_lo, a_hi) = a.split_at(len / 2);
+        let (b_lo, b_hi) = b.split_at(len / 2);
+        sum_halves(a_lo, b_lo, term) + sum_halves(a_hi, b_hi, term)
+    }
+    sum_halves(a, b, &term)
+}
+
+/// Relative tolerance within which the SIMD kernels agree with the scalar
+/// ones. They add in a different order (eight partial sums, then the
+/// remainder), so results can differ in the last few bits.
+#[cfg(feature = "simd")]
+pub const SIMD_RELATIVE_EPSILON: f32 = 1e-5;
+
+#[cfg(feature = "simd")]
+const LANES: usize = 8;
+
+/// Split two equal-length slices into 8-lane chunks plus a scalar remainder.
+#[cfg(feature = "simd")]
+fn lanes<'a>(
+    a: &'a [f32],
+    b: &'a [f32],
+) -> (
+    impl Iterator<Item = (wide::f32x8, wide::f32x8)> + 'a,
+    impl Iterator<Item = (&'a f32, &'a f32)> + 'a,
+) {
+    let split = a.len().min(b.len()) / LANES * LANES;
+    let to_lanes = |chunk: &[f32]| wide::f32x8::from(<[f32; LANES]>::try_from(chunk).unwrap());
+    let chunks = a[..split]
+        .chunks_exact(LANES)
+        .zip(b[..split].chunks_exact(LANES))
+        .map(move |(x, y)| (to_lanes(x), to_lanes(y)));
+    let rest = a[split..].iter().zip(&b[split..]);
+    (chunks, rest)
+}
+
+/// Euclidean distance over 8-wide SIMD lanes; the tail that doesn't fill a
+/// lane is handled with scalar code.
+#[cfg(feature = "simd")]
+pub fn euclidean_distance_simd(v1: &Vector, v2: &Vector) -> f32 {
+    let (chunks, rest) = lanes(v1.as_slice(), v2.as_slice());
+    let mut acc = wide::f32x8::ZERO;
+    for (x, y) in chunks {
+        let d = x - y;
+        acc = d.mul_add(d, acc);
+    }
+    let tail: f32 = rest.map(|(a, b)| (a - b) * (a - b)).sum();
+    (acc.reduce_add() + tail).sqrt()
+}
+
+/// Dot product over 8-wide SIMD lanes, with a scalar tail.
+#[cfg(feature = "simd")]
+pub fn dot_product_simd(v1: &Vector, v2: &Vector) -> f32 {
+    let (chunks, rest) = lanes(v1.as_slice(), v2.as_slice());
+    let mut acc = wide::f32x8::ZERO;
+    for (x, y) in chunks {
+        acc = x.mul_add(y, acc);
+    }
+    let tail: f32 = rest.map(|(a, b)| a * b).sum();
+    acc.reduce_add() + tail
+}
+
+/// Euclidean distance with every term widened to f64 before summing.
+///
+/// Components are still stored as f32, but an f32 running sum loses
+/// precision as the dimension grows; a double-precision one doesn't.
+pub fn euclidean_distance_f64(v1: &Vector, v2: &Vector) -> f64 {
     v1.as_slice()
         .iter()
-        .zip(v2.as_slice().iter())
-        .map(|(a, b)| a * b)
+        .zip(v2.as_slice())
+        .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Dot product accumulated in f64.
+pub fn dot_product_f64(v1: &Vector, v2: &Vector) -> f64 {
+    v1.as_slice()
+        .iter()
+        .zip(v2.as_slice())
+        .map(|(&a, &b)| f64::from(a) * f64::from(b))
+        .sum()
+}
+
+/// Cosine distance accumulated in f64.
+pub fn cosine_distance_f64(v1: &Vector, v2: &Vector) -> Result<f64> {
+    let norm1 = v1.norm_f64();
+    let norm2 = v2.norm_f64();
+
+    if norm1 == 0.0 || norm2 == 0.0 {
+        return Err(VectorDbError::InvalidVector {
+            reason: "Cannot compute cosine distance with zero vector".to_string(),
+        });
+    }
+
+    let similarity = (dot_product_f64(v1, v2) / (norm1 * norm2)).clamp(-1.0, 1.0);
+    Ok(1.0 - similarity)
+}
+
+/// Number of differing bits between two packed binary vectors.
+pub fn hamming_distance(a: &BinaryVector, b: &BinaryVector) -> Result<u32> {
+    if a.dimension() != b.dimension() {
+        return Err(VectorDbError::DimensionMismatch {
+            expected: a.dimension(),
+            actual: b.dimension(),
+        });
+    }
+    Ok(a.as_words()
+        .iter()
+        .zip(b.as_words())
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum())
+}
+
+/// Hamming distance between the sign bits of unpacked vectors.
+fn sign_hamming_distance(v1: &Vector, v2: &Vector) -> u32 {
+    v1.as_slice()
+        .iter()
+        .zip(v2.as_slice())
+        .filter(|(&a, &b)| (a > 0.0) != (b > 0.0))
+        .count() as u32
+}
+
+/// How far a probability vector's sum may drift from 1 (f32 rounding,
+/// softmax outputs serialized with few digits).
+const PROBABILITY_SUM_TOLERANCE: f64 = 1e-3;
+
+/// Reject vectors that aren't probability distributions.
+fn check_probability(v: &Vector) -> Result<()> {
+    let mut sum = 0.0f64;
+    for &x in v.as_slice() {
+        if !(x >= 0.0 && x.is_finite()) {
+            return Err(VectorDbError::InvalidVector {
+                reason: format!("probability components must be non-negative, found {}", x),
+            });
+        }
+        sum += f64::from(x);
+    }
+    if (sum - 1.0).abs() > PROBABILITY_SUM_TOLERANCE {
+        return Err(VectorDbError::InvalidVector {
+            reason: format!("probability vector sums to {}, expected 1", sum),
+        });
+    }
+    Ok(())
+}
+
+/// Jensen–Shannon divergence, accumulated in f64:
+/// `JS(p, q) = (KL(p || m) + KL(q || m)) / 2` with `m = (p + q) / 2`.
+///
+/// Symmetric and always finite: a zero component contributes nothing, and
+/// wherever `p` or `q` is positive so is `m`. Fails with `InvalidVector` if
+/// either input isn't a probability distribution.
+pub fn jensen_shannon_divergence(v1: &Vector, v2: &Vector) -> Result<f64> {
+    check_probability(v1)?;
+    check_probability(v2)?;
+    Ok(jensen_shannon_unchecked(v1, v2))
+}
+
+/// `jensen_shannon_divergence` for inputs already known to be distributions.
+fn jensen_shannon_unchecked(v1: &Vector, v2: &Vector) -> f64 {
+    // p * ln(p / m), taking 0 * ln 0 = 0
+    let kl_term = |p: f64, m: f64| if p > 0.0 { p * (p / m).ln() } else { 0.0 };
+    let divergence: f64 = v1
+        .as_slice()
+        .iter()
+        .zip(v2.as_slice())
+        .map(|(&a, &b)| {
+            let (p, q) = (f64::from(a), f64::from(b));
+            let m = (p + q) / 2.0;
+            kl_term(p, m) + kl_term(q, m)
+        })
+        .sum::<f64>()
+        / 2.0;
+
+    // Rounding can leave identical inputs a hair below zero
+    divergence.max(0.0)
+}
+
+/// Canberra distance, accumulated in f64. A component where both vectors
+/// are zero contributes 0 rather than `0 / 0`.
+pub fn canberra_distance(v1: &Vector, v2: &Vector) -> f64 {
+    v1.as_slice()
+        .iter()
+        .zip(v2.as_slice())
+        .map(|(&a, &b)| {
+            let (a, b) = (f64::from(a), f64::from(b));
+            let denominator = a.abs() + b.abs();
+            if denominator > 0.0 {
+                (a - b).abs() / denominator
+            } else {
+                0.0
+            }
+        })
         .sum()
 }
 
@@ -85,6 +473,42 @@ mod tests {
         assert_relative_eq!(dist, 5.196152, epsilon = 1e-5);
     }
 
+    #[test]
+    fn test_distance_batch_matches_single_calls() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let query = Vector::new((0..16).map(|_| rng.gen_range(-1.0..1.0)).collect());
+        // One batch below the parallel threshold and one above it
+        for n in [10, PARALLEL_BATCH_MIN + 5] {
+            let vectors: Vec<Vector> = (0..n)
+                .map(|_| Vector::new((0..16).map(|_| rng.gen_range(-1.0..1.0)).collect()))
+                .collect();
+            for metric in [
+                DistanceMetric::Euclidean,
+                DistanceMetric::Cosine,
+                DistanceMetric::InnerProduct,
+                DistanceMetric::Hamming,
+            ] {
+                let batch = metric.distance_batch(&query, &vectors).unwrap();
+                let single: Vec<f32> =
+                    vectors.iter().map(|v| metric.distance(&query, v).unwrap()).collect();
+                assert_eq!(batch, single, "{} with {} vectors", metric, n);
+            }
+        }
+
+        assert!(DistanceMetric::Euclidean.distance_batch(&query, &[]).unwrap().is_empty());
+        let mixed = [Vector::new(vec![0.0; 16]), Vector::new(vec![0.0; 3])];
+        assert!(matches!(
+            DistanceMetric::Euclidean.distance_batch(&query, &mixed),
+            Err(VectorDbError::DimensionMismatch {
+                expected: 16,
+                actual: 3
+            })
+        ));
+        // Metric-specific errors still surface
+        assert!(DistanceMetric::Cosine.distance_batch(&query, &mixed[..1]).is_err());
+    }
+
     #[test]
     fn test_euclidean_same_vector() {
         let v = Vector::new(vec![1.0, 2.0, 3.0]);
@@ -100,6 +524,190 @@ mod tests {
         assert_relative_eq!(dot, 32.0, epsilon = 1e-6);
     }
 
+    #[test]
+    fn test_pairwise_sum_beats_naive_sum() {
+        let data: Vec<f32> = (0..2048).map(|i| 0.1 + (i % 7) as f32 * 0.01).collect();
+        let v = Vector::new(data.clone());
+        let reference: f64 = data.iter().map(|&x| f64::from(x) * f64::from(x)).sum();
+
+        let naive: f32 = data.iter().map(|x| x * x).sum();
+        let naive_error = (f64::from(naive) - reference).abs();
+        let pairwise_error = (f64::from(dot_product_scalar(&v, &v)) - reference).abs();
+        assert!(
+            pairwise_error < naive_error,
+            "pairwise {} vs naive {}",
+            pairwise_error,
+            naive_error
+        );
+
+        let norm_error = (f64::from(v.norm()) - reference.sqrt()).abs();
+        assert!(norm_error <= (f64::from(naive.sqrt()) - reference.sqrt()).abs());
+    }
+
+    #[test]
+    fn test_f64_accumulation_reduces_error() {
+        let dim = 1_000_000;
+        let v1 = Vector::from_f64(&vec![0.1; dim]);
+        let v2 = Vector::new(vec![1.0; dim]);
+        // The stored components are 0.1 rounded to f32; the exact sum uses that value
+        let exact = f64::from(0.1f32) * dim as f64;
+
+        let running_sum: f32 = v1.as_slice().iter().sum();
+        let f32_error = (f64::from(running_sum) - exact).abs() / exact;
+        let f64_error = (dot_product_f64(&v1, &v2) - exact).abs() / exact;
+        assert!(f32_error > 1e-4, "f32 error {}", f32_error);
+        assert!(f64_error < 1e-9, "f64 error {}", f64_error);
+
+        let zero = Vector::new(vec![0.0; dim]);
+        let dist = DistanceMetric::Euclidean.distance_f64(&v1, &zero).unwrap();
+        assert_relative_eq!(dist, f64::from(0.1f32) * (dim as f64).sqrt(), epsilon = 1e-9);
+        assert_eq!(v1.as_f64()[0], f64::from(0.1f32));
+    }
+
+    #[test]
+    fn test_metric_from_str() {
+        for (s, metric) in [
+            ("euclidean", DistanceMetric::Euclidean),
+            ("COSINE", DistanceMetric::Cosine),
+            ("dot", DistanceMetric::DotProduct),
+            ("DotProduct", DistanceMetric::DotProduct),
+            ("dot_product", DistanceMetric::DotProduct),
+            ("inner-product", DistanceMetric::InnerProduct),
+            ("jensen_shannon", DistanceMetric::JensenShannon),
+            ("JS", DistanceMetric::JensenShannon),
+            ("Hamming", DistanceMetric::Hamming),
+            ("Canberra", DistanceMetric::Canberra),
+        ] {
+            assert_eq!(s.parse::<DistanceMetric>().unwrap(), metric, "{}", s);
+        }
+        for s in ["", "manhattan", "dot product", "l2"] {
+            assert!(matches!(
+                s.parse::<DistanceMetric>(),
+                Err(VectorDbError::UnknownMetric { name }) if name == s
+            ));
+        }
+    }
+
+    #[test]
+    fn test_metric_display_round_trips() {
+        for metric in [
+            DistanceMetric::Euclidean,
+            DistanceMetric::Cosine,
+            DistanceMetric::DotProduct,
+            DistanceMetric::InnerProduct,
+            DistanceMetric::JensenShannon,
+            DistanceMetric::Hamming,
+            DistanceMetric::Canberra,
+        ] {
+            assert_eq!(metric.to_string().parse::<DistanceMetric>().unwrap(), metric);
+        }
+        assert_eq!(DistanceMetric::DotProduct.to_string(), "dot");
+    }
+
+    #[test]
+    fn test_inner_product_is_not_negated() {
+        let v1 = Vector::new(vec![1.0, 2.0, 3.0]);
+        let v2 = Vector::new(vec![4.0, 5.0, 6.0]);
+        let metric = DistanceMetric::InnerProduct;
+        assert!(metric.higher_is_better());
+        assert_relative_eq!(metric.distance(&v1, &v2).unwrap(), 32.0, epsilon = 1e-6);
+        assert_relative_eq!(metric.rank_distance(&v1, &v2).unwrap(), -32.0, epsilon = 1e-6);
+        assert_eq!(metric.compare(32.0, 5.0), Ordering::Less);
+        assert_eq!(metric.compare(f32::NAN, 5.0), Ordering::Greater);
+        assert_eq!(DistanceMetric::DotProduct.compare(32.0, 5.0), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_is_better_than_respects_direction() {
+        assert!(DistanceMetric::Euclidean.is_better_than(1.0, 2.0));
+        assert!(!DistanceMetric::Euclidean.is_better_than(2.0, 1.0));
+        assert!(DistanceMetric::InnerProduct.is_better_than(2.0, 1.0));
+        assert!(!DistanceMetric::InnerProduct.is_better_than(1.0, 2.0));
+        assert!(!DistanceMetric::InnerProduct.is_better_than(1.0, 1.0));
+        for metric in [DistanceMetric::Euclidean, DistanceMetric::InnerProduct] {
+            assert!(!metric.is_better_than(f32::NAN, 1.0));
+            assert!(metric.is_better_than(1.0, f32::NAN));
+        }
+    }
+
+    #[test]
+    fn test_hamming_distance_known_patterns() {
+        let a = BinaryVector::from_bools(&[true, false, true, false]);
+        let b = BinaryVector::from_bools(&[true, true, false, false]);
+        assert_eq!(hamming_distance(&a, &a).unwrap(), 0);
+        assert_eq!(hamming_distance(&a, &b).unwrap(), 2);
+
+        let zeros = BinaryVector::from_bools(&[false; 130]);
+        let ones = BinaryVector::from_bools(&[true; 130]);
+        assert_eq!(hamming_distance(&zeros, &ones).unwrap(), 130);
+        assert!(matches!(
+            hamming_distance(&a, &zeros),
+            Err(VectorDbError::DimensionMismatch { expected: 4, actual: 130 })
+        ));
+
+        let v1 = Vector::new(vec![0.3, -0.1, 2.0, -5.0]);
+        let v2 = Vector::new(vec![1.0, 4.0, -2.0, 0.0]);
+        assert_eq!(DistanceMetric::Hamming.distance(&v1, &v2).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_jensen_shannon_identical_is_zero() {
+        let p = Vector::new(vec![0.1, 0.2, 0.3, 0.4]);
+        let d = DistanceMetric::JensenShannon.distance(&p, &p).unwrap();
+        assert!(d.abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_jensen_shannon_disjoint_support_is_ln_2() {
+        let p = Vector::new(vec![0.5, 0.5, 0.0, 0.0]);
+        let q = Vector::new(vec![0.0, 0.0, 0.25, 0.75]);
+        let metric = DistanceMetric::JensenShannon;
+        assert!((metric.distance(&p, &q).unwrap() - std::f32::consts::LN_2).abs() < 1e-6);
+        assert!((metric.distance_f64(&q, &p).unwrap() - std::f64::consts::LN_2).abs() < 1e-12);
+
+        let r = Vector::new(vec![0.25, 0.25, 0.25, 0.25]);
+        let d = metric.distance(&p, &r).unwrap();
+        assert!(d > 0.0 && d < std::f32::consts::LN_2);
+        assert_eq!(d, metric.distance(&r, &p).unwrap());
+    }
+
+    #[test]
+    fn test_jensen_shannon_rejects_non_distributions() {
+        let p = Vector::new(vec![0.5, 0.5]);
+        for bad in [vec![1.5, -0.5], vec![0.5, 0.6], vec![f32::NAN, 1.0]] {
+            let bad = Vector::new(bad);
+            assert!(matches!(
+                DistanceMetric::JensenShannon.validate(&bad),
+                Err(VectorDbError::InvalidVector { .. })
+            ));
+            assert!(jensen_shannon_divergence(&bad, &p).is_err());
+            assert!(jensen_shannon_divergence(&p, &bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_canberra_distance() {
+        let metric = DistanceMetric::Canberra;
+        // |1-2|/3 + |2-2|/4 + |3-(-1)|/4 + |(-4)-0|/4 = 1/3 + 0 + 1 + 1
+        let v1 = Vector::new(vec![1.0, 2.0, 3.0, -4.0]);
+        let v2 = Vector::new(vec![2.0, 2.0, -1.0, 0.0]);
+        assert_relative_eq!(metric.distance(&v1, &v2).unwrap(), 7.0 / 3.0, epsilon = 1e-6);
+        assert_relative_eq!(metric.distance_f64(&v1, &v2).unwrap(), 7.0 / 3.0, epsilon = 1e-9);
+        assert_eq!(metric.distance(&v1, &v1).unwrap(), 0.0);
+
+        // Coordinates that are zero in both vectors add nothing
+        let a = Vector::new(vec![0.0, 1.0, 0.0]);
+        let b = Vector::new(vec![0.0, 3.0, 0.0]);
+        assert_relative_eq!(metric.distance(&a, &b).unwrap(), 0.5, epsilon = 1e-6);
+        let zero = Vector::new(vec![0.0; 3]);
+        assert_eq!(metric.distance(&zero, &zero).unwrap(), 0.0);
+
+        assert!(matches!(
+            metric.distance(&a, &v1),
+            Err(VectorDbError::DimensionMismatch { expected: 3, actual: 4 })
+        ));
+    }
+
     #[test]
     fn test_cosine_distance() {
         let v1 = Vector::new(vec![1.0, 0.0, 0.0]);
@@ -132,6 +740,29 @@ mod tests {
         assert_relative_eq!(dist, 5.196152, epsilon = 1e-5);
     }
 
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_matches_scalar() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        // Cover lane-multiple dimensions and every remainder length
+        for dim in (1..=40).chain([128, 129, 1536]) {
+            let random = |rng: &mut rand::rngs::ThreadRng| {
+                Vector::new((0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            };
+            let (a, b) = (random(&mut rng), random(&mut rng));
+            let close = |x: f32, y: f32| {
+                (x - y).abs() <= SIMD_RELATIVE_EPSILON * x.abs().max(y.abs()).max(1.0)
+            };
+
+            let simd = euclidean_distance_simd(&a, &b);
+            let scalar = euclidean_distance_scalar(&a, &b);
+            assert!(close(simd, scalar), "dim {}: {} vs {}", dim, simd, scalar);
+            let (simd, scalar) = (dot_product_simd(&a, &b), dot_product_scalar(&a, &b));
+            assert!(close(simd, scalar), "dim {}: {} vs {}", dim, simd, scalar);
+        }
+    }
+
     #[test]
     fn test_dimension_mismatch() {
         let v1 = Vector::new(vec![1.0, 2.0]);
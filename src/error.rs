@@ -1,5 +1,6 @@
 //! Error types for the vector database
 
+use crate::distance::DistanceMetric;
 use thiserror::Error;
 
 /// Result type alias for VectorDB operations
@@ -14,6 +15,9 @@ pub enum VectorDbError {
     #[error("Vector not found: {id}")]
     VectorNotFound { id: String },
 
+    #[error("Vector already exists: {id}")]
+    VectorExists { id: String },
+
     #[error("Invalid vector: {reason}")]
     InvalidVector { reason: String },
 
@@ -26,6 +30,30 @@ pub enum VectorDbError {
     #[error("Storage error: {0}")]
     StorageError(String),
 
+    #[error("Collection not found: {name}")]
+    CollectionNotFound { name: String },
+
+    #[error("Collection already exists: {name}")]
+    CollectionExists { name: String },
+
+    #[error("Metric mismatch: data was written with {stored:?}, opened with {requested:?}")]
+    MetricMismatch {
+        stored: DistanceMetric,
+        requested: DistanceMetric,
+    },
+
+    #[error(
+        "Unknown distance metric: {name} \
+         (expected euclidean, cosine, dot, inner-product, jensen-shannon, hamming or canberra)"
+    )]
+    UnknownMetric { name: String },
+
     #[error("Index error: {0}")]
     IndexError(String),
+
+    #[error("Query {index} in batch failed: {source}")]
+    BatchQueryError {
+        index: usize,
+        source: Box<VectorDbError>,
+    },
 }
@@ -0,0 +1,89 @@
+//! Recall measurement: how many of the exact nearest neighbors an index finds.
+//!
+//! Useful for tuning approximate indexes (e.g. HNSW `ef_search`, IVF `nprobe`)
+//! against a `FlatIndex` holding the same vectors.
+
+use std::collections::HashSet;
+
+use crate::error::Result;
+use crate::flat_index::FlatIndex;
+use crate::index::Index;
+use crate::vector::Vector;
+
+/// Fraction of the exact neighbors in `ground_truth` that also appear in `results`.
+pub fn recall_at_k(ground_truth: &[(usize, f32)], results: &[(usize, f32)]) -> f64 {
+    if ground_truth.is_empty() {
+        return 1.0;
+    }
+    let truth: HashSet<usize> = ground_truth.iter().map(|(id, _)| *id).collect();
+    let found = results.iter().filter(|(id, _)| truth.contains(id)).count();
+    found as f64 / ground_truth.len() as f64
+}
+
+/// Mean recall@k of `index` over `queries`, taking `ground_truth`'s exact
+/// results as correct. Both indexes must use the same internal IDs.
+/// Returns 1.0 for an empty query set.
+pub fn evaluate_recall<I: Index>(
+    index: &I,
+    ground_truth: &FlatIndex,
+    queries: &[Vector],
+    k: usize,
+) -> Result<f64> {
+    if queries.is_empty() {
+        return Ok(1.0);
+    }
+    let mut total = 0.0;
+    for query in queries {
+        let truth = ground_truth.search(query, k)?;
+        total += recall_at_k(&truth, &index.search(query, k)?);
+    }
+    Ok(total / queries.len() as f64)
+}
+
+/// Copy every full-precision vector of `index` into a `FlatIndex` with the
+/// same IDs, for use as ground truth.
+pub fn flat_copy<'a>(
+    index: &impl Index,
+    ids: impl IntoIterator<Item = &'a usize>,
+) -> Result<FlatIndex> {
+    let mut flat = FlatIndex::new(index.metric());
+    for &id in ids {
+        if let Some(vector) = index.get_vector(id) {
+            flat.add(id, vector.clone())?;
+        }
+    }
+    Ok(flat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::random_vectors;
+    use crate::distance::DistanceMetric;
+    use crate::hnsw::HnswIndex;
+
+    #[test]
+    fn test_index_against_itself_is_perfect() {
+        let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+        for (i, v) in random_vectors(200, 8).into_iter().enumerate() {
+            flat.add(i, v).unwrap();
+        }
+        let queries = random_vectors(20, 8);
+        assert_eq!(evaluate_recall(&flat, &flat, &queries, 10).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_recall_is_a_fraction() {
+        let mut hnsw = HnswIndex::new(DistanceMetric::Euclidean);
+        for (i, v) in random_vectors(300, 8).into_iter().enumerate() {
+            hnsw.add(i, v).unwrap();
+        }
+        let ids: Vec<usize> = (0..300).collect();
+        let flat = flat_copy(&hnsw, &ids).unwrap();
+        assert_eq!(flat.len(), 300);
+
+        let recall = evaluate_recall(&hnsw, &flat, &random_vectors(20, 8), 5).unwrap();
+        assert!((0.0..=1.0).contains(&recall));
+        assert_eq!(recall_at_k(&[(1, 0.0), (2, 0.0)], &[(2, 0.0), (3, 0.0)]), 0.5);
+    }
+}
@@ -1,10 +1,12 @@
 //! Brute-force flat index — O(n) k-NN search
 
 use std::collections::HashMap;
+use std::path::Path;
 
 use crate::distance::DistanceMetric;
 use crate::error::Result;
-use crate::index::Index;
+use crate::index::{self, Index, PersistIndex};
+use crate::persistence::serialization;
 use crate::vector::Vector;
 
 /// A flat (brute-force) index that computes distance to every stored vector.
@@ -12,6 +14,8 @@ use crate::vector::Vector;
 pub struct FlatIndex {
     vectors: HashMap<usize, Vector>,
     metric: DistanceMetric,
+    /// Vector dimension, fixed by the first insert.
+    dimension: Option<usize>,
 }
 
 impl FlatIndex {
@@ -20,6 +24,7 @@ impl FlatIndex {
         Self {
             vectors: HashMap::new(),
             metric,
+            dimension: None,
         }
     }
 
@@ -36,10 +41,18 @@ impl FlatIndex {
 
 impl Index for FlatIndex {
     fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        index::check_dimension(self.dimension, &vector)?;
+        self.dimension = Some(vector.dimension());
         self.vectors.insert(id, vector);
         Ok(())
     }
 
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        self.dimension = index::check_batch_dimension(self.dimension, &items)?;
+        self.vectors.extend(items);
+        Ok(())
+    }
+
     fn remove(&mut self, id: usize) -> Result<()> {
         self.vectors.remove(&id);
         Ok(())
@@ -50,20 +63,38 @@ impl Index for FlatIndex {
     }
 
     fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.search_filtered(query, k, &|_| true, 1)
+    }
+
+    /// Pre-filtering scan: IDs rejected by `predicate` are skipped before any
+    /// distance is computed, so `k` results are returned whenever `k` IDs match.
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
         let mut results: Vec<(usize, f32)> = self
             .vectors
             .iter()
+            .filter(|(&id, _)| predicate(id))
             .map(|(&id, vec)| {
                 let distance = self.metric.distance(query, vec)?;
                 Ok((id, distance))
             })
             .collect::<Result<Vec<_>>>()?;
 
-        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        // HashMap order is arbitrary, so break distance ties by ID
+        results.sort_by(|a, b| self.metric.compare(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
         results.truncate(k);
         Ok(results)
     }
 
+    fn prefilters(&self) -> bool {
+        true
+    }
+
     fn metric(&self) -> DistanceMetric {
         self.metric
     }
@@ -71,12 +102,64 @@ impl Index for FlatIndex {
     fn len(&self) -> usize {
         self.vectors.len()
     }
+
+    fn memory_usage_bytes(&self) -> usize {
+        // hashbrown stores (key, value) pairs plus one control byte per bucket
+        let table = self.vectors.capacity()
+            * (std::mem::size_of::<usize>() + std::mem::size_of::<Vector>() + 1);
+        let data: usize = self
+            .vectors
+            .values()
+            .map(|v| v.dimension() * std::mem::size_of::<f32>())
+            .sum();
+        table + data
+    }
+}
+
+/// Stored as bincode of the id -> vector map; the metric isn't recorded.
+impl PersistIndex for FlatIndex {
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serialization::to_bincode(&self.vectors)?)?;
+        Ok(())
+    }
+
+    fn load(path: &Path, metric: DistanceMetric) -> Result<Self> {
+        let vectors: HashMap<usize, Vector> =
+            serialization::from_bincode(&std::fs::read(path)?)?;
+        let dimension = vectors.values().next().map(Vector::dimension);
+        Ok(Self {
+            vectors,
+            metric,
+            dimension,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_flat_index_higher_is_better_inverts_ranking() {
+        let vectors = [vec![1.0, 0.0], vec![2.0, 0.0], vec![3.0, 0.0]];
+        let mut dot = FlatIndex::new(DistanceMetric::DotProduct);
+        let mut inner = FlatIndex::new(DistanceMetric::InnerProduct);
+        for (i, v) in vectors.iter().enumerate() {
+            dot.add(i, Vector::new(v.clone())).unwrap();
+            inner.add(i, Vector::new(v.clone())).unwrap();
+        }
+
+        let query = Vector::new(vec![1.0, 0.0]);
+        let ids = |r: Vec<(usize, f32)>| r.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+        // DotProduct is negated, so lower is better and the largest dot wins too
+        assert_eq!(ids(dot.search(&query, 3).unwrap()), vec![2, 1, 0]);
+        let results = inner.search(&query, 3).unwrap();
+        assert_eq!(results.iter().map(|r| r.1).collect::<Vec<_>>(), vec![3.0, 2.0, 1.0]);
+
+        // With k smaller than n, the worst scores are the ones dropped
+        assert_eq!(ids(inner.search(&query, 1).unwrap()), vec![2]);
+    }
+
     #[test]
     fn test_flat_index_basic() {
         let mut index = FlatIndex::new(DistanceMetric::Euclidean);
@@ -102,6 +185,121 @@ mod tests {
         assert_eq!(index.get_vector(99), None);
     }
 
+    #[test]
+    fn test_flat_index_memory_usage_grows() {
+        let mut index = FlatIndex::new(DistanceMetric::Euclidean);
+        let mut previous = index.memory_usage_bytes();
+        for i in 0..50 {
+            index.add(i, Vector::new(vec![i as f32; 8])).unwrap();
+            let current = index.memory_usage_bytes();
+            assert!(current > previous);
+            previous = current;
+        }
+    }
+
+    #[test]
+    fn test_flat_index_search_filtered_selective() {
+        let mut index = FlatIndex::new(DistanceMetric::Euclidean);
+        for i in 0..100 {
+            index.add(i, Vector::new(vec![i as f32, 0.0])).unwrap();
+        }
+
+        // Only 4 IDs match, all far from the query
+        let predicate = |id: usize| id % 25 == 24;
+        let results = index
+            .search_filtered(&Vector::new(vec![0.0, 0.0]), 4, &predicate, 1)
+            .unwrap();
+
+        let ids: Vec<usize> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![24, 49, 74, 99]);
+    }
+
+    #[test]
+    fn test_flat_index_ties_break_by_id() {
+        let query = Vector::new(vec![0.0, 0.0]);
+        for _ in 0..5 {
+            // A fresh index each time, so the HashMap's iteration order changes
+            let mut index = FlatIndex::new(DistanceMetric::Euclidean);
+            for id in (0..20).rev() {
+                let sign = if id % 2 == 0 { 1.0 } else { -1.0 };
+                let v = if id % 4 < 2 { vec![sign, 0.0] } else { vec![0.0, sign] };
+                index.add(id, Vector::new(v)).unwrap();
+            }
+            index.add(20, Vector::new(vec![0.5, 0.0])).unwrap();
+
+            let ids: Vec<usize> = index.search(&query, 6).unwrap().iter().map(|r| r.0).collect();
+            assert_eq!(ids, vec![20, 0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_flat_index_nan_distances_sort_last() {
+        let mut index = FlatIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![f32::NAN, 0.0])).unwrap();
+        index.add(1, Vector::new(vec![2.0, 0.0])).unwrap();
+        index.add(2, Vector::new(vec![1.0, 0.0])).unwrap();
+        index.add(3, Vector::new(vec![f32::INFINITY, 0.0])).unwrap();
+
+        let results = index.search(&Vector::new(vec![0.0, 0.0]), 4).unwrap();
+        let ids: Vec<usize> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![2, 1, 3, 0]);
+
+        // inf - inf gives a NaN, which x86 produces with the sign bit set
+        let query = Vector::new(vec![f32::INFINITY, 0.0]);
+        let results = index.search(&query, 4).unwrap();
+        let nan_ids: Vec<usize> = results.iter().skip(2).map(|(id, _)| *id).collect();
+        assert!(results[..2].iter().all(|r| r.1 == f32::INFINITY));
+        assert!(nan_ids.contains(&0) && nan_ids.contains(&3));
+    }
+
+    #[test]
+    fn test_flat_index_save_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("flat.bin");
+        let mut index = FlatIndex::new(DistanceMetric::Cosine);
+        for i in 0..20 {
+            index.add(i, Vector::new(vec![i as f32, 1.0, (i % 3) as f32])).unwrap();
+        }
+        index.save(&path).unwrap();
+
+        let loaded = FlatIndex::load(&path, DistanceMetric::Cosine).unwrap();
+        assert_eq!(loaded.len(), 20);
+        let query = Vector::new(vec![4.5, 1.0, 2.0]);
+        assert_eq!(index.search(&query, 5).unwrap(), loaded.search(&query, 5).unwrap());
+    }
+
+    #[test]
+    fn test_flat_index_rejects_mismatched_dimension() {
+        use crate::error::VectorDbError;
+
+        let mismatch = |r: Result<()>| {
+            matches!(
+                r,
+                Err(VectorDbError::DimensionMismatch {
+                    expected: 3,
+                    actual: 2
+                })
+            )
+        };
+
+        let mut index = FlatIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![1.0, 0.0, 0.0])).unwrap();
+        assert!(mismatch(index.add(1, Vector::new(vec![1.0, 0.0]))));
+        let batch = vec![
+            (1, Vector::new(vec![0.0, 1.0, 0.0])),
+            (2, Vector::new(vec![1.0, 0.0])),
+        ];
+        assert!(mismatch(index.add_batch(batch)));
+        assert_eq!(index.len(), 1);
+
+        // A reloaded index still knows its dimension
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("flat.bin");
+        index.save(&path).unwrap();
+        let mut loaded = FlatIndex::load(&path, DistanceMetric::Euclidean).unwrap();
+        assert!(mismatch(loaded.add(1, Vector::new(vec![1.0, 0.0]))));
+    }
+
     #[test]
     fn test_flat_index_remove() {
         let mut index = FlatIndex::new(DistanceMetric::Euclidean);
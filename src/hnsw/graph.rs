@@ -4,19 +4,28 @@
 //! "Efficient and robust approximate nearest neighbor search using
 //!  Hierarchical Navigable Small World graphs" (Malkov & Yashunin, 2016/2018).
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::distance::DistanceMetric;
 use crate::error::{Result, VectorDbError};
+use crate::index;
+use crate::persistence::serialization::{
+    self, SerializedHnswGraph, SerializedHnswGraphV1, SerializedHnswNode,
+};
 use crate::vector::Vector;
 
 use super::neighbor_queue::{MaxHeap, MinHeap, Neighbor};
 
+/// Upper bound on how many vectors `insert_batch` searches for concurrently.
+const PARALLEL_CHUNK: usize = 256;
+
 /// Configuration parameters for the HNSW index.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HnswParams {
     /// Max number of connections per node (layers > 0).
     pub m: usize,
@@ -47,6 +56,12 @@ impl Default for HnswParams {
 }
 
 impl HnswParams {
+    /// Start building parameters; unset fields take their `default` values,
+    /// with `m_max0` and `ml` derived from `m`.
+    pub fn builder() -> HnswParamsBuilder {
+        HnswParamsBuilder::default()
+    }
+
     pub fn new(m: usize, ef_construction: usize, ef_search: usize) -> Self {
         Self {
             m,
@@ -57,6 +72,121 @@ impl HnswParams {
             max_layers: 16,
         }
     }
+
+    /// Reject parameter sets that would build a broken graph or panic.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(VectorDbError::IndexError(reason));
+        if self.m < 1 {
+            return invalid("m must be at least 1".to_string());
+        }
+        if self.m_max0 < 1 {
+            return invalid("m_max0 must be at least 1".to_string());
+        }
+        if self.ef_construction < self.m {
+            return invalid(format!(
+                "ef_construction ({}) must be at least m ({})",
+                self.ef_construction, self.m
+            ));
+        }
+        if self.ef_search < 1 {
+            return invalid("ef_search must be at least 1".to_string());
+        }
+        if self.max_layers < 1 {
+            return invalid("max_layers must be at least 1".to_string());
+        }
+        // `new` derives ml = 1 / ln(m), which is infinite for m = 1
+        if !(self.ml.is_finite() && self.ml > 0.0) {
+            return invalid(format!("ml must be finite and positive, got {}", self.ml));
+        }
+        Ok(())
+    }
+}
+
+/// Builder for `HnswParams`, from `HnswParams::builder()`.
+#[derive(Debug, Clone, Default)]
+pub struct HnswParamsBuilder {
+    m: Option<usize>,
+    m_max0: Option<usize>,
+    ef_construction: Option<usize>,
+    ef_search: Option<usize>,
+    max_layers: Option<usize>,
+}
+
+impl HnswParamsBuilder {
+    /// Max connections per node on layers above 0. Also sets `ml` to 1 / ln(m).
+    pub fn m(mut self, m: usize) -> Self {
+        self.m = Some(m);
+        self
+    }
+
+    /// Max connections at layer 0 (default 2 * m).
+    pub fn m_max0(mut self, m_max0: usize) -> Self {
+        self.m_max0 = Some(m_max0);
+        self
+    }
+
+    pub fn ef_construction(mut self, ef_construction: usize) -> Self {
+        self.ef_construction = Some(ef_construction);
+        self
+    }
+
+    pub fn ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = Some(ef_search);
+        self
+    }
+
+    pub fn max_layers(mut self, max_layers: usize) -> Self {
+        self.max_layers = Some(max_layers);
+        self
+    }
+
+    /// Fill in defaults and check the result with `HnswParams::validate`.
+    pub fn build(self) -> Result<HnswParams> {
+        let defaults = HnswParams::default();
+        let m = self.m.unwrap_or(defaults.m);
+        let params = HnswParams {
+            m_max0: self.m_max0.unwrap_or(2 * m),
+            max_layers: self.max_layers.unwrap_or(defaults.max_layers),
+            ..HnswParams::new(
+                m,
+                self.ef_construction.unwrap_or(defaults.ef_construction),
+                self.ef_search.unwrap_or(defaults.ef_search),
+            )
+        };
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+/// Structural summary of an HNSW graph, for debugging recall.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HnswStats {
+    /// Number of live nodes.
+    pub node_count: usize,
+    /// Highest layer in the graph.
+    pub max_level: usize,
+    /// `layer_node_counts[l]` is the number of live nodes present at layer `l`.
+    pub layer_node_counts: Vec<usize>,
+    /// Average number of layer-0 neighbors per live node.
+    pub avg_degree_layer0: f64,
+    /// Fewest layer-0 neighbors of any live node.
+    pub min_degree_layer0: usize,
+    /// Most layer-0 neighbors of any live node.
+    pub max_degree_layer0: usize,
+    /// Empty slots left behind by deletes (reclaimable with `compact`).
+    pub tombstones: usize,
+}
+
+/// Where one node sits in an HNSW graph, for working out why it isn't found.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeInfo {
+    /// Highest layer the node was inserted into.
+    pub level: usize,
+    /// `neighbor_counts[l]` is the node's out-degree at layer `l`, for every
+    /// layer from 0 up to `level`.
+    pub neighbor_counts: Vec<usize>,
+    /// Whether searches start from this node.
+    pub is_entry_point: bool,
 }
 
 /// A node in the HNSW graph.
@@ -67,6 +197,9 @@ struct HnswNode {
     vector: Vector,
     /// Neighbors per layer. neighbors[l] is the list of neighbor IDs at layer l.
     neighbors: Vec<Vec<usize>>,
+    /// Nodes that list this one as a neighbor on some layer, so `remove` can
+    /// find inbound edges without scanning the graph.
+    inbound: HashSet<usize>,
     /// The maximum layer this node was inserted into.
     level: usize,
 }
@@ -86,20 +219,42 @@ pub struct HnswGraph {
     metric: DistanceMetric,
     /// RNG for level generation.
     rng: StdRng,
+    /// Seed from `with_seed`, saved with the graph so a reloaded graph keeps
+    /// drawing reproducible levels.
+    seed: Option<u64>,
     /// Count of active (non-deleted) nodes.
     count: usize,
+    /// Vector dimension, fixed by the first insert.
+    dimension: Option<usize>,
 }
 
 impl HnswGraph {
     pub fn new(metric: DistanceMetric, params: HnswParams) -> Self {
+        Self::with_rng(metric, params, StdRng::from_entropy(), None)
+    }
+
+    /// Create a graph whose level generation is seeded, so the same inserts in
+    /// the same order always produce the same graph.
+    pub fn with_seed(metric: DistanceMetric, params: HnswParams, seed: u64) -> Self {
+        Self::with_rng(metric, params, StdRng::seed_from_u64(seed), Some(seed))
+    }
+
+    fn with_rng(
+        metric: DistanceMetric,
+        params: HnswParams,
+        rng: StdRng,
+        seed: Option<u64>,
+    ) -> Self {
         Self {
             nodes: Vec::new(),
             entry_point: None,
             max_level: 0,
             params,
             metric,
-            rng: StdRng::from_entropy(),
+            rng,
+            seed,
             count: 0,
+            dimension: None,
         }
     }
 
@@ -107,6 +262,20 @@ impl HnswGraph {
         self.metric
     }
 
+    /// Dimension of the stored vectors, or None before the first insert.
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    /// Fail with `DimensionMismatch` if `vector` doesn't match the graph's dimension.
+    fn check_dimension(&self, vector: &Vector) -> Result<()> {
+        index::check_dimension(self.dimension, vector)
+    }
+
+    pub fn params(&self) -> &HnswParams {
+        &self.params
+    }
+
     pub fn len(&self) -> usize {
         self.count
     }
@@ -115,6 +284,96 @@ impl HnswGraph {
         self.count == 0
     }
 
+    /// Compute structural statistics by walking every node.
+    pub fn stats(&self) -> HnswStats {
+        let mut layer_node_counts = vec![0; self.max_level + 1];
+        let mut degree_sum = 0;
+        let mut min_degree = usize::MAX;
+        let mut max_degree = 0;
+
+        for node in self.nodes.iter().flatten() {
+            for count in layer_node_counts.iter_mut().take(node.level + 1) {
+                *count += 1;
+            }
+            let degree = node.neighbors[0].len();
+            degree_sum += degree;
+            min_degree = min_degree.min(degree);
+            max_degree = max_degree.max(degree);
+        }
+
+        HnswStats {
+            node_count: self.count,
+            max_level: self.max_level,
+            layer_node_counts,
+            avg_degree_layer0: if self.count > 0 {
+                degree_sum as f64 / self.count as f64
+            } else {
+                0.0
+            },
+            min_degree_layer0: if self.count > 0 { min_degree } else { 0 },
+            max_degree_layer0: max_degree,
+            tombstones: self.nodes.len() - self.count,
+        }
+    }
+
+    /// Level and per-layer degree of the node with internal ID `id`, or None
+    /// if there is no such live node.
+    pub fn node_info(&self, id: usize) -> Option<NodeInfo> {
+        let node = self.nodes.get(id)?.as_ref()?;
+        Some(NodeInfo {
+            level: node.level,
+            neighbor_counts: node.neighbors.iter().map(Vec::len).collect(),
+            is_entry_point: self.entry_point == Some(id),
+        })
+    }
+
+    /// Render one layer as a GraphViz digraph: a node per live vector present at
+    /// `layer` (labelled with its internal id) and an edge per neighbor link.
+    /// Edges to deleted slots are left out. Render with `dot -Tsvg`.
+    pub fn to_dot(&self, layer: usize) -> String {
+        let mut dot = format!("digraph hnsw_layer_{} {{\n", layer);
+        for node in self.nodes.iter().flatten().filter(|n| n.level >= layer) {
+            dot.push_str(&format!("    n{} [label=\"{}\"];\n", node.id, node.id));
+        }
+        for node in self.nodes.iter().flatten().filter(|n| n.level >= layer) {
+            for &neighbor in &node.neighbors[layer] {
+                if self.nodes.get(neighbor).is_some_and(|n| n.is_some()) {
+                    dot.push_str(&format!("    n{} -> n{};\n", node.id, neighbor));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Number of node slots, including ones left empty by deletes.
+    pub fn slot_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Approximate heap memory held by the graph: node slots, vector data,
+    /// neighbor list capacities and the inbound edge sets.
+    pub fn memory_usage_bytes(&self) -> usize {
+        let slots = self.nodes.capacity() * std::mem::size_of::<Option<HnswNode>>();
+        let per_node: usize = self
+            .nodes
+            .iter()
+            .flatten()
+            .map(|node| {
+                let data = node.vector.dimension() * std::mem::size_of::<f32>();
+                let lists = node.neighbors.capacity() * std::mem::size_of::<Vec<usize>>();
+                let edges: usize = node
+                    .neighbors
+                    .iter()
+                    .map(|l| l.capacity() * std::mem::size_of::<usize>())
+                    .sum();
+                let inbound = node.inbound.capacity() * std::mem::size_of::<usize>();
+                data + lists + edges + inbound
+            })
+            .sum();
+        slots + per_node
+    }
+
     /// Generate a random level for a new node.
     fn random_level(&mut self) -> usize {
         let r: f64 = self.rng.gen();
@@ -122,12 +381,14 @@ impl HnswGraph {
         level.min(self.params.max_layers - 1)
     }
 
-    /// Compute distance between a query vector and a node.
+    /// Compute distance between a query vector and a node. The graph works
+    /// with `DistanceMetric::rank_distance` throughout, so lower is closer
+    /// even for `InnerProduct`; `search_knn` converts back.
     fn distance(&self, query: &Vector, node_id: usize) -> Result<f32> {
         let node = self.nodes[node_id]
             .as_ref()
             .ok_or_else(|| VectorDbError::IndexError("Node not found".to_string()))?;
-        self.metric.distance(query, &node.vector)
+        self.metric.rank_distance(query, &node.vector)
     }
 
     /// Get the vector for a given node ID (for internal use).
@@ -140,6 +401,9 @@ impl HnswGraph {
     /// Search a single layer of the graph for the ef closest neighbors to query.
     /// `ep` is the set of entry points (their IDs).
     /// Returns the ef closest neighbors found.
+    ///
+    /// Distances here come from `rank_distance`, so the heap ordering and the
+    /// `<`/`>` checks hold for metrics where `higher_is_better()` too.
     fn search_layer(
         &self,
         query: &Vector,
@@ -224,7 +488,7 @@ impl HnswGraph {
                 self.nodes.get(nid).and_then(|n| n.as_ref()).map(|n| {
                     let dist = self
                         .metric
-                        .distance(&node_vec, &n.vector)
+                        .rank_distance(&node_vec, &n.vector)
                         .unwrap_or(f32::MAX);
                     (nid, dist)
                 })
@@ -234,69 +498,184 @@ impl HnswGraph {
         scored.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
         scored.truncate(m);
 
+        let mut dropped = Vec::new();
         if let Some(node) = &mut self.nodes[node_id] {
             if layer < node.neighbors.len() {
-                node.neighbors[layer] = scored.into_iter().map(|(nid, _)| nid).collect();
+                // Reuse the existing allocation; the list refills to `m` on later inserts
+                let list = &mut node.neighbors[layer];
+                dropped.extend(list.iter().copied().filter(|n| !scored.iter().any(|s| s.0 == *n)));
+                list.clear();
+                list.extend(scored.into_iter().map(|(nid, _)| nid));
+            }
+        }
+        for nid in dropped {
+            self.unlink_inbound(node_id, nid);
+        }
+    }
+
+    /// Record that `from` lists `to` as a neighbor.
+    fn link_inbound(&mut self, from: usize, to: usize) {
+        if let Some(Some(node)) = self.nodes.get_mut(to) {
+            node.inbound.insert(from);
+        }
+    }
+
+    /// Forget `from` as an inbound neighbor of `to` once no layer of `from`
+    /// links to `to` any more.
+    fn unlink_inbound(&mut self, from: usize, to: usize) {
+        let still_linked = match self.nodes.get(from) {
+            Some(Some(node)) => node.neighbors.iter().any(|l| l.contains(&to)),
+            _ => false,
+        };
+        if !still_linked {
+            if let Some(Some(node)) = self.nodes.get_mut(to) {
+                node.inbound.remove(&from);
             }
         }
     }
 
     /// INSERT: Algorithm 1 from the HNSW paper.
     pub fn insert(&mut self, id: usize, vector: Vector) -> Result<()> {
+        self.check_dimension(&vector)?;
+        self.dimension = Some(vector.dimension());
         let level = self.random_level();
+        let candidates = self.search_candidates(&vector, level)?;
+        self.place_node(id, vector, level, &candidates);
+        Ok(())
+    }
 
-        // Ensure the nodes Vec is large enough
-        if id >= self.nodes.len() {
-            self.nodes.resize_with(id + 1, || None);
+    /// Insert many vectors, running the neighbor searches on the rayon pool.
+    ///
+    /// Levels are drawn up front in input order, so a seeded graph stays
+    /// reproducible. Vectors are then handled in chunks: every member of a chunk
+    /// searches the graph as it stood before the chunk (in parallel), and the
+    /// members are linked in one at a time. Since chunk members can't reach each
+    /// other through the graph, exact distances to the earlier members of the
+    /// same chunk are merged into each one's candidates. Chunks start at one
+    /// vector and grow with the graph, up to `PARALLEL_CHUNK`.
+    pub fn insert_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        // Validate the whole batch before anything is linked in
+        self.dimension = index::check_batch_dimension(self.dimension, &items)?;
+
+        let mut pending: Vec<(usize, Vector, usize)> = items
+            .into_iter()
+            .map(|(id, vector)| (id, vector, self.random_level()))
+            .collect();
+
+        while !pending.is_empty() {
+            let chunk_len = self.count.clamp(1, PARALLEL_CHUNK).min(pending.len());
+            let chunk: Vec<(usize, Vector, usize)> = pending.drain(..chunk_len).collect();
+
+            let searched: Vec<Vec<Vec<Neighbor>>> = chunk
+                .par_iter()
+                .map(|(_, vector, level)| self.search_candidates(vector, *level))
+                .collect::<Result<_>>()?;
+
+            let intra: Vec<Vec<(usize, f32)>> = (0..chunk.len())
+                .into_par_iter()
+                .map(|i| {
+                    (0..i)
+                        .map(|j| Ok((j, self.metric.rank_distance(&chunk[i].1, &chunk[j].1)?)))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .collect::<Result<_>>()?;
+
+            for (((id, vector, level), mut candidates), earlier) in
+                chunk.iter().cloned().zip(searched).zip(intra)
+            {
+                for (j, distance) in earlier {
+                    let (other_id, _, other_level) = chunk[j];
+                    for layer in candidates.iter_mut().take(level.min(other_level) + 1) {
+                        layer.push(Neighbor::new(other_id, distance));
+                    }
+                }
+                for layer in &mut candidates {
+                    layer.sort();
+                }
+                self.place_node(id, vector, level, &candidates);
+            }
         }
 
-        // Create the node
-        let node = HnswNode {
-            id,
-            vector: vector.clone(),
-            neighbors: vec![Vec::new(); level + 1],
-            level,
-        };
-        self.nodes[id] = Some(node);
-        self.count += 1;
+        Ok(())
+    }
 
-        // If this is the first node, set it as entry point
-        let entry_point = match self.entry_point {
-            None => {
-                self.entry_point = Some(id);
-                self.max_level = level;
-                return Ok(());
-            }
+    /// Find the `ef_construction` nearest existing nodes for a new vector at
+    /// each layer it will join: element `l` of the result holds layer `l`'s
+    /// candidates, closest first. Layers above the current top are left empty.
+    /// Read-only, so many of these can run concurrently.
+    fn search_candidates(&self, vector: &Vector, level: usize) -> Result<Vec<Vec<Neighbor>>> {
+        let mut layers: Vec<Vec<Neighbor>> = (0..=level).map(|_| Vec::new()).collect();
+        let mut ep_id = match self.entry_point {
             Some(ep) => ep,
+            None => return Ok(layers),
         };
 
-        let mut ep_id = entry_point;
-        let current_max_level = self.max_level;
-
         // Phase 1: Greedy descent from top layer down to level+1 (ef=1)
-        if current_max_level > level {
-            for l in (level + 1..=current_max_level).rev() {
-                let nearest = self.search_layer(&vector, &[ep_id], 1, l)?;
+        if self.max_level > level {
+            for l in (level + 1..=self.max_level).rev() {
+                let nearest = self.search_layer(vector, &[ep_id], 1, l)?;
                 if let Some(n) = nearest.first() {
                     ep_id = n.id;
                 }
             }
         }
 
-        // Phase 2: Insert at layers min(level, current_max_level) down to 0
-        let insert_from = level.min(current_max_level);
-        for l in (0..=insert_from).rev() {
+        // Phase 2: Search layers min(level, max_level) down to 0
+        for l in (0..=level.min(self.max_level)).rev() {
+            let nearest = self.search_layer(vector, &[ep_id], self.params.ef_construction, l)?;
+
+            // Update ep for next layer
+            if let Some(n) = nearest.first() {
+                ep_id = n.id;
+            }
+            layers[l] = nearest;
+        }
+
+        Ok(layers)
+    }
+
+    /// Store a node and connect it to its per-layer `candidates` (closest first),
+    /// adding reverse edges and pruning neighbors that go over capacity.
+    fn place_node(
+        &mut self,
+        id: usize,
+        vector: Vector,
+        level: usize,
+        candidates: &[Vec<Neighbor>],
+    ) {
+        // Ensure the nodes Vec is large enough
+        if id >= self.nodes.len() {
+            self.nodes.resize_with(id + 1, || None);
+        }
+
+        self.nodes[id] = Some(HnswNode {
+            id,
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            inbound: HashSet::new(),
+            level,
+        });
+        self.count += 1;
+
+        // If this is the first node, set it as entry point
+        if self.entry_point.is_none() {
+            self.entry_point = Some(id);
+            self.max_level = level;
+            return;
+        }
+
+        for (l, nearest) in candidates.iter().enumerate().rev() {
+            if nearest.is_empty() {
+                continue;
+            }
             let m = if l == 0 {
                 self.params.m_max0
             } else {
                 self.params.m
             };
 
-            let nearest =
-                self.search_layer(&vector, &[ep_id], self.params.ef_construction, l)?;
-
             // Select M closest neighbors
-            let neighbors = Self::select_neighbors_simple(&nearest, m);
+            let neighbors = Self::select_neighbors_simple(nearest, m);
 
             // Set the neighbors for this node at this layer
             if let Some(node) = &mut self.nodes[id] {
@@ -307,12 +686,15 @@ impl HnswGraph {
 
             // Add bidirectional connections
             for &neighbor_id in &neighbors {
+                self.link_inbound(id, neighbor_id);
                 // First, add the connection and check if pruning is needed
                 let needs_pruning = if let Some(neighbor_node) = &mut self.nodes[neighbor_id]
                 {
                     if l < neighbor_node.neighbors.len() {
                         neighbor_node.neighbors[l].push(id);
-                        neighbor_node.neighbors[l].len() > m
+                        let over = neighbor_node.neighbors[l].len() > m;
+                        self.link_inbound(neighbor_id, id);
+                        over
                     } else {
                         false
                     }
@@ -325,11 +707,6 @@ impl HnswGraph {
                     self.prune_neighbors(neighbor_id, l, m);
                 }
             }
-
-            // Update ep for next layer
-            if let Some(n) = nearest.first() {
-                ep_id = n.id;
-            }
         }
 
         // Update entry point if new node has a higher level
@@ -337,28 +714,67 @@ impl HnswGraph {
             self.entry_point = Some(id);
             self.max_level = level;
         }
-
-        Ok(())
     }
 
-    /// Remove a node from the graph (lazy deletion — removes from neighbor lists).
+    /// Remove a node from the graph and repair the neighborhoods it leaves behind.
+    ///
+    /// Every node that linked to the removed one loses that edge; at each layer
+    /// it is offered the removed node's former neighbors as replacements and its
+    /// list is re-selected down to the layer's capacity. This keeps the graph
+    /// navigable under repeated deletes. Inbound edges come from the node's
+    /// reverse adjacency, so a delete only touches the nodes that linked to it.
     pub fn remove(&mut self, id: usize) -> Result<()> {
         if id >= self.nodes.len() || self.nodes[id].is_none() {
             return Ok(());
         }
 
-        // Remove this node's ID from all its neighbors' neighbor lists
         if let Some(node) = self.nodes[id].take() {
-            for (layer, neighbors) in node.neighbors.iter().enumerate() {
-                for &neighbor_id in neighbors {
-                    if let Some(Some(neighbor_node)) = self.nodes.get_mut(neighbor_id) {
-                        if layer < neighbor_node.neighbors.len() {
-                            neighbor_node.neighbors[layer].retain(|&n| n != id);
+            self.count -= 1;
+
+            for &out in node.neighbors.iter().flatten() {
+                if let Some(Some(other)) = self.nodes.get_mut(out) {
+                    other.inbound.remove(&id);
+                }
+            }
+
+            // Drop every edge pointing at the removed node, remembering who lost one
+            let mut affected: Vec<(usize, usize)> = Vec::new();
+            for &nid in &node.inbound {
+                if let Some(Some(other)) = self.nodes.get_mut(nid) {
+                    for (layer, list) in other.neighbors.iter_mut().enumerate() {
+                        let before = list.len();
+                        list.retain(|&n| n != id);
+                        if list.len() != before {
+                            affected.push((nid, layer));
                         }
                     }
                 }
             }
-            self.count -= 1;
+            affected.sort_unstable();
+
+            // Reconnect: offer the removed node's neighbors as replacement edges
+            for (nid, layer) in affected {
+                let m = if layer == 0 {
+                    self.params.m_max0
+                } else {
+                    self.params.m
+                };
+                let replacements = node.neighbors.get(layer).map(Vec::as_slice).unwrap_or(&[]);
+                let mut added = Vec::new();
+                if let Some(other) = &mut self.nodes[nid] {
+                    let list = &mut other.neighbors[layer];
+                    for &candidate in replacements {
+                        if candidate != nid && !list.contains(&candidate) {
+                            list.push(candidate);
+                            added.push(candidate);
+                        }
+                    }
+                }
+                for candidate in added {
+                    self.link_inbound(nid, candidate);
+                }
+                self.prune_neighbors(nid, layer, m);
+            }
 
             // Update entry point if we removed it
             if self.entry_point == Some(id) {
@@ -380,6 +796,39 @@ impl HnswGraph {
         Ok(())
     }
 
+    /// Drop empty slots left by deletes, renumbering live nodes densely in
+    /// their existing order.
+    ///
+    /// Returns the old -> new id mapping for every live node; callers that
+    /// hold ids (e.g. `VectorStore`) must apply it. Edges are rewritten, so
+    /// search results are unchanged apart from the ids.
+    pub fn compact(&mut self) -> HashMap<usize, usize> {
+        let remap: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(new_id, node)| (node.id, new_id))
+            .collect();
+
+        let nodes: Vec<Option<HnswNode>> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .flatten()
+            .map(|mut node| {
+                node.id = remap[&node.id];
+                for list in &mut node.neighbors {
+                    *list = list.iter().filter_map(|n| remap.get(n).copied()).collect();
+                }
+                node.inbound = node.inbound.iter().filter_map(|n| remap.get(n).copied()).collect();
+                Some(node)
+            })
+            .collect();
+
+        self.nodes = nodes;
+        self.entry_point = self.entry_point.and_then(|ep| remap.get(&ep).copied());
+        remap
+    }
+
     /// SEARCH: Algorithm 5 from the HNSW paper.
     ///
     /// Search for the k nearest neighbors, using ef candidates.
@@ -389,6 +838,7 @@ impl HnswGraph {
         k: usize,
         ef: usize,
     ) -> Result<Vec<Neighbor>> {
+        self.check_dimension(query)?;
         let entry_point = match self.entry_point {
             Some(ep) => ep,
             None => return Ok(vec![]),
@@ -408,11 +858,153 @@ impl HnswGraph {
         let ef_actual = ef.max(k);
         let mut results = self.search_layer(query, &[ep_id], ef_actual, 0)?;
 
-        // Return top k
+        // Return top k, as the metric reports distances
         results.truncate(k);
+        if self.metric.higher_is_better() {
+            for n in &mut results {
+                n.distance = -n.distance;
+            }
+        }
         Ok(results)
     }
 
+    /// Convert the graph into its serializable form.
+    pub fn to_serialized(&self) -> SerializedHnswGraph {
+        SerializedHnswGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|slot| {
+                    slot.as_ref().map(|node| SerializedHnswNode {
+                        id: node.id,
+                        data: node.vector.as_slice().to_vec(),
+                        neighbors: node.neighbors.clone(),
+                        level: node.level,
+                    })
+                })
+                .collect(),
+            entry_point: self.entry_point,
+            max_level: self.max_level,
+            params: self.params.clone(),
+            metric: self.metric,
+            seed: self.seed,
+        }
+    }
+
+    /// Rebuild a graph from its serializable form, checking the params, that
+    /// every node has the same dimension and a level the params allow, that
+    /// neighbor references are in bounds, and that the entry point is a live
+    /// node on the top level.
+    ///
+    /// Edges to deleted slots are allowed: graphs saved before `remove` repaired
+    /// inbound edges can contain them, and search already skips deleted neighbors.
+    ///
+    /// A seeded graph is reseeded from its seed and slot count, so loading the
+    /// same file and making the same inserts always gives the same graph.
+    pub fn from_serialized(graph: SerializedHnswGraph) -> Result<Self> {
+        let invalid = |reason: String| VectorDbError::SerializationError(reason);
+        let live = |id: usize| matches!(graph.nodes.get(id), Some(Some(_)));
+        let slots = graph.nodes.len();
+        graph.params.validate()?;
+        let dimension = graph.nodes.iter().flatten().next().map(|n| n.data.len());
+
+        for (slot, node) in graph.nodes.iter().enumerate() {
+            let Some(node) = node else { continue };
+            if node.id != slot
+                || node.neighbors.len() != node.level + 1
+                || node.level > graph.max_level
+                || node.level >= graph.params.max_layers
+            {
+                return Err(invalid(format!("Malformed HNSW node at slot {}", slot)));
+            }
+            if Some(node.data.len()) != dimension {
+                return Err(invalid(format!(
+                    "HNSW node {} has dimension {}, expected {}",
+                    slot,
+                    node.data.len(),
+                    dimension.unwrap_or(0)
+                )));
+            }
+            if let Some(&bad) = node.neighbors.iter().flatten().find(|&&n| n >= slots) {
+                return Err(invalid(format!(
+                    "HNSW node {} references missing neighbor {}",
+                    slot, bad
+                )));
+            }
+        }
+        match graph.entry_point {
+            Some(ep) if !live(ep) => {
+                return Err(invalid(format!("HNSW entry point {} is missing", ep)));
+            }
+            Some(ep) if graph.nodes[ep].as_ref().map(|n| n.level) != Some(graph.max_level) => {
+                return Err(invalid(format!("HNSW entry point {} is not on the top level", ep)));
+            }
+            None if dimension.is_some() => {
+                return Err(invalid("HNSW graph has nodes but no entry point".to_string()));
+            }
+            _ => {}
+        }
+
+        let nodes: Vec<Option<HnswNode>> = graph
+            .nodes
+            .into_iter()
+            .map(|slot| {
+                slot.map(|node| HnswNode {
+                    id: node.id,
+                    vector: Vector::new(node.data),
+                    neighbors: node.neighbors,
+                    inbound: HashSet::new(),
+                    level: node.level,
+                })
+            })
+            .collect();
+        let edges: Vec<(usize, usize)> = nodes
+            .iter()
+            .flatten()
+            .flat_map(|node| node.neighbors.iter().flatten().map(move |&to| (node.id, to)))
+            .collect();
+        let mut nodes = nodes;
+        for (from, to) in edges {
+            if let Some(Some(node)) = nodes.get_mut(to) {
+                node.inbound.insert(from);
+            }
+        }
+        let count = nodes.iter().flatten().count();
+        let rng = match graph.seed {
+            Some(seed) => StdRng::seed_from_u64(seed ^ slots as u64),
+            None => StdRng::from_entropy(),
+        };
+
+        Ok(Self {
+            nodes,
+            entry_point: graph.entry_point,
+            max_level: graph.max_level,
+            params: graph.params,
+            metric: graph.metric,
+            rng,
+            seed: graph.seed,
+            count,
+            dimension,
+        })
+    }
+
+    /// Serialize the graph (nodes, neighbor lists, entry point, params) to bincode bytes.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        serialization::to_bincode(&self.to_serialized())
+    }
+
+    /// Deserialize a graph previously written by `serialize`, including ones
+    /// written before the seed was recorded.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let graph = match serialization::from_bincode::<SerializedHnswGraph>(bytes) {
+            Ok(graph) => graph,
+            Err(e) => serialization::from_bincode::<SerializedHnswGraphV1>(bytes)
+                .map_err(|_| e)?
+                .into(),
+        };
+        Self::from_serialized(graph)
+    }
+
     /// Search with a specific ef_search value (runtime tuning without rebuilding).
     pub fn search_with_ef(
         &self,
@@ -432,6 +1024,22 @@ mod tests {
         HnswParams::new(4, 32, 16)
     }
 
+    #[test]
+    fn test_search_higher_is_better_metric() {
+        let mut graph = HnswGraph::new(DistanceMetric::InnerProduct, make_params());
+        for i in 0..50 {
+            graph.insert(i, Vector::new(vec![i as f32, 1.0])).unwrap();
+        }
+
+        let results = graph.search_knn(&Vector::new(vec![1.0, 0.0]), 5, 50).unwrap();
+        let ids: Vec<usize> = results.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![49, 48, 47, 46, 45]);
+        assert_eq!(results[0].distance, 49.0);
+        assert!(results
+            .windows(2)
+            .all(|w| DistanceMetric::InnerProduct.is_better_than(w[0].distance, w[1].distance)));
+    }
+
     #[test]
     fn test_insert_single() {
         let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
@@ -503,6 +1111,67 @@ mod tests {
         assert!(ids.contains(&1));
     }
 
+    #[test]
+    fn test_params_validate() {
+        assert!(HnswParams::default().validate().is_ok());
+        assert!(make_params().validate().is_ok());
+
+        let cases = [
+            HnswParams::new(0, 0, 0),
+            HnswParams::new(16, 8, 50),
+            HnswParams::new(16, 200, 0),
+            HnswParams {
+                max_layers: 0,
+                ..HnswParams::default()
+            },
+            // ml = 1 / ln(1) is infinite
+            HnswParams::new(1, 10, 10),
+        ];
+        for params in cases {
+            assert!(
+                matches!(params.validate(), Err(VectorDbError::IndexError(_))),
+                "{:?} should be rejected",
+                params
+            );
+        }
+    }
+
+    #[test]
+    fn test_params_builder() {
+        let params = HnswParams::builder()
+            .m(16)
+            .m_max0(24)
+            .max_layers(8)
+            .ef_construction(100)
+            .ef_search(40)
+            .build()
+            .unwrap();
+        assert_eq!(params.m, 16);
+        assert_eq!(params.m_max0, 24);
+        assert_eq!(params.max_layers, 8);
+        assert_eq!(params.ef_construction, 100);
+        assert_eq!(params.ef_search, 40);
+        assert_eq!(params.ml, 1.0 / 16f64.ln());
+
+        // Unset fields fall back to the defaults, derived from m where they depend on it
+        let params = HnswParams::builder().m(8).build().unwrap();
+        assert_eq!(params.m_max0, 16);
+        assert_eq!(params.ml, 1.0 / 8f64.ln());
+        assert_eq!(params.max_layers, HnswParams::default().max_layers);
+        assert_eq!(params.ef_search, HnswParams::default().ef_search);
+        let default = HnswParams::builder().build().unwrap();
+        assert_eq!(format!("{:?}", default), format!("{:?}", HnswParams::default()));
+
+        for builder in [
+            HnswParams::builder().m(1),
+            HnswParams::builder().m_max0(0),
+            HnswParams::builder().max_layers(0),
+            HnswParams::builder().m(32).ef_construction(16),
+        ] {
+            assert!(matches!(builder.build(), Err(VectorDbError::IndexError(_))));
+        }
+    }
+
     #[test]
     fn test_remove() {
         let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
@@ -536,4 +1205,266 @@ mod tests {
             .unwrap();
         assert!(!results.is_empty());
     }
+
+    #[test]
+    fn test_remove_leaves_no_dangling_edges() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        for i in 0..200 {
+            graph
+                .insert(i, Vector::new(vec![i as f32, (i * 13 % 17) as f32]))
+                .unwrap();
+        }
+        for i in (0..200).step_by(2) {
+            graph.remove(i).unwrap();
+        }
+        assert_eq!(graph.len(), 100);
+
+        for node in graph.nodes.iter().flatten() {
+            for (layer, list) in node.neighbors.iter().enumerate() {
+                for &n in list {
+                    let neighbor = graph.nodes[n].as_ref().expect("edge to deleted node");
+                    assert!(neighbor.level >= layer);
+                }
+            }
+            // Survivors keep at least one edge at their base layer
+            assert!(!node.neighbors[0].is_empty());
+        }
+    }
+
+    /// Every node's `inbound` set holds exactly the nodes that link to it.
+    fn assert_inbound_matches_edges(graph: &HnswGraph) {
+        let mut expected: HashMap<usize, HashSet<usize>> = HashMap::new();
+        for node in graph.nodes.iter().flatten() {
+            for &to in node.neighbors.iter().flatten() {
+                expected.entry(to).or_default().insert(node.id);
+            }
+        }
+        for node in graph.nodes.iter().flatten() {
+            assert_eq!(node.inbound, expected.remove(&node.id).unwrap_or_default());
+        }
+    }
+
+    #[test]
+    fn test_reverse_adjacency_tracks_edges() {
+        let mut graph = HnswGraph::with_seed(DistanceMetric::Euclidean, make_params(), 3);
+        for i in 0..300 {
+            graph
+                .insert(i, Vector::new(vec![i as f32, (i * 7 % 11) as f32]))
+                .unwrap();
+        }
+        assert_inbound_matches_edges(&graph);
+
+        for i in (0..300).filter(|i| i % 3 != 0) {
+            graph.remove(i).unwrap();
+        }
+        assert_inbound_matches_edges(&graph);
+
+        graph.compact();
+        assert_inbound_matches_edges(&graph);
+
+        let loaded = HnswGraph::deserialize(&graph.serialize().unwrap()).unwrap();
+        assert_inbound_matches_edges(&loaded);
+    }
+
+    #[test]
+    fn test_compact_after_churn() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        let mut next_id = 0;
+        for round in 0..5 {
+            for _ in 0..40 {
+                let x = next_id as f32;
+                graph.insert(next_id, Vector::new(vec![x, x % 5.0])).unwrap();
+                next_id += 1;
+            }
+            // Delete most of this round's inserts
+            for id in (round * 40)..(round * 40 + 30) {
+                graph.remove(id).unwrap();
+            }
+        }
+        assert_eq!(graph.len(), 50);
+        assert_eq!(graph.slot_count(), 200);
+
+        let query = Vector::new(vec![95.0, 0.0]);
+        let before = graph.search_knn(&query, 5, 32).unwrap();
+
+        let remap = graph.compact();
+        assert_eq!(remap.len(), 50);
+        assert_eq!(graph.slot_count(), 50);
+        assert_eq!(graph.len(), 50);
+
+        let after = graph.search_knn(&query, 5, 32).unwrap();
+        let expected: Vec<(usize, f32)> =
+            before.iter().map(|n| (remap[&n.id], n.distance)).collect();
+        let actual: Vec<(usize, f32)> = after.iter().map(|n| (n.id, n.distance)).collect();
+        assert_eq!(actual, expected);
+
+        // The compacted graph keeps accepting inserts at the next dense id
+        graph.insert(50, Vector::new(vec![95.0, 0.0])).unwrap();
+        assert_eq!(graph.search_knn(&query, 1, 32).unwrap()[0].id, 50);
+    }
+
+    #[test]
+    fn test_insert_batch_links_every_node() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        let items: Vec<(usize, Vector)> = (0..500)
+            .map(|i| (i, Vector::new(vec![(i % 25) as f32, (i / 25) as f32])))
+            .collect();
+        graph.insert_batch(items).unwrap();
+        assert_eq!(graph.len(), 500);
+
+        for node in graph.nodes.iter().flatten() {
+            assert!(!node.neighbors[0].is_empty());
+        }
+        let results = graph.search_knn(&Vector::new(vec![7.0, 11.0]), 1, 32).unwrap();
+        assert_eq!(results[0].id, 11 * 25 + 7);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        for i in 0..5 {
+            graph.insert(i, Vector::new(vec![i as f32])).unwrap();
+        }
+
+        let dot = graph.to_dot(0);
+        assert!(dot.starts_with("digraph hnsw_layer_0 {"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        for i in 0..5 {
+            assert!(dot.contains(&format!("n{} [label=\"{}\"];", i, i)));
+        }
+
+        let expected_edges: usize = graph
+            .nodes
+            .iter()
+            .flatten()
+            .map(|n| n.neighbors[0].len())
+            .sum();
+        let edge_lines = dot.lines().filter(|l| l.contains(" -> ")).count();
+        assert_eq!(edge_lines, expected_edges);
+        // With m_max0 well above 4, every node links to every other one
+        assert_eq!(edge_lines, 20);
+
+        // Layers above the top are empty but still valid
+        let top = graph.to_dot(graph.max_level + 1);
+        assert_eq!(top, format!("digraph hnsw_layer_{} {{\n}}\n", graph.max_level + 1));
+    }
+
+    #[test]
+    fn test_seeded_graphs_are_identical() {
+        let build = || {
+            let mut graph = HnswGraph::with_seed(DistanceMetric::Euclidean, make_params(), 42);
+            for i in 0..300 {
+                let v = Vector::new(vec![(i * 37 % 101) as f32, (i * 11 % 53) as f32]);
+                graph.insert(i, v).unwrap();
+            }
+            graph
+        };
+        let a = build();
+        let b = build();
+
+        assert_eq!(a.entry_point, b.entry_point);
+        assert_eq!(a.max_level, b.max_level);
+        for q in 0..20 {
+            let query = Vector::new(vec![q as f32 * 5.0, q as f32 * 2.5]);
+            assert_eq!(
+                a.search_knn(&query, 10, 16).unwrap(),
+                b.search_knn(&query, 10, 16).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        for i in 0..50 {
+            graph
+                .insert(i, Vector::new(vec![i as f32, (i % 7) as f32, (i % 3) as f32]))
+                .unwrap();
+        }
+        graph.remove(10).unwrap();
+
+        let bytes = graph.serialize().unwrap();
+        let loaded = HnswGraph::deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded.len(), graph.len());
+        assert_eq!(loaded.entry_point, graph.entry_point);
+        assert_eq!(loaded.max_level, graph.max_level);
+
+        for q in 0..10 {
+            let query = Vector::new(vec![q as f32 * 4.5, 2.0, 1.0]);
+            let a = graph.search_knn(&query, 5, 16).unwrap();
+            let b = loaded.search_knn(&query, 5, 16).unwrap();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_rejects_dangling_neighbor() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        graph.insert(0, Vector::new(vec![0.0])).unwrap();
+        graph.insert(1, Vector::new(vec![1.0])).unwrap();
+
+        let mut serialized = graph.to_serialized();
+        serialized.nodes[0].as_mut().unwrap().neighbors[0].push(42);
+        assert!(matches!(
+            HnswGraph::from_serialized(serialized),
+            Err(VectorDbError::SerializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_rejects_bad_dimension_and_params() {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, make_params());
+        graph.insert(0, Vector::new(vec![0.0, 0.0])).unwrap();
+        graph.insert(1, Vector::new(vec![1.0, 1.0])).unwrap();
+
+        let mut serialized = graph.to_serialized();
+        serialized.nodes[1].as_mut().unwrap().data.push(2.0);
+        assert!(matches!(
+            HnswGraph::from_serialized(serialized),
+            Err(VectorDbError::SerializationError(_))
+        ));
+
+        let mut serialized = graph.to_serialized();
+        serialized.params.m = 0;
+        assert!(HnswGraph::from_serialized(serialized).is_err());
+    }
+
+    #[test]
+    fn test_seeded_graph_reloads_reproducibly() {
+        let mut graph = HnswGraph::with_seed(DistanceMetric::Euclidean, make_params(), 7);
+        for i in 0..30 {
+            graph.insert(i, Vector::new(vec![i as f32, (i % 5) as f32])).unwrap();
+        }
+        let bytes = graph.serialize().unwrap();
+
+        let mut a = HnswGraph::deserialize(&bytes).unwrap();
+        let mut b = HnswGraph::deserialize(&bytes).unwrap();
+        assert_eq!(a.seed, Some(7));
+        for i in 30..60 {
+            let v = Vector::new(vec![i as f32, (i % 5) as f32]);
+            a.insert(i, v.clone()).unwrap();
+            b.insert(i, v).unwrap();
+        }
+        assert_eq!(a.serialize().unwrap(), b.serialize().unwrap());
+    }
+
+    #[test]
+    fn test_deserialize_reads_graphs_without_seed() {
+        let mut graph = HnswGraph::with_seed(DistanceMetric::Euclidean, make_params(), 7);
+        graph.insert(0, Vector::new(vec![0.0])).unwrap();
+        graph.insert(1, Vector::new(vec![1.0])).unwrap();
+
+        let s = graph.to_serialized();
+        let legacy = SerializedHnswGraphV1 {
+            nodes: s.nodes,
+            entry_point: s.entry_point,
+            max_level: s.max_level,
+            params: s.params,
+            metric: s.metric,
+        };
+        let loaded = HnswGraph::deserialize(&serialization::to_bincode(&legacy).unwrap()).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.seed, None);
+    }
 }
@@ -3,11 +3,15 @@
 pub mod graph;
 pub mod neighbor_queue;
 
-pub use graph::{HnswGraph, HnswParams};
+pub use graph::{HnswGraph, HnswParams, HnswParamsBuilder, HnswStats, NodeInfo};
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
 
 use crate::distance::DistanceMetric;
-use crate::error::Result;
-use crate::index::Index;
+use crate::error::{Result, VectorDbError};
+use crate::index::{Index, PersistIndex};
 use crate::vector::Vector;
 
 /// An HNSW-based approximate nearest neighbor index.
@@ -25,20 +29,55 @@ impl HnswIndex {
     }
 
     /// Create a new HNSW index with custom parameters.
-    pub fn with_params(metric: DistanceMetric, params: HnswParams) -> Self {
-        Self {
+    /// Fails with `IndexError` if the parameters don't pass `HnswParams::validate`.
+    pub fn with_params(metric: DistanceMetric, params: HnswParams) -> Result<Self> {
+        params.validate()?;
+        Ok(Self {
             graph: HnswGraph::new(metric, params),
-        }
+        })
+    }
+
+    /// Create a new HNSW index with custom parameters and a fixed RNG seed,
+    /// making builds reproducible for a given insert order.
+    pub fn with_seed(metric: DistanceMetric, params: HnswParams, seed: u64) -> Result<Self> {
+        params.validate()?;
+        Ok(Self {
+            graph: HnswGraph::with_seed(metric, params, seed),
+        })
+    }
+
+    /// Wrap an existing graph (e.g. one loaded from disk).
+    pub fn from_graph(graph: HnswGraph) -> Self {
+        Self { graph }
+    }
+
+    /// Structural statistics of the graph (layer sizes, degrees, tombstones).
+    pub fn stats(&self) -> HnswStats {
+        self.graph.stats()
+    }
+
+    /// Level and per-layer neighbor counts of one node (see `HnswGraph::node_info`).
+    pub fn node_info(&self, id: usize) -> Option<NodeInfo> {
+        self.graph.node_info(id)
     }
 
-    /// Build the index from a batch of vectors (parallel distance computation with rayon).
-    /// Vectors are inserted sequentially into the graph, but distance computations
-    /// during search_layer use rayon for parallelism on large neighbor lists.
+    /// `index` as an `HnswIndex`, if that's what it is. Lets code generic over
+    /// `Index` (the server, the CLI) report graph details such as `stats`
+    /// without the other index types knowing about them.
+    pub fn downcast<I: Index + 'static>(index: &I) -> Option<&HnswIndex> {
+        (index as &dyn Any).downcast_ref()
+    }
+
+    /// Get a reference to the underlying graph.
+    pub fn graph(&self) -> &HnswGraph {
+        &self.graph
+    }
+
+    /// Build the index from a batch of vectors. Neighbor searches run in
+    /// parallel with rayon while graph updates stay serial; see
+    /// `HnswGraph::insert_batch`.
     pub fn build_batch(&mut self, vectors: Vec<(usize, Vector)>) -> Result<()> {
-        for (id, vector) in vectors {
-            self.graph.insert(id, vector)?;
-        }
-        Ok(())
+        self.graph.insert_batch(vectors)
     }
 
     /// Search with a specific ef value for runtime tuning.
@@ -58,17 +97,29 @@ impl Index for HnswIndex {
         self.graph.insert(id, vector)
     }
 
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        self.build_batch(items)
+    }
+
     fn remove(&mut self, id: usize) -> Result<()> {
         self.graph.remove(id)
     }
 
+    fn compact(&mut self) -> HashMap<usize, usize> {
+        self.graph.compact()
+    }
+
     fn get_vector(&self, id: usize) -> Option<&Vector> {
         self.graph.get_vector(id)
     }
 
+    /// Searches with the configured `HnswParams::ef_search`.
     fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
-        let results = self.graph.search_knn(query, k, 50)?; // default ef_search=50
-        Ok(results.into_iter().map(|n| (n.id, n.distance)).collect())
+        HnswIndex::search_with_ef(self, query, k, self.graph.params().ef_search)
+    }
+
+    fn search_with_ef(&self, query: &Vector, k: usize, ef: usize) -> Result<Vec<(usize, f32)>> {
+        HnswIndex::search_with_ef(self, query, k, ef)
     }
 
     fn metric(&self) -> DistanceMetric {
@@ -78,6 +129,30 @@ impl Index for HnswIndex {
     fn len(&self) -> usize {
         self.graph.len()
     }
+
+    fn memory_usage_bytes(&self) -> usize {
+        self.graph.memory_usage_bytes()
+    }
+}
+
+/// Stored with `HnswGraph::serialize`, so the graph is reloaded as built
+/// rather than re-inserted.
+impl PersistIndex for HnswIndex {
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.graph.serialize()?)?;
+        Ok(())
+    }
+
+    fn load(path: &Path, metric: DistanceMetric) -> Result<Self> {
+        let graph = HnswGraph::deserialize(&std::fs::read(path)?)?;
+        if graph.metric() != metric {
+            return Err(VectorDbError::MetricMismatch {
+                stored: graph.metric(),
+                requested: metric,
+            });
+        }
+        Ok(Self::from_graph(graph))
+    }
 }
 
 #[cfg(test)]
@@ -85,6 +160,86 @@ mod tests {
     use super::*;
     use crate::storage::VectorStore;
 
+    #[test]
+    fn test_node_info_respects_params() {
+        let params = HnswParams::new(4, 32, 32);
+        let mut index = HnswIndex::with_seed(DistanceMetric::Euclidean, params.clone(), 3).unwrap();
+        for i in 0..300 {
+            let angle = i as f32 * 0.1;
+            index.add(i, Vector::new(vec![angle.cos() * i as f32, angle.sin()])).unwrap();
+        }
+
+        let mut entry_points = 0;
+        for id in 0..300 {
+            let info = index.node_info(id).unwrap();
+            assert!(info.level < params.max_layers);
+            assert_eq!(info.neighbor_counts.len(), info.level + 1);
+            assert!(info.neighbor_counts[0] <= params.m_max0);
+            assert!(info.neighbor_counts[1..].iter().all(|&d| d <= params.m));
+            entry_points += usize::from(info.is_entry_point);
+        }
+        assert_eq!(entry_points, 1);
+
+        index.remove(7).unwrap();
+        assert!(index.node_info(7).is_none());
+        assert!(index.node_info(1000).is_none());
+        let flat = crate::flat_index::FlatIndex::new(DistanceMetric::Euclidean);
+        assert!(HnswIndex::downcast(&flat).is_none());
+        assert!(HnswIndex::downcast(&index).is_some());
+    }
+
+    #[test]
+    fn test_hnsw_index_save_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("hnsw.bin");
+        let mut index = HnswIndex::new(DistanceMetric::Euclidean);
+        for i in 0..200 {
+            let x = i as f32;
+            index.add(i, Vector::new(vec![x.sin(), x.cos(), x * 0.01])).unwrap();
+        }
+        index.remove(17).unwrap();
+        index.save(&path).unwrap();
+
+        let loaded = HnswIndex::load(&path, DistanceMetric::Euclidean).unwrap();
+        assert_eq!(loaded.len(), 199);
+        for i in 0..10 {
+            let query = Vector::new(vec![(i as f32).cos(), 0.5, 1.0]);
+            assert_eq!(index.search(&query, 10).unwrap(), loaded.search(&query, 10).unwrap());
+        }
+        assert!(matches!(
+            HnswIndex::load(&path, DistanceMetric::Cosine),
+            Err(VectorDbError::MetricMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hnsw_index_rejects_mismatched_dimension() {
+        let mismatch = |r: Result<_>| {
+            matches!(
+                r,
+                Err(VectorDbError::DimensionMismatch {
+                    expected: 3,
+                    actual: 2
+                })
+            )
+        };
+
+        let mut index = HnswIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![1.0, 0.0, 0.0])).unwrap();
+        assert!(mismatch(index.add(1, Vector::new(vec![1.0, 0.0]))));
+        assert!(mismatch(index.search(&Vector::new(vec![1.0, 0.0]), 1).map(|_| ())));
+        assert_eq!(index.len(), 1);
+
+        // A bad item anywhere in a batch rejects the whole batch
+        let batch = vec![
+            (1, Vector::new(vec![0.0, 1.0, 0.0])),
+            (2, Vector::new(vec![0.0, 1.0])),
+        ];
+        assert!(mismatch(index.add_batch(batch)));
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.graph().dimension(), Some(3));
+    }
+
     #[test]
     fn test_hnsw_index_via_trait() {
         let mut index = HnswIndex::new(DistanceMetric::Euclidean);
@@ -110,10 +265,8 @@ mod tests {
 
     #[test]
     fn test_hnsw_via_vectorstore() {
-        let index = HnswIndex::with_params(
-            DistanceMetric::Euclidean,
-            HnswParams::new(4, 32, 16),
-        );
+        let index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(4, 32, 16)).unwrap();
         let mut store = VectorStore::with_index(index);
 
         store
@@ -133,12 +286,37 @@ mod tests {
         assert_eq!(results[0].id, "v1");
     }
 
+    #[test]
+    fn test_hnsw_add_batch_via_trait() {
+        let mut index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(4, 32, 16)).unwrap();
+        let items: Vec<(usize, Vector)> = (0..10)
+            .map(|i| (i, Vector::new(vec![i as f32, 0.0])))
+            .collect();
+        Index::add_batch(&mut index, items).unwrap();
+        assert_eq!(index.len(), 10);
+
+        let results = index.search(&Vector::new(vec![3.0, 0.0]), 1).unwrap();
+        assert_eq!(results[0].0, 3);
+    }
+
+    #[test]
+    fn test_hnsw_memory_usage_grows() {
+        let mut index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(4, 32, 16)).unwrap();
+        let mut previous = index.memory_usage_bytes();
+        for i in 0..50 {
+            index.add(i, Vector::new(vec![i as f32; 8])).unwrap();
+            let current = index.memory_usage_bytes();
+            assert!(current > previous);
+            previous = current;
+        }
+    }
+
     #[test]
     fn test_hnsw_delete_via_vectorstore() {
-        let index = HnswIndex::with_params(
-            DistanceMetric::Euclidean,
-            HnswParams::new(4, 32, 16),
-        );
+        let index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(4, 32, 16)).unwrap();
         let mut store = VectorStore::with_index(index);
 
         store
@@ -152,4 +330,80 @@ mod tests {
         store.delete("v1").unwrap();
         assert_eq!(store.len(), 1);
     }
+
+    #[test]
+    fn test_vectorstore_compact() {
+        let index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(8, 64, 32)).unwrap();
+        let mut store = VectorStore::with_index(index);
+        for round in 0..4 {
+            for i in 0..50 {
+                let mut meta = crate::storage::Metadata::new();
+                meta.insert("round".to_string(), round.to_string());
+                let v = Vector::new(vec![i as f32, round as f32]);
+                store
+                    .insert_with_metadata(format!("r{}-{}", round, i), v, meta)
+                    .unwrap();
+            }
+            for i in 0..40 {
+                store.delete(&format!("r{}-{}", round, i)).unwrap();
+            }
+        }
+        assert_eq!(store.index().graph().slot_count(), 200);
+
+        // Off the grid, so no two of the top 5 are tied
+        let query = Vector::new(vec![45.3, 2.2]);
+        let before = store.search(&query, 5).unwrap();
+
+        store.compact();
+        assert_eq!(store.index().graph().slot_count(), 40);
+        assert_eq!(store.len(), 40);
+
+        let after = store.search(&query, 5).unwrap();
+        assert_eq!(
+            before.iter().map(|r| (&r.id, r.distance)).collect::<Vec<_>>(),
+            after.iter().map(|r| (&r.id, r.distance)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            store.get_metadata("r3-45").unwrap().get("round"),
+            Some(&"3".to_string())
+        );
+        assert_eq!(store.get("r1-49"), Some(&Vector::new(vec![49.0, 1.0])));
+
+        // New inserts land after the compacted range and stay findable
+        let fresh = Vector::new(vec![45.0, 2.5]);
+        store.insert("fresh", fresh.clone()).unwrap();
+        assert_eq!(store.index().graph().slot_count(), 41);
+        assert_eq!(store.search(&fresh, 1).unwrap()[0].id, "fresh");
+    }
+
+    #[test]
+    fn test_stats() {
+        let params = HnswParams::new(4, 32, 16);
+        let m_max0 = params.m_max0;
+        let mut index = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
+        for i in 0..300 {
+            index
+                .add(i, Vector::new(vec![(i % 17) as f32, (i / 17) as f32]))
+                .unwrap();
+        }
+        index.remove(5).unwrap();
+        index.remove(6).unwrap();
+
+        let stats = index.stats();
+        assert_eq!(stats.node_count, 298);
+        assert_eq!(stats.tombstones, 2);
+        assert_eq!(stats.layer_node_counts[0], 298);
+        assert_eq!(stats.layer_node_counts.len(), stats.max_level + 1);
+        assert!(stats.min_degree_layer0 >= 1);
+        assert!(stats.max_degree_layer0 <= m_max0);
+        assert!(stats.avg_degree_layer0 <= m_max0 as f64);
+        assert_eq!(HnswIndex::downcast(&index).map(HnswIndex::stats), Some(stats));
+    }
+
+    #[test]
+    fn test_with_params_rejects_invalid() {
+        let err = HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(0, 0, 0));
+        assert!(matches!(err, Err(crate::error::VectorDbError::IndexError(_))));
+    }
 }
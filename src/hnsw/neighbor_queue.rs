@@ -59,6 +59,7 @@ impl Ord for Reversed {
 }
 
 /// Max-heap of neighbors (largest distance on top). Used as the result set bounded by ef.
+#[derive(Default)]
 pub struct MaxHeap {
     heap: BinaryHeap<Neighbor>,
 }
@@ -107,6 +108,7 @@ impl MaxHeap {
 }
 
 /// Min-heap of neighbors (smallest distance on top). Used as the candidate set.
+#[derive(Default)]
 pub struct MinHeap {
     heap: BinaryHeap<Reversed>,
 }
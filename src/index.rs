@@ -1,9 +1,16 @@
 //! Index trait for pluggable search backends
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use crate::distance::DistanceMetric;
-use crate::error::Result;
+use crate::error::{Result, VectorDbError};
 use crate::vector::Vector;
 
+/// Over-fetch multiplier used by post-filtering searches unless the caller
+/// picks another.
+pub const DEFAULT_OVER_FETCH: usize = 3;
+
 /// A search index that supports insertion, removal, and k-NN search.
 ///
 /// Implementations use `usize` internal IDs for cache efficiency;
@@ -12,6 +19,15 @@ pub trait Index {
     /// Add a vector with the given internal ID.
     fn add(&mut self, id: usize, vector: Vector) -> Result<()>;
 
+    /// Add many vectors at once. Implementations may override this with a
+    /// faster bulk-loading path; the default adds them one by one.
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        for (id, vector) in items {
+            self.add(id, vector)?;
+        }
+        Ok(())
+    }
+
     /// Remove the vector with the given internal ID.
     fn remove(&mut self, id: usize) -> Result<()>;
 
@@ -19,12 +35,71 @@ pub trait Index {
     /// Returns a Vec of `(id, distance)` pairs sorted by distance ascending.
     fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>>;
 
+    /// Search with an explicit search-beam width `ef`, trading latency for recall.
+    ///
+    /// Only meaningful for graph indexes such as HNSW; exhaustive indexes
+    /// (e.g. `FlatIndex`) ignore `ef` and fall back to `search`.
+    fn search_with_ef(&self, query: &Vector, k: usize, ef: usize) -> Result<Vec<(usize, f32)>> {
+        let _ = ef;
+        self.search(query, k)
+    }
+
+    /// Search for the `k` nearest neighbors of `query` whose IDs satisfy `predicate`.
+    ///
+    /// The default post-filters an over-fetched `search` (`over_fetch` times `k`),
+    /// so it can return fewer than `k` results for selective predicates. Indexes
+    /// that can skip IDs during the scan should override this with true
+    /// pre-filtering and ignore `over_fetch`.
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        let fetch_k = k.saturating_mul(over_fetch).max(k).min(self.len());
+        Ok(self
+            .search(query, fetch_k)?
+            .into_iter()
+            .filter(|(id, _)| predicate(*id))
+            .take(k)
+            .collect())
+    }
+
+    /// Whether `search_filtered` applies the predicate during the scan and
+    /// ignores `over_fetch`, so retrying with a larger over-fetch can't find
+    /// more matches. False for the post-filtering default.
+    fn prefilters(&self) -> bool {
+        false
+    }
+
+    /// Reclaim space left by removed vectors, possibly renumbering IDs.
+    ///
+    /// Returns an old -> new mapping for IDs that changed; IDs missing from
+    /// the map keep their value. The default does nothing.
+    fn compact(&mut self) -> HashMap<usize, usize> {
+        HashMap::new()
+    }
+
     /// Retrieve a vector by its internal ID.
+    /// Indexes that don't keep full-precision vectors return `None`.
     fn get_vector(&self, id: usize) -> Option<&Vector>;
 
+    /// An owned copy of the vector stored under `id`, decoded if the index
+    /// keeps it in another form (quantized, bit-packed, on disk). Used
+    /// wherever a store needs every vector back, e.g. for snapshots and
+    /// exports; `None` only for unknown IDs, unless the index can't recover
+    /// its vectors at all.
+    fn reconstruct_vector(&self, id: usize) -> Option<Vector> {
+        self.get_vector(id).cloned()
+    }
+
     /// The distance metric used by this index.
     fn metric(&self) -> DistanceMetric;
 
+    /// Approximate heap memory held by this index, in bytes.
+    fn memory_usage_bytes(&self) -> usize;
+
     /// The number of vectors in this index.
     fn len(&self) -> usize;
 
@@ -33,3 +108,43 @@ pub trait Index {
         self.len() == 0
     }
 }
+
+/// An index that can be written to a single file and read back, without going
+/// through the storage engine.
+///
+/// Separate from `Index` because `load` returns `Self`, which would keep
+/// `Index` from being usable as a trait object.
+pub trait PersistIndex: Index + Sized {
+    /// Write the index to `path`, replacing any existing file.
+    fn save(&self, path: &Path) -> Result<()>;
+
+    /// Read an index written by `save`. Indexes that record their metric fail
+    /// with `MetricMismatch` if it differs from `metric`.
+    fn load(path: &Path, metric: DistanceMetric) -> Result<Self>;
+}
+
+/// Fail with `DimensionMismatch` if `vector` doesn't match `expected`, the
+/// dimension an index fixed on its first insert.
+pub(crate) fn check_dimension(expected: Option<usize>, vector: &Vector) -> Result<()> {
+    match expected {
+        Some(expected) if vector.dimension() != expected => Err(VectorDbError::DimensionMismatch {
+            expected,
+            actual: vector.dimension(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// `check_dimension` for a whole batch, before any of it is added. Returns the
+/// index's dimension once the batch is in: `expected`, or else the first
+/// item's.
+pub(crate) fn check_batch_dimension(
+    expected: Option<usize>,
+    items: &[(usize, Vector)],
+) -> Result<Option<usize>> {
+    let dimension = expected.or_else(|| items.first().map(|(_, v)| v.dimension()));
+    for (_, vector) in items {
+        check_dimension(dimension, vector)?;
+    }
+    Ok(dimension)
+}
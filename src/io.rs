@@ -0,0 +1,179 @@
+//! Bulk import from external formats.
+
+use std::io::Read;
+
+use crate::error::{Result, VectorDbError};
+use crate::index::Index;
+use crate::storage::{BatchInsertItem, Metadata, VectorStore};
+use crate::vector::Vector;
+
+/// Parse a CSV file with a header row into batch items.
+///
+/// `id_col` names the column holding vector IDs and `meta_cols` the columns
+/// copied into metadata. `vector_cols` lists the vector components in order;
+/// if empty, every column that isn't the ID or metadata is used, in header order.
+///
+/// Rows shorter than the header are accepted, but a row whose vector ends up a
+/// different length than the first row's is rejected with `DimensionMismatch`.
+pub fn read_csv<R: Read>(
+    reader: R,
+    id_col: &str,
+    vector_cols: &[&str],
+    meta_cols: &[&str],
+) -> Result<Vec<BatchInsertItem>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            VectorDbError::SerializationError(format!("CSV has no column '{}'", name))
+        })
+    };
+
+    let id_idx = column(id_col)?;
+    let meta_idx = meta_cols
+        .iter()
+        .map(|name| column(name).map(|idx| (name.to_string(), idx)))
+        .collect::<Result<Vec<_>>>()?;
+    let vector_idx: Vec<usize> = if vector_cols.is_empty() {
+        (0..headers.len())
+            .filter(|&i| i != id_idx && meta_idx.iter().all(|(_, m)| *m != i))
+            .collect()
+    } else {
+        vector_cols
+            .iter()
+            .map(|name| column(name))
+            .collect::<Result<_>>()?
+    };
+
+    let mut items = Vec::new();
+    let mut dimension = None;
+
+    for (row_no, record) in reader.records().enumerate() {
+        let record = record.map_err(csv_error)?;
+        // Header is line 1
+        let line = row_no + 2;
+
+        let id = record
+            .get(id_idx)
+            .ok_or_else(|| VectorDbError::InvalidVector {
+                reason: format!("line {}: missing id", line),
+            })?;
+
+        let data = vector_idx
+            .iter()
+            .filter_map(|&i| record.get(i))
+            .map(|field| {
+                field
+                    .parse::<f32>()
+                    .map_err(|_| VectorDbError::InvalidVector {
+                        reason: format!("line {}: '{}' is not a number", line, field),
+                    })
+            })
+            .collect::<Result<Vec<f32>>>()?;
+
+        let expected = *dimension.get_or_insert(data.len());
+        if data.len() != expected {
+            return Err(VectorDbError::DimensionMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        let mut metadata = Metadata::new();
+        for (name, idx) in &meta_idx {
+            if let Some(value) = record.get(*idx) {
+                metadata.insert(name.clone(), value.to_string());
+            }
+        }
+
+        items.push(BatchInsertItem {
+            id: id.to_string(),
+            vector: Vector::new(data),
+            metadata,
+        });
+    }
+
+    Ok(items)
+}
+
+fn csv_error(e: csv::Error) -> VectorDbError {
+    VectorDbError::SerializationError(e.to_string())
+}
+
+impl<I: Index> VectorStore<I> {
+    /// Insert every row of a CSV file (see [`read_csv`] for the column rules).
+    /// Returns the number of rows imported.
+    pub fn import_csv<R: Read>(
+        &mut self,
+        reader: R,
+        id_col: &str,
+        vector_cols: &[&str],
+        meta_cols: &[&str],
+    ) -> Result<usize> {
+        let items = read_csv(reader, id_col, vector_cols, meta_cols)?;
+        let count = items.len();
+        self.insert_batch(items)?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::DistanceMetric;
+
+    const CSV: &str = "\
+id,dim0,dim1,dim2,label
+a,1.0,0.0,0.0,cat
+b,0.0,1.0,0.0,dog
+c,0.9,0.1,0.0,cat
+";
+
+    #[test]
+    fn test_import_csv_with_metadata() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let count = store
+            .import_csv(CSV.as_bytes(), "id", &["dim0", "dim1", "dim2"], &["label"])
+            .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(store.dimension(), Some(3));
+        assert_eq!(
+            store.get_metadata("b").unwrap().get("label"),
+            Some(&"dog".to_string())
+        );
+
+        let results = store.search(&Vector::new(vec![1.0, 0.0, 0.0]), 2).unwrap();
+        assert_eq!(results[0].id, "a");
+        assert_eq!(results[1].id, "c");
+    }
+
+    #[test]
+    fn test_import_csv_infers_vector_columns() {
+        let items = read_csv(CSV.as_bytes(), "id", &[], &["label"]).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2].vector.as_slice(), &[0.9, 0.1, 0.0]);
+    }
+
+    #[test]
+    fn test_import_csv_rejects_short_row() {
+        let csv = "id,dim0,dim1\na,1.0,2.0\nb,3.0\n";
+        let err = read_csv(csv.as_bytes(), "id", &["dim0", "dim1"], &[]).unwrap_err();
+        assert!(matches!(
+            err,
+            VectorDbError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn test_import_csv_unknown_column() {
+        let err = read_csv(CSV.as_bytes(), "key", &[], &[]).unwrap_err();
+        assert!(err.to_string().contains("'key'"));
+    }
+}
@@ -0,0 +1,364 @@
+//! IVF (inverted file) index: a coarse k-means quantizer over posting lists.
+//!
+//! Vectors are bucketed by their nearest centroid; a search ranks the centroids
+//! against the query and brute-forces only the `nprobe` closest buckets.
+//!
+//! Trade-offs compared to the other indexes:
+//! - Build is cheap — a few k-means passes over a training sample plus one
+//!   centroid lookup per vector — far faster than HNSW construction.
+//! - Search costs `nlist` centroid distances plus roughly `n * nprobe / nlist`
+//!   vector distances, so it sits between `FlatIndex` and HNSW.
+//! - Recall is governed by `nprobe / nlist`: neighbors that fall just across a
+//!   bucket boundary are missed unless their bucket is probed too.
+//!
+//! Until the index is trained (explicitly with [`IvfIndex::train`], or
+//! automatically once `train_size` vectors have been added) every vector sits
+//! in an unassigned pool that is searched exhaustively.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::distance::DistanceMetric;
+use crate::error::{Result, VectorDbError};
+use crate::index::{self, Index};
+use crate::kmeans::KMeans;
+use crate::vector::Vector;
+
+/// Configuration parameters for the IVF index.
+#[derive(Debug, Clone)]
+pub struct IvfParams {
+    /// Number of centroids / posting lists.
+    pub nlist: usize,
+    /// Number of closest posting lists scanned per query.
+    pub nprobe: usize,
+    /// Train automatically once this many vectors have been added.
+    pub train_size: usize,
+    /// Lloyd iterations run by k-means during training.
+    pub kmeans_iters: usize,
+    /// Seed for centroid initialization, so training is reproducible.
+    pub seed: u64,
+}
+
+impl Default for IvfParams {
+    fn default() -> Self {
+        Self::new(64, 8)
+    }
+}
+
+impl IvfParams {
+    pub fn new(nlist: usize, nprobe: usize) -> Self {
+        Self {
+            nlist,
+            nprobe,
+            train_size: nlist * 32,
+            kmeans_iters: 10,
+            seed: 42,
+        }
+    }
+
+    /// Reject parameter sets that can't produce a working index.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Err(VectorDbError::IndexError(reason.to_string()));
+        if self.nlist < 1 {
+            return invalid("nlist must be at least 1");
+        }
+        if self.nprobe < 1 {
+            return invalid("nprobe must be at least 1");
+        }
+        if self.train_size < self.nlist {
+            return invalid("train_size must be at least nlist");
+        }
+        Ok(())
+    }
+}
+
+/// An inverted-file index over k-means buckets.
+#[derive(Debug)]
+pub struct IvfIndex {
+    params: IvfParams,
+    metric: DistanceMetric,
+    vectors: HashMap<usize, Vector>,
+    /// Coarse quantizer; has no centroids until trained.
+    quantizer: KMeans,
+    /// `lists[c]` holds the IDs assigned to centroid `c`.
+    lists: Vec<Vec<usize>>,
+    /// ID -> posting list, for removal.
+    assignment: HashMap<usize, usize>,
+    /// Vector dimension, fixed by the first insert.
+    dimension: Option<usize>,
+}
+
+impl IvfIndex {
+    /// Create a new IVF index with the given metric and default parameters.
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self::build(metric, IvfParams::default())
+    }
+
+    /// Create a new IVF index with custom parameters.
+    /// Fails with `IndexError` if the parameters don't pass `IvfParams::validate`.
+    pub fn with_params(metric: DistanceMetric, params: IvfParams) -> Result<Self> {
+        params.validate()?;
+        Ok(Self::build(metric, params))
+    }
+
+    fn build(metric: DistanceMetric, params: IvfParams) -> Self {
+        Self {
+            params,
+            metric,
+            vectors: HashMap::new(),
+            quantizer: KMeans::new(metric),
+            lists: Vec::new(),
+            assignment: HashMap::new(),
+            dimension: None,
+        }
+    }
+
+    /// Whether centroids have been built.
+    pub fn is_trained(&self) -> bool {
+        !self.centroids().is_empty()
+    }
+
+    /// The centroids found by training (empty before training).
+    pub fn centroids(&self) -> &[Vector] {
+        self.quantizer.centroids()
+    }
+
+    /// Change how many posting lists each query scans.
+    pub fn set_nprobe(&mut self, nprobe: usize) {
+        self.params.nprobe = nprobe.max(1);
+    }
+
+    /// Run k-means over (a sample of up to `train_size`) stored vectors and
+    /// reassign every vector to its nearest centroid. Can be called again to
+    /// retrain after the data distribution shifts.
+    pub fn train(&mut self) -> Result<()> {
+        if self.vectors.is_empty() {
+            return Ok(());
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.params.seed);
+        let mut ids: Vec<usize> = self.vectors.keys().copied().collect();
+        ids.sort_unstable();
+        ids.shuffle(&mut rng);
+        ids.truncate(self.params.train_size);
+        let sample: Vec<&Vector> = ids.iter().map(|id| &self.vectors[id]).collect();
+
+        self.quantizer.fit(
+            sample,
+            self.params.nlist,
+            self.params.kmeans_iters,
+            self.params.seed,
+        )?;
+
+        self.lists = vec![Vec::new(); self.centroids().len()];
+        self.assignment.clear();
+        let mut ids: Vec<usize> = self.vectors.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let list = self.quantizer.assign(&self.vectors[&id])?;
+            self.lists[list].push(id);
+            self.assignment.insert(id, list);
+        }
+        Ok(())
+    }
+
+    /// Posting lists to scan for `query`, closest centroid first.
+    fn probe_lists(&self, query: &Vector) -> Result<Vec<usize>> {
+        let mut ranked: Vec<(usize, f32)> = self
+            .centroids()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Ok((i, self.metric.rank_distance(query, c)?)))
+            .collect::<Result<_>>()?;
+        ranked.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(self.params.nprobe);
+        Ok(ranked.into_iter().map(|(i, _)| i).collect())
+    }
+
+    fn scan<'a>(
+        &self,
+        query: &Vector,
+        k: usize,
+        ids: impl Iterator<Item = &'a usize>,
+        predicate: &dyn Fn(usize) -> bool,
+    ) -> Result<Vec<(usize, f32)>> {
+        let mut results: Vec<(usize, f32)> = ids
+            .filter(|&&id| predicate(id))
+            .map(|&id| Ok((id, self.metric.distance(query, &self.vectors[&id])?)))
+            .collect::<Result<_>>()?;
+        results.sort_by(|a, b| self.metric.compare(a.1, b.1));
+        results.truncate(k);
+        Ok(results)
+    }
+}
+
+impl Index for IvfIndex {
+    fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        index::check_dimension(self.dimension, &vector)?;
+        self.dimension = Some(vector.dimension());
+        if self.is_trained() {
+            let list = self.quantizer.assign(&vector)?;
+            self.lists[list].push(id);
+            self.assignment.insert(id, list);
+            self.vectors.insert(id, vector);
+        } else {
+            self.vectors.insert(id, vector);
+            if self.vectors.len() >= self.params.train_size {
+                self.train()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every dimension before adding any, so a bad item can't leave
+    /// the batch half-added.
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        self.dimension = index::check_batch_dimension(self.dimension, &items)?;
+        for (id, vector) in items {
+            self.add(id, vector)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: usize) -> Result<()> {
+        self.vectors.remove(&id);
+        if let Some(list) = self.assignment.remove(&id) {
+            self.lists[list].retain(|&other| other != id);
+        }
+        Ok(())
+    }
+
+    fn get_vector(&self, id: usize) -> Option<&Vector> {
+        self.vectors.get(&id)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.search_filtered(query, k, &|_| true, 1)
+    }
+
+    /// Pre-filters within the probed lists, so results can still run short of
+    /// `k` when few matching IDs fall in those buckets.
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        if !self.is_trained() {
+            return self.scan(query, k, self.vectors.keys(), predicate);
+        }
+        let probed = self.probe_lists(query)?;
+        let ids = probed.iter().flat_map(|&list| self.lists[list].iter());
+        self.scan(query, k, ids, predicate)
+    }
+
+    fn prefilters(&self) -> bool {
+        true
+    }
+
+    fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        let table = self.vectors.capacity()
+            * (std::mem::size_of::<usize>() + std::mem::size_of::<Vector>() + 1);
+        let data: usize = self
+            .vectors
+            .values()
+            .map(|v| v.dimension() * std::mem::size_of::<f32>())
+            .sum();
+        let centroids: usize = self
+            .centroids()
+            .iter()
+            .map(|c| std::mem::size_of::<Vector>() + c.dimension() * std::mem::size_of::<f32>())
+            .sum();
+        let lists: usize = self
+            .lists
+            .iter()
+            .map(|l| {
+                std::mem::size_of::<Vec<usize>>() + l.capacity() * std::mem::size_of::<usize>()
+            })
+            .sum();
+        let assignment = self.assignment.capacity() * (2 * std::mem::size_of::<usize>() + 1);
+        table + data + centroids + lists + assignment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(n: usize) -> Vec<Vector> {
+        (0..n)
+            .map(|i| Vector::new(vec![(i % 10) as f32, (i / 10) as f32]))
+            .collect()
+    }
+
+    #[test]
+    fn test_untrained_search_is_exact() {
+        let mut index =
+            IvfIndex::with_params(DistanceMetric::Euclidean, IvfParams::new(4, 1)).unwrap();
+        for (i, v) in grid(50).into_iter().enumerate() {
+            index.add(i, v).unwrap();
+        }
+        assert!(!index.is_trained());
+
+        let results = index.search(&Vector::new(vec![3.0, 2.0]), 1).unwrap();
+        assert_eq!(results[0].0, 23);
+    }
+
+    #[test]
+    fn test_auto_train_and_remove() {
+        let mut params = IvfParams::new(4, 4);
+        params.train_size = 40;
+        let mut index = IvfIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
+        for (i, v) in grid(100).into_iter().enumerate() {
+            index.add(i, v).unwrap();
+        }
+        assert!(index.is_trained());
+        assert_eq!(index.centroids().len(), 4);
+        assert_eq!(index.lists.iter().map(Vec::len).sum::<usize>(), 100);
+
+        // Probing every list makes search exact
+        let results = index.search(&Vector::new(vec![7.0, 8.0]), 1).unwrap();
+        assert_eq!(results[0].0, 87);
+
+        index.remove(87).unwrap();
+        assert_eq!(index.len(), 99);
+        assert!(index.get_vector(87).is_none());
+        let results = index.search(&Vector::new(vec![7.0, 8.0]), 1).unwrap();
+        assert_ne!(results[0].0, 87);
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        for params in [IvfParams::new(0, 1), IvfParams::new(8, 0)] {
+            assert!(matches!(
+                IvfIndex::with_params(DistanceMetric::Euclidean, params),
+                Err(VectorDbError::IndexError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_ivf_rejects_mismatched_dimension() {
+        let mut index = IvfIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![1.0, 0.0])).unwrap();
+        assert!(matches!(
+            index.add(1, Vector::new(vec![1.0, 0.0, 0.0])),
+            Err(VectorDbError::DimensionMismatch { expected: 2, actual: 3 })
+        ));
+        let batch = vec![(1, Vector::new(vec![0.0, 1.0])), (2, Vector::new(vec![1.0]))];
+        assert!(index.add_batch(batch).is_err());
+        assert_eq!(index.len(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+//! k-means clustering: Lloyd's algorithm with k-means++ seeding, plus
+//! online (MacQueen) updates for folding in vectors one at a time.
+//!
+//! Distances come from the crate's `DistanceMetric` (ranked with
+//! `rank_distance`, so `InnerProduct` works too), but centroids are always
+//! component-wise means, which is what makes Lloyd's algorithm converge for
+//! Euclidean distance. Other metrics get a usable clustering, not an optimal one.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::distance::DistanceMetric;
+use crate::error::{Result, VectorDbError};
+use crate::vector::Vector;
+
+/// A set of k-means centroids.
+#[derive(Debug, Clone)]
+pub struct KMeans {
+    metric: DistanceMetric,
+    centroids: Vec<Vector>,
+    /// Vectors assigned to each centroid by the last `fit` plus any `update`s;
+    /// sets the step size of online updates.
+    counts: Vec<usize>,
+}
+
+impl KMeans {
+    /// An untrained model; call `fit` before `assign`.
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self {
+            metric,
+            centroids: Vec::new(),
+            counts: Vec::new(),
+        }
+    }
+
+    /// Cluster `vectors` into `k` groups with `iters` rounds of Lloyd's
+    /// algorithm after k-means++ seeding, replacing any previous centroids.
+    ///
+    /// Deterministic for a given `seed` and input order. Returns at most `k`
+    /// centroids (fewer if there are fewer vectors); a cluster that ends up
+    /// empty is reseeded from a random input vector.
+    pub fn fit<'a>(
+        &mut self,
+        vectors: impl IntoIterator<Item = &'a Vector>,
+        k: usize,
+        iters: usize,
+        seed: u64,
+    ) -> Result<&[Vector]> {
+        let sample: Vec<&Vector> = vectors.into_iter().collect();
+        let Some(first) = sample.first() else {
+            return Err(VectorDbError::InvalidVector {
+                reason: "k-means needs at least one vector".to_string(),
+            });
+        };
+        if k == 0 {
+            return Err(VectorDbError::IndexError("k-means needs k >= 1".to_string()));
+        }
+        let dim = first.dimension();
+        if let Some(v) = sample.iter().find(|v| v.dimension() != dim) {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: dim,
+                actual: v.dimension(),
+            });
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.centroids = self.seed_centroids(&sample, k.min(sample.len()), &mut rng)?;
+
+        let mut counts = vec![0; self.centroids.len()];
+        for _ in 0..iters {
+            let mut sums = vec![vec![0.0f32; dim]; self.centroids.len()];
+            counts = vec![0; self.centroids.len()];
+            for v in &sample {
+                let c = self.assign(v)?;
+                for (s, x) in sums[c].iter_mut().zip(v.as_slice()) {
+                    *s += x;
+                }
+                counts[c] += 1;
+            }
+
+            for (c, (sum, &count)) in sums.into_iter().zip(&counts).enumerate() {
+                self.centroids[c] = if count > 0 {
+                    Vector::new(sum.into_iter().map(|s| s / count as f32).collect())
+                } else {
+                    (*sample.choose(&mut rng).expect("sample is non-empty")).clone()
+                };
+            }
+        }
+        self.counts = counts;
+
+        Ok(&self.centroids)
+    }
+
+    /// k-means++: after a uniformly random first centroid, each next one is
+    /// drawn with probability proportional to its squared distance from the
+    /// nearest centroid so far. Distances below zero (possible with dot-product
+    /// metrics) count as zero; if every weight is zero the draw is uniform.
+    fn seed_centroids(
+        &self,
+        sample: &[&Vector],
+        k: usize,
+        rng: &mut StdRng,
+    ) -> Result<Vec<Vector>> {
+        let mut centroids = vec![sample[rng.gen_range(0..sample.len())].clone()];
+        let mut nearest: Vec<f32> = sample
+            .iter()
+            .map(|v| self.metric.rank_distance(v, &centroids[0]))
+            .collect::<Result<_>>()?;
+
+        while centroids.len() < k {
+            let weights: Vec<f64> = nearest
+                .iter()
+                .map(|&d| f64::from(d.max(0.0)).powi(2))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            let pick = if total > 0.0 && total.is_finite() {
+                let mut target = rng.gen::<f64>() * total;
+                weights
+                    .iter()
+                    .position(|&w| {
+                        target -= w;
+                        target < 0.0
+                    })
+                    .unwrap_or(sample.len() - 1)
+            } else {
+                rng.gen_range(0..sample.len())
+            };
+
+            let centroid = sample[pick].clone();
+            for (d, v) in nearest.iter_mut().zip(sample) {
+                *d = d.min(self.metric.rank_distance(v, &centroid)?);
+            }
+            centroids.push(centroid);
+        }
+        Ok(centroids)
+    }
+
+    /// Index of the centroid nearest to `vector`. Errors if the model hasn't
+    /// been fitted.
+    pub fn assign(&self, vector: &Vector) -> Result<usize> {
+        if self.centroids.is_empty() {
+            return Err(VectorDbError::IndexError("k-means model is not fitted".to_string()));
+        }
+        let mut best = (0, f32::INFINITY);
+        for (i, c) in self.centroids.iter().enumerate() {
+            let d = self.metric.rank_distance(vector, c)?;
+            if d < best.1 {
+                best = (i, d);
+            }
+        }
+        Ok(best.0)
+    }
+
+    /// Fold one more vector into the model without refitting: its nearest
+    /// centroid moves toward it by `1 / n`, where `n` counts the vectors that
+    /// centroid has absorbed. Returns the centroid index it was assigned to.
+    pub fn update(&mut self, vector: &Vector) -> Result<usize> {
+        let c = self.assign(vector)?;
+        self.counts[c] += 1;
+        let step = 1.0 / self.counts[c] as f32;
+        let moved = self.centroids[c]
+            .as_slice()
+            .iter()
+            .zip(vector.as_slice())
+            .map(|(&m, &x)| m + (x - m) * step)
+            .collect();
+        self.centroids[c] = Vector::new(moved);
+        Ok(c)
+    }
+
+    /// The current centroids (empty before `fit`).
+    pub fn centroids(&self) -> &[Vector] {
+        &self.centroids
+    }
+
+    /// The metric used for assignment.
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::euclidean_distance;
+
+    const TRUE_CENTERS: [[f32; 2]; 3] = [[0.0, 0.0], [10.0, 10.0], [-10.0, 10.0]];
+
+    /// 50 points in a small deterministic ring around each true center.
+    fn clusters() -> Vec<Vector> {
+        TRUE_CENTERS
+            .iter()
+            .flat_map(|c| {
+                (0..50).map(move |i| {
+                    let angle = i as f32 * 0.7;
+                    let r = 0.2 + (i % 5) as f32 * 0.1;
+                    Vector::new(vec![c[0] + r * angle.cos(), c[1] + r * angle.sin()])
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_recovers_separated_centers() {
+        let data = clusters();
+        let mut model = KMeans::new(DistanceMetric::Euclidean);
+        let centroids = model.fit(&data, 3, 10, 42).unwrap().to_vec();
+        assert_eq!(centroids.len(), 3);
+
+        for center in TRUE_CENTERS {
+            let center = Vector::new(center.to_vec());
+            let closest = centroids
+                .iter()
+                .map(|c| euclidean_distance(c, &center))
+                .fold(f32::INFINITY, f32::min);
+            assert!(closest < 0.1, "no centroid near {:?}", center);
+        }
+
+        // Every point is assigned with the rest of its cluster
+        for cluster in data.chunks(50) {
+            let label = model.assign(&cluster[0]).unwrap();
+            assert!(cluster.iter().all(|v| model.assign(v).unwrap() == label));
+        }
+    }
+
+    #[test]
+    fn test_fit_is_reproducible() {
+        let data = clusters();
+        let fit = |seed| {
+            let mut model = KMeans::new(DistanceMetric::Euclidean);
+            model.fit(&data, 4, 5, seed).unwrap().to_vec()
+        };
+        assert_eq!(fit(7), fit(7));
+    }
+
+    #[test]
+    fn test_update_moves_centroid() {
+        let data = clusters();
+        let mut model = KMeans::new(DistanceMetric::Euclidean);
+        model.fit(&data, 3, 10, 1).unwrap();
+        let point = Vector::new(vec![1.0, 1.0]);
+        let c = model.assign(&point).unwrap();
+        let before = euclidean_distance(&model.centroids()[c], &point);
+
+        assert_eq!(model.update(&point).unwrap(), c);
+        let after = euclidean_distance(&model.centroids()[c], &point);
+        // One of 51 members now: the centroid moves 1/51 of the way
+        assert!((after - before * 50.0 / 51.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_fit_errors() {
+        let mut model = KMeans::new(DistanceMetric::Euclidean);
+        assert!(model.assign(&Vector::new(vec![0.0])).is_err());
+        assert!(model.fit(&[], 2, 1, 0).is_err());
+        assert!(model.fit(&[Vector::new(vec![1.0])], 0, 1, 0).is_err());
+        let mixed = [Vector::new(vec![1.0]), Vector::new(vec![1.0, 2.0])];
+        assert!(matches!(
+            model.fit(&mixed, 2, 1, 0),
+            Err(VectorDbError::DimensionMismatch { .. })
+        ));
+
+        // Fewer vectors than k
+        let two = [Vector::new(vec![0.0]), Vector::new(vec![5.0])];
+        assert_eq!(model.fit(&two, 5, 3, 0).unwrap().len(), 2);
+    }
+}
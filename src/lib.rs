@@ -3,9 +3,10 @@
 //! A vector database implementation in Rust for learning purposes.
 //!
 //! This library provides:
-//! - Vector storage and management
+//! - Vector storage and management, with named collections
 //! - Distance metrics (Euclidean, Cosine, Dot Product)
-//! - Pluggable search indexes (FlatIndex, HNSW)
+//! - Pluggable search indexes (FlatIndex, QuantizedFlatIndex, MmapFlatIndex, IVF,
+//!   LSH, HNSW)
 //! - Persistence layer
 //!
 //! ## Example
@@ -29,19 +30,40 @@
 
 pub mod vector;
 pub mod storage;
+pub mod database;
 pub mod distance;
 pub mod error;
 pub mod index;
 pub mod flat_index;
+pub mod quantized_index;
+pub mod binary_index;
+pub mod mmap_index;
+pub mod kmeans;
+pub mod pca;
+pub mod random_projection;
+pub mod ivf;
+pub mod lsh;
 pub mod hnsw;
 pub mod persistence;
 pub mod server;
 pub mod metrics;
+pub mod bench;
+pub mod eval;
+pub mod io;
 
 pub use vector::Vector;
 pub use storage::VectorStore;
+pub use database::Database;
 pub use distance::DistanceMetric;
 pub use error::{VectorDbError, Result};
-pub use index::Index;
+pub use index::{Index, PersistIndex};
 pub use flat_index::FlatIndex;
-pub use hnsw::{HnswIndex, HnswParams};
+pub use quantized_index::QuantizedFlatIndex;
+pub use binary_index::{BinaryFlatIndex, BinaryVector};
+pub use mmap_index::MmapFlatIndex;
+pub use kmeans::KMeans;
+pub use pca::Pca;
+pub use random_projection::RandomProjection;
+pub use ivf::{IvfIndex, IvfParams};
+pub use lsh::{LshIndex, LshParams};
+pub use hnsw::{HnswIndex, HnswParams, HnswParamsBuilder};
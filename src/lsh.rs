@@ -0,0 +1,333 @@
+//! Random-hyperplane LSH (locality-sensitive hashing) index for cosine similarity.
+//!
+//! Each table draws `num_bits` random hyperplanes; a vector's hash is the bit
+//! pattern of which side of each plane it falls on. Two vectors at angle θ
+//! agree on a single bit with probability `1 - θ/π`, so near neighbors tend to
+//! share buckets. A search gathers every ID in the query's bucket in each of
+//! the `num_tables` tables and ranks those candidates exactly with the index
+//! metric.
+//!
+//! More bits make buckets smaller (faster, lower recall); more tables give a
+//! neighbor more chances to collide (higher recall, more memory).
+
+use std::collections::{HashMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::distance::DistanceMetric;
+use crate::error::{Result, VectorDbError};
+use crate::index::Index;
+use crate::vector::Vector;
+
+/// Configuration parameters for the LSH index.
+#[derive(Debug, Clone)]
+pub struct LshParams {
+    /// Number of independent hash tables.
+    pub num_tables: usize,
+    /// Hyperplanes (hash bits) per table, at most 64.
+    pub num_bits: usize,
+    /// Seed for hyperplane generation, so hashing is reproducible.
+    pub seed: u64,
+}
+
+impl Default for LshParams {
+    fn default() -> Self {
+        Self::new(8, 12)
+    }
+}
+
+impl LshParams {
+    pub fn new(num_tables: usize, num_bits: usize) -> Self {
+        Self {
+            num_tables,
+            num_bits,
+            seed: 42,
+        }
+    }
+
+    /// Reject parameter sets that can't produce a working index.
+    pub fn validate(&self) -> Result<()> {
+        if self.num_tables < 1 {
+            return Err(VectorDbError::IndexError(
+                "num_tables must be at least 1".to_string(),
+            ));
+        }
+        if !(1..=64).contains(&self.num_bits) {
+            return Err(VectorDbError::IndexError(
+                "num_bits must be between 1 and 64".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An approximate index that buckets vectors by random-hyperplane hashes.
+///
+/// Hyperplanes are generated from the seed when the first vector arrives,
+/// since that fixes the dimension.
+#[derive(Debug)]
+pub struct LshIndex {
+    params: LshParams,
+    metric: DistanceMetric,
+    vectors: HashMap<usize, Vector>,
+    /// `planes[t][b]` is the normal of bit `b` in table `t`.
+    planes: Vec<Vec<Vec<f32>>>,
+    tables: Vec<HashMap<u64, Vec<usize>>>,
+}
+
+impl LshIndex {
+    /// Create a new LSH index with the given metric and default parameters.
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self::build(metric, LshParams::default())
+    }
+
+    /// Create a new LSH index with custom parameters.
+    /// Fails with `IndexError` if the parameters don't pass `LshParams::validate`.
+    pub fn with_params(metric: DistanceMetric, params: LshParams) -> Result<Self> {
+        params.validate()?;
+        Ok(Self::build(metric, params))
+    }
+
+    fn build(metric: DistanceMetric, params: LshParams) -> Self {
+        Self {
+            tables: vec![HashMap::new(); params.num_tables],
+            params,
+            metric,
+            vectors: HashMap::new(),
+            planes: Vec::new(),
+        }
+    }
+
+    /// Draw Gaussian hyperplane normals (Box-Muller over the seeded RNG).
+    fn generate_planes(&mut self, dim: usize) {
+        let mut rng = StdRng::seed_from_u64(self.params.seed);
+        let mut gaussian = move || {
+            let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+            let u2: f32 = rng.gen();
+            (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+        };
+        self.planes = (0..self.params.num_tables)
+            .map(|_| {
+                (0..self.params.num_bits)
+                    .map(|_| (0..dim).map(|_| gaussian()).collect())
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn hash(&self, table: usize, vector: &Vector) -> Result<u64> {
+        let mut hash = 0u64;
+        for (bit, plane) in self.planes[table].iter().enumerate() {
+            if plane.len() != vector.dimension() {
+                return Err(VectorDbError::DimensionMismatch {
+                    expected: plane.len(),
+                    actual: vector.dimension(),
+                });
+            }
+            let side: f32 = plane
+                .iter()
+                .zip(vector.as_slice())
+                .map(|(p, x)| p * x)
+                .sum();
+            if side >= 0.0 {
+                hash |= 1 << bit;
+            }
+        }
+        Ok(hash)
+    }
+
+    /// IDs sharing the query's bucket in at least one table.
+    fn candidates(&self, query: &Vector) -> Result<HashSet<usize>> {
+        let mut found = HashSet::new();
+        if self.planes.is_empty() {
+            return Ok(found);
+        }
+        for (t, table) in self.tables.iter().enumerate() {
+            if let Some(bucket) = table.get(&self.hash(t, query)?) {
+                found.extend(bucket.iter().copied());
+            }
+        }
+        Ok(found)
+    }
+}
+
+impl Index for LshIndex {
+    fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        if self.planes.is_empty() {
+            self.generate_planes(vector.dimension());
+        }
+        let hashes = (0..self.tables.len())
+            .map(|t| self.hash(t, &vector))
+            .collect::<Result<Vec<_>>>()?;
+        if self.vectors.contains_key(&id) {
+            self.remove(id)?;
+        }
+        for (table, hash) in self.tables.iter_mut().zip(hashes) {
+            table.entry(hash).or_default().push(id);
+        }
+        self.vectors.insert(id, vector);
+        Ok(())
+    }
+
+    fn remove(&mut self, id: usize) -> Result<()> {
+        let Some(vector) = self.vectors.remove(&id) else {
+            return Ok(());
+        };
+        for t in 0..self.tables.len() {
+            let hash = self.hash(t, &vector)?;
+            if let Some(bucket) = self.tables[t].get_mut(&hash) {
+                bucket.retain(|&other| other != id);
+                if bucket.is_empty() {
+                    self.tables[t].remove(&hash);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_vector(&self, id: usize) -> Option<&Vector> {
+        self.vectors.get(&id)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.search_filtered(query, k, &|_| true, 1)
+    }
+
+    /// Only vectors colliding with the query are ranked, so results may hold
+    /// fewer than `k` entries even when the index is larger than `k`.
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        let mut results: Vec<(usize, f32)> = self
+            .candidates(query)?
+            .into_iter()
+            .filter(|&id| predicate(id))
+            .map(|id| Ok((id, self.metric.distance(query, &self.vectors[&id])?)))
+            .collect::<Result<_>>()?;
+        results.sort_by(|a, b| self.metric.compare(a.1, b.1));
+        results.truncate(k);
+        Ok(results)
+    }
+
+    fn prefilters(&self) -> bool {
+        true
+    }
+
+    fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        let f32_size = std::mem::size_of::<f32>();
+        let table = self.vectors.capacity()
+            * (std::mem::size_of::<usize>() + std::mem::size_of::<Vector>() + 1);
+        let data: usize = self
+            .vectors
+            .values()
+            .map(|v| v.dimension() * f32_size)
+            .sum();
+        let planes: usize = self
+            .planes
+            .iter()
+            .flatten()
+            .map(|p| std::mem::size_of::<Vec<f32>>() + p.len() * f32_size)
+            .sum();
+        let buckets: usize = self
+            .tables
+            .iter()
+            .map(|t| {
+                t.capacity() * (std::mem::size_of::<(u64, Vec<usize>)>() + 1)
+                    + t.values()
+                        .map(|b| b.capacity() * std::mem::size_of::<usize>())
+                        .sum::<usize>()
+            })
+            .sum();
+        table + data + planes + buckets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match_is_found() {
+        let mut index = LshIndex::new(DistanceMetric::Cosine);
+        index.add(0, Vector::new(vec![1.0, 0.0, 0.0])).unwrap();
+        index.add(1, Vector::new(vec![0.0, 1.0, 0.0])).unwrap();
+        index.add(2, Vector::new(vec![-1.0, 0.0, 0.5])).unwrap();
+
+        let results = index.search(&Vector::new(vec![0.0, 1.0, 0.0]), 1).unwrap();
+        assert_eq!(results[0].0, 1);
+        assert!(results[0].1 < 1e-5);
+    }
+
+    #[test]
+    fn test_same_seed_same_buckets() {
+        let a = {
+            let mut index = LshIndex::new(DistanceMetric::Cosine);
+            index.add(0, Vector::new(vec![0.3, -0.7, 0.2])).unwrap();
+            index
+        };
+        let b = {
+            let mut index = LshIndex::new(DistanceMetric::Cosine);
+            index.add(0, Vector::new(vec![0.3, -0.7, 0.2])).unwrap();
+            index
+        };
+        assert_eq!(a.planes, b.planes);
+        let key = |i: &LshIndex| {
+            i.tables
+                .iter()
+                .map(|t| *t.keys().next().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(key(&a), key(&b));
+    }
+
+    #[test]
+    fn test_remove_clears_buckets() {
+        let mut index =
+            LshIndex::with_params(DistanceMetric::Cosine, LshParams::new(4, 6)).unwrap();
+        index.add(7, Vector::new(vec![1.0, 2.0])).unwrap();
+        index.remove(7).unwrap();
+        assert_eq!(index.len(), 0);
+        assert!(index.tables.iter().all(HashMap::is_empty));
+        assert!(index
+            .search(&Vector::new(vec![1.0, 2.0]), 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let mut index = LshIndex::new(DistanceMetric::Cosine);
+        index.add(0, Vector::new(vec![1.0, 0.0])).unwrap();
+        assert!(matches!(
+            index.add(1, Vector::new(vec![1.0, 0.0, 0.0])),
+            Err(VectorDbError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        for params in [
+            LshParams::new(0, 8),
+            LshParams::new(4, 0),
+            LshParams::new(4, 65),
+        ] {
+            assert!(matches!(
+                LshIndex::with_params(DistanceMetric::Cosine, params),
+                Err(VectorDbError::IndexError(_))
+            ));
+        }
+    }
+}
@@ -2,7 +2,14 @@
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::Duration;
+use vectordb_from_scratch::bench;
 use vectordb_from_scratch::persistence::engine::{EngineConfig, StorageEngine};
+use vectordb_from_scratch::persistence::mmap::MmapVectorStorage;
+use vectordb_from_scratch::persistence::wal::WriteAheadLog;
+use vectordb_from_scratch::server::ServerConfig;
 use vectordb_from_scratch::{
     DistanceMetric, HnswIndex, HnswParams, Index, Vector, VectorStore,
 };
@@ -15,6 +22,13 @@ struct Cli {
     #[arg(long, value_enum, default_value = "flat")]
     index: IndexType,
 
+    /// Distance metric: euclidean, cosine, dot, inner-product (raw inner
+    /// product, higher is closer), jensen-shannon (probability vectors),
+    /// hamming (sign bits) or canberra. A persistent store keeps the metric it
+    /// was created with.
+    #[arg(long, default_value = "euclidean")]
+    metric: DistanceMetric,
+
     /// Data directory for persistence. If set, data is persisted to disk.
     #[arg(long)]
     data_dir: Option<String>,
@@ -54,15 +68,92 @@ enum Commands {
     },
     /// List all vector IDs
     List,
+    /// Print a summary of the store: size, dimension, metric, index stats
+    Stats,
+    /// Open the store once and read commands from stdin until EOF or `quit`
+    Repl,
     /// Start the HTTP API server
     Serve {
         /// Address to bind to
         #[arg(long, default_value = "0.0.0.0:3000")]
         addr: String,
+        /// Enable admin endpoints (e.g. POST /admin/selfbench)
+        #[arg(long)]
+        admin: bool,
+        /// Require `Authorization: Bearer <KEY>` on all endpoints except /health
+        #[arg(long)]
+        api_key: Option<String>,
+        /// Seconds between sweeps deleting expired vectors (0 disables)
+        #[arg(long, default_value = "60")]
+        expiry_interval_secs: u64,
+        /// Reject vectors with more components than this
+        #[arg(long)]
+        max_dimension: Option<usize>,
+        /// Largest request body accepted, in bytes (default 2 MiB)
+        #[arg(long)]
+        max_body_bytes: Option<usize>,
+        /// Answer searches still running after this many milliseconds with a 408
+        #[arg(long)]
+        search_timeout_ms: Option<u64>,
+        /// Most searches allowed to run at once; the rest wait for a slot
+        #[arg(long)]
+        max_concurrent_searches: Option<usize>,
+    },
+    /// Export all vectors to a JSON Lines file
+    Export {
+        /// Output file path
+        file: String,
+    },
+    /// Import vectors from a JSON Lines file
+    Import {
+        /// Input file path
+        file: String,
+    },
+    /// Import vectors from a CSV file with a header row
+    ImportCsv {
+        /// Input file path
+        file: String,
+        /// Column holding vector IDs
+        #[arg(long, default_value = "id")]
+        id_col: String,
+        /// Comma-separated vector columns, in order (default: all other columns)
+        #[arg(long, value_delimiter = ',')]
+        vector_cols: Vec<String>,
+        /// Comma-separated columns to store as metadata
+        #[arg(long, value_delimiter = ',')]
+        meta_cols: Vec<String>,
+    },
+    /// Check a data directory's write-ahead log and report where replay stops
+    WalVerify {
+        /// Data directory holding wal.log
+        data_dir: String,
+    },
+    /// Compare flat and HNSW latency and HNSW recall@k on random data
+    Bench {
+        /// Number of random vectors to index
+        #[arg(long, default_value = "10000")]
+        n: usize,
+        /// Vector dimension
+        #[arg(long, default_value = "128")]
+        dim: usize,
+        /// Neighbors per query
+        #[arg(short, long, default_value = "10")]
+        k: usize,
+        /// Number of random queries
+        #[arg(long, default_value = "100")]
+        queries: usize,
+    },
+    /// Check the integrity of an mmap vector file (header, length, checksums)
+    Verify {
+        /// Path to the vector file
+        path: String,
     },
 }
 
-fn run_with_engine(mut engine: StorageEngine, command: Commands) -> Result<()> {
+fn run_with_engine<I: Index + 'static>(
+    engine: &mut StorageEngine<I>,
+    command: Commands,
+) -> Result<()> {
     match command {
         Commands::Insert { id, vector } => {
             let v = Vector::from_str(&vector)?;
@@ -97,15 +188,42 @@ fn run_with_engine(mut engine: StorageEngine, command: Commands) -> Result<()> {
                 }
             }
         }
-        Commands::Serve { .. } => {
-            anyhow::bail!("Serve command is not supported with --data-dir (persistent storage). Use in-memory mode.");
+        Commands::Stats => print_stats(engine.store()),
+        Commands::Repl => repl(|command| run_with_engine(engine, command))?,
+        Commands::Export { file } => {
+            engine.export_jsonl(File::create(&file)?)?;
+            println!("Exported {} vectors to {}", engine.len(), file);
+        }
+        Commands::Import { file } => {
+            let count = engine.import_jsonl(File::open(&file)?)?;
+            println!("Imported {} vectors from {}", count, file);
+        }
+        Commands::ImportCsv {
+            file,
+            id_col,
+            vector_cols,
+            meta_cols,
+        } => {
+            let count = engine.import_csv(
+                File::open(&file)?,
+                &id_col,
+                &str_refs(&vector_cols),
+                &str_refs(&meta_cols),
+            )?;
+            println!("Imported {} vectors from {}", count, file);
+        }
+        Commands::Serve { .. }
+        | Commands::Verify { .. }
+        | Commands::WalVerify { .. }
+        | Commands::Bench { .. } => {
+            unreachable!("Serve, Verify, WalVerify and Bench handled separately");
         }
     }
     Ok(())
 }
 
-fn run_in_memory<I: Index + std::fmt::Debug>(
-    mut store: VectorStore<I>,
+fn run_in_memory<I: Index + std::fmt::Debug + 'static>(
+    store: &mut VectorStore<I>,
     command: Commands,
 ) -> Result<()> {
     match command {
@@ -142,29 +260,265 @@ fn run_in_memory<I: Index + std::fmt::Debug>(
                 }
             }
         }
-        Commands::Serve { .. } => {
-            unreachable!("Serve handled separately");
+        Commands::Stats => print_stats(store),
+        Commands::Repl => repl(|command| run_in_memory(store, command))?,
+        Commands::Export { file } => {
+            store.export_jsonl(File::create(&file)?)?;
+            println!("Exported {} vectors to {}", store.len(), file);
+        }
+        Commands::Import { file } => {
+            let count = store.import_jsonl(File::open(&file)?)?;
+            println!("Imported {} vectors from {}", count, file);
+        }
+        Commands::ImportCsv {
+            file,
+            id_col,
+            vector_cols,
+            meta_cols,
+        } => {
+            let count = store.import_csv(
+                File::open(&file)?,
+                &id_col,
+                &str_refs(&vector_cols),
+                &str_refs(&meta_cols),
+            )?;
+            println!("Imported {} vectors from {}", count, file);
+        }
+        Commands::Serve { .. }
+        | Commands::Verify { .. }
+        | Commands::WalVerify { .. }
+        | Commands::Bench { .. } => {
+            unreachable!("Serve, Verify, WalVerify and Bench handled separately");
         }
     }
     Ok(())
 }
 
+/// A single REPL line, parsed with the same subcommands as the CLI.
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct ReplLine {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Read commands from stdin and hand each to `run` until EOF or `quit`.
+/// Errors are printed and the loop continues.
+fn repl(mut run: impl FnMut(Commands) -> Result<()>) -> Result<()> {
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "quit" || line == "exit" {
+            break;
+        }
+
+        let command = match ReplLine::try_parse_from(line.split_whitespace()) {
+            Ok(parsed) => parsed.command,
+            Err(e) => {
+                println!("{}", e.render().to_string().trim_end());
+                continue;
+            }
+        };
+        if matches!(
+            command,
+            Commands::Serve { .. }
+                | Commands::Verify { .. }
+                | Commands::WalVerify { .. }
+                | Commands::Bench { .. }
+                | Commands::Repl
+        ) {
+            println!("Error: command not available in the REPL");
+            continue;
+        }
+        if let Err(e) = run(command) {
+            println!("Error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn print_stats<I: Index + 'static>(store: &VectorStore<I>) {
+    println!("Vectors:    {}", store.len());
+    match store.dimension() {
+        Some(dimension) => println!("Dimension:  {}", dimension),
+        None => println!("Dimension:  - (empty store)"),
+    }
+    println!("Metric:     {}", store.metric());
+    println!("Memory:     {} bytes (index estimate)", store.memory_usage_bytes());
+    if let Some(stats) = HnswIndex::downcast(store.index()).map(HnswIndex::stats) {
+        println!("HNSW:       {} layers", stats.max_level + 1);
+        for (layer, count) in stats.layer_node_counts.iter().enumerate() {
+            println!("  layer {}:  {} nodes", layer, count);
+        }
+        println!(
+            "  degree:   avg {:.2}, min {}, max {} (layer 0)",
+            stats.avg_degree_layer0, stats.min_degree_layer0, stats.max_degree_layer0
+        );
+        println!("  tombstones: {}", stats.tombstones);
+    }
+}
+
+fn str_refs(values: &[String]) -> Vec<&str> {
+    values.iter().map(String::as_str).collect()
+}
+
+fn run_bench(n: usize, dim: usize, k: usize, queries: usize, metric: DistanceMetric) -> Result<()> {
+    println!(
+        "Benchmarking {} vectors, dim {}, {} queries, k={} ({:?})",
+        n, dim, queries, k, metric
+    );
+    let report = bench::compare_flat_hnsw(n, dim, k, queries, metric)?;
+
+    println!();
+    println!("{:<6} {:>12} {:>16} {:>10}", "index", "build (ms)", "avg latency (us)", "recall@k");
+    println!(
+        "{:<6} {:>12.1} {:>16.1} {:>10.3}",
+        "flat", report.flat.build_time_ms, report.flat.avg_latency_us, 1.0
+    );
+    println!(
+        "{:<6} {:>12.1} {:>16.1} {:>10.3}",
+        "hnsw", report.hnsw.build_time_ms, report.hnsw.avg_latency_us, report.hnsw_recall
+    );
+    Ok(())
+}
+
+fn verify_mmap_file(path: &str) -> Result<()> {
+    let storage = MmapVectorStorage::open(path)?;
+    let report = storage.verify()?;
+
+    println!("File:       {}", path);
+    println!("Dimension:  {}", report.dimension);
+    println!("Vectors:    {}", report.count);
+    println!(
+        "Size:       {} bytes (expected {})",
+        report.file_bytes, report.expected_bytes
+    );
+    if report.checksums_present {
+        println!("Checksums:  {} slots verified", report.slots_checked);
+    } else {
+        println!("Checksums:  none (file predates checksums)");
+    }
+
+    if let Some(slot) = report.first_corrupt_slot {
+        anyhow::bail!("Corruption detected at vector slot {}", slot);
+    }
+    if !report.is_ok() {
+        anyhow::bail!("File length does not match header");
+    }
+    println!("OK");
+    Ok(())
+}
+
+fn verify_wal(data_dir: &str) -> Result<()> {
+    let path = std::path::Path::new(data_dir).join("wal.log");
+    if !path.exists() {
+        anyhow::bail!("No WAL found at {}", path.display());
+    }
+    let report = WriteAheadLog::open(&path)?.verify()?;
+
+    println!("WAL:        {} ({} segment(s))", path.display(), report.segments);
+    println!("Entries:    {} valid", report.valid_entries);
+    println!("Size:       {} bytes", report.total_bytes);
+
+    if let Some(stop) = report.stop {
+        anyhow::bail!(
+            "Replay stops at {} offset {}: {}",
+            stop.segment.display(),
+            stop.offset,
+            stop.reason
+        );
+    }
+    println!("OK");
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let metric = cli.metric;
+
+    // Server request logs go to stderr; RUST_LOG overrides the default level
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
 
     // Handle serve command specially — it needs the async runtime
-    if let Commands::Serve { ref addr } = cli.command {
-        let metric = DistanceMetric::Euclidean;
+    if let Commands::Serve {
+        ref addr,
+        admin,
+        ref api_key,
+        expiry_interval_secs,
+        max_dimension,
+        max_body_bytes,
+        search_timeout_ms,
+        max_concurrent_searches,
+    } = cli.command
+    {
+        let config = ServerConfig {
+            admin_enabled: admin,
+            api_key: api_key.clone(),
+            expiry_interval: (expiry_interval_secs > 0)
+                .then(|| Duration::from_secs(expiry_interval_secs)),
+            max_dimension,
+            max_body_bytes,
+            search_timeout: search_timeout_ms.map(Duration::from_millis),
+            max_concurrent_searches,
+        };
+        if let Some(data_dir) = &cli.data_dir {
+            let engine_config = EngineConfig {
+                metric,
+                ..Default::default()
+            };
+            match cli.index {
+                IndexType::Flat => {
+                    vectordb_from_scratch::server::start_persistent(
+                        addr,
+                        data_dir,
+                        engine_config,
+                        config,
+                    )
+                    .await?;
+                }
+                IndexType::Hnsw => {
+                    vectordb_from_scratch::server::start_persistent_with_index(
+                        addr,
+                        data_dir,
+                        engine_config,
+                        HnswIndex::new,
+                        config,
+                    )
+                    .await?;
+                }
+            }
+            return Ok(());
+        }
         match cli.index {
             IndexType::Flat => {
-                vectordb_from_scratch::server::start_flat(addr, metric).await?;
+                vectordb_from_scratch::server::start_flat(addr, metric, config).await?;
             }
             IndexType::Hnsw => {
                 vectordb_from_scratch::server::start_hnsw(
                     addr,
                     metric,
                     HnswParams::default(),
+                    config,
                 )
                 .await?;
             }
@@ -172,27 +526,53 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Commands::Verify { ref path } = cli.command {
+        return verify_mmap_file(path);
+    }
+
+    if let Commands::WalVerify { ref data_dir } = cli.command {
+        return verify_wal(data_dir);
+    }
+
+    if let Commands::Bench {
+        n,
+        dim,
+        k,
+        queries,
+    } = cli.command
+    {
+        return run_bench(n, dim, k, queries, metric);
+    }
+
     // If --data-dir is set, use persistent storage engine
     if let Some(data_dir) = cli.data_dir {
         let config = EngineConfig {
             checkpoint_interval: 1000,
-            metric: DistanceMetric::Euclidean,
+            metric,
+            ..Default::default()
+        };
+        return match cli.index {
+            IndexType::Flat => {
+                let mut engine = StorageEngine::open(data_dir, config)?;
+                run_with_engine(&mut engine, cli.command)
+            }
+            IndexType::Hnsw => {
+                let mut engine = StorageEngine::open_with_index(data_dir, config, HnswIndex::new)?;
+                run_with_engine(&mut engine, cli.command)
+            }
         };
-        let engine = StorageEngine::open(data_dir, config)?;
-        return run_with_engine(engine, cli.command);
     }
 
     // Otherwise, in-memory
     match cli.index {
         IndexType::Flat => {
-            let store = VectorStore::with_flat_index(DistanceMetric::Euclidean);
-            run_in_memory(store, cli.command)
+            let mut store = VectorStore::with_flat_index(metric);
+            run_in_memory(&mut store, cli.command)
         }
         IndexType::Hnsw => {
-            let index =
-                HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::default());
-            let store = VectorStore::with_index(index);
-            run_in_memory(store, cli.command)
+            let index = HnswIndex::with_params(metric, HnswParams::default())?;
+            let mut store = VectorStore::with_index(index);
+            run_in_memory(&mut store, cli.command)
         }
     }
 }
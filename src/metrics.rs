@@ -1,30 +1,159 @@
 //! Observability metrics: query latency, insert/delete throughput, index stats.
 
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Default number of recent query latencies kept for percentiles.
+pub const DEFAULT_LATENCY_WINDOW: usize = 100_000;
+
+/// Queries per second are averaged over this many trailing seconds.
+pub const QPS_WINDOW_SECS: u64 = 60;
+
+/// Significant bits kept per histogram bucket. Values below `2^SIG_BITS` get
+/// exact buckets; larger values share a bucket with others within a relative
+/// width of `2^-(SIG_BITS-1)`, so a reported percentile (the bucket midpoint)
+/// is within 0.4% of the exact sample.
+const SIG_BITS: u32 = 8;
+const SUB_BUCKETS: usize = 1 << (SIG_BITS - 1);
+const HISTOGRAM_BUCKETS: usize = (64 - SIG_BITS as usize + 1) * SUB_BUCKETS + SUB_BUCKETS;
+
+/// Log-linear (HDR-style) histogram of latencies in whole microseconds.
+#[derive(Debug, Clone)]
+struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: u64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; HISTOGRAM_BUCKETS],
+            total: 0,
+        }
+    }
+
+    fn bucket(value: u64) -> usize {
+        if value < (1 << SIG_BITS) {
+            return value as usize;
+        }
+        let shift = (63 - value.leading_zeros()) - (SIG_BITS - 1);
+        shift as usize * SUB_BUCKETS + (value >> shift) as usize
+    }
+
+    /// Midpoint of the values mapping to `bucket`.
+    fn value_of(bucket: usize) -> f64 {
+        if bucket < (1 << SIG_BITS) {
+            return bucket as f64;
+        }
+        let shift = (bucket / SUB_BUCKETS - 1) as u32;
+        let mantissa = (bucket - shift as usize * SUB_BUCKETS) as u64;
+        let low = mantissa << shift;
+        low as f64 + ((1u64 << shift) - 1) as f64 / 2.0
+    }
+
+    fn add(&mut self, value: u64) {
+        self.counts[Self::bucket(value)] += 1;
+        self.total += 1;
+    }
+
+    fn remove(&mut self, value: u64) {
+        self.counts[Self::bucket(value)] -= 1;
+        self.total -= 1;
+    }
+
+    /// Value at `rank` (0-based) in sorted order.
+    fn value_at_rank(&self, rank: u64) -> f64 {
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen > rank {
+                return Self::value_of(bucket);
+            }
+        }
+        0.0
+    }
+}
 
 /// Collects runtime metrics for the vector database.
+///
+/// Counters are cumulative; latency average and percentiles cover only the
+/// most recent `capacity` queries.
 #[derive(Debug)]
 pub struct MetricsCollector {
-    query_latencies_us: Vec<f64>,
+    /// Ring buffer of recent latencies; the oldest sample is evicted when full.
+    query_latencies_us: VecDeque<u64>,
+    /// Bucketed copy of the window, for O(buckets) percentiles.
+    histogram: LatencyHistogram,
+    /// Sum of the samples in the window.
+    window_sum_us: u64,
+    capacity: usize,
+    /// Reference point for `query_seconds`.
+    started: Instant,
+    /// Query counts per whole second since `started`, covering the QPS window.
+    query_seconds: VecDeque<(u64, u64)>,
+    /// Sum over all queries ever recorded, for the Prometheus summary.
+    total_latency_us: f64,
     total_queries: u64,
     total_inserts: u64,
     total_deletes: u64,
+    total_updates: u64,
 }
 
 impl MetricsCollector {
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_LATENCY_WINDOW)
+    }
+
+    /// Create a collector keeping the last `capacity` query latencies (at least 1).
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         Self {
-            query_latencies_us: Vec::new(),
+            query_latencies_us: VecDeque::with_capacity(capacity.min(DEFAULT_LATENCY_WINDOW)),
+            histogram: LatencyHistogram::new(),
+            window_sum_us: 0,
+            capacity,
+            started: Instant::now(),
+            query_seconds: VecDeque::new(),
+            total_latency_us: 0.0,
             total_queries: 0,
             total_inserts: 0,
             total_deletes: 0,
+            total_updates: 0,
         }
     }
 
     /// Record a query with its duration.
     pub fn record_query(&mut self, duration: Duration) {
+        self.record_query_at(duration, Instant::now());
+    }
+
+    /// Record a query that completed at `at` (lets tests control the clock).
+    pub fn record_query_at(&mut self, duration: Duration, at: Instant) {
+        let second = at.saturating_duration_since(self.started).as_secs();
+        match self.query_seconds.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => self.query_seconds.push_back((second, 1)),
+        }
+        while let Some(&(oldest, _)) = self.query_seconds.front() {
+            if oldest + QPS_WINDOW_SECS > second {
+                break;
+            }
+            self.query_seconds.pop_front();
+        }
+
+        let latency_us = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
         self.total_queries += 1;
-        self.query_latencies_us.push(duration.as_micros() as f64);
+        self.total_latency_us += latency_us as f64;
+        if self.query_latencies_us.len() == self.capacity {
+            if let Some(evicted) = self.query_latencies_us.pop_front() {
+                self.histogram.remove(evicted);
+                self.window_sum_us -= evicted;
+            }
+        }
+        self.query_latencies_us.push_back(latency_us);
+        self.histogram.add(latency_us);
+        self.window_sum_us = self.window_sum_us.saturating_add(latency_us);
     }
 
     /// Record an insert operation.
@@ -37,6 +166,21 @@ impl MetricsCollector {
         self.total_deletes += 1;
     }
 
+    /// Record a metadata update.
+    pub fn record_update(&mut self) {
+        self.total_updates += 1;
+    }
+
+    /// Clear all counters and recorded latencies, keeping the window size.
+    pub fn reset(&mut self) {
+        *self = Self::with_capacity(self.capacity);
+    }
+
+    /// Number of latency samples currently in the window.
+    pub fn latency_samples(&self) -> usize {
+        self.query_latencies_us.len()
+    }
+
     pub fn total_queries(&self) -> u64 {
         self.total_queries
     }
@@ -49,26 +193,96 @@ impl MetricsCollector {
         self.total_deletes
     }
 
-    /// Average query latency in microseconds.
+    pub fn total_updates(&self) -> u64 {
+        self.total_updates
+    }
+
+    /// Average query latency in microseconds over the window.
     pub fn avg_query_latency_us(&self) -> f64 {
         if self.query_latencies_us.is_empty() {
             return 0.0;
         }
-        let sum: f64 = self.query_latencies_us.iter().sum();
-        sum / self.query_latencies_us.len() as f64
+        self.window_sum_us as f64 / self.query_latencies_us.len() as f64
+    }
+
+    /// Query rate over the last `QPS_WINDOW_SECS` seconds (or since the
+    /// collector started, if that is more recent).
+    pub fn queries_per_second(&self) -> f64 {
+        self.queries_per_second_at(Instant::now())
     }
 
-    /// Get a percentile of query latency (e.g., 50.0, 95.0, 99.0).
+    /// `queries_per_second` as seen at `now`.
+    pub fn queries_per_second_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        let span = elapsed.as_secs_f64().min(QPS_WINDOW_SECS as f64);
+        if span <= 0.0 {
+            return 0.0;
+        }
+        let current = elapsed.as_secs();
+        let recent: u64 = self
+            .query_seconds
+            .iter()
+            .filter(|(second, _)| second + QPS_WINDOW_SECS > current)
+            .map(|(_, count)| count)
+            .sum();
+        recent as f64 / span
+    }
+
+    /// Get a percentile of query latency over the window (e.g., 50.0, 95.0, 99.0).
+    ///
+    /// Read from a log-bucket histogram: exact below 256us, otherwise within
+    /// 0.4% of the exact nearest-rank value.
     pub fn percentile_query_latency_us(&self, percentile: f64) -> f64 {
-        if self.query_latencies_us.is_empty() {
+        if self.histogram.total == 0 {
             return 0.0;
         }
+        let rank = ((percentile / 100.0) * (self.histogram.total - 1) as f64).round() as u64;
+        self.histogram
+            .value_at_rank(rank.min(self.histogram.total - 1))
+    }
 
-        let mut sorted = self.query_latencies_us.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    /// Render the counters and a query latency summary (in seconds) in the
+    /// Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            (
+                "vectordb_queries_total",
+                "Total search queries served.",
+                self.total_queries,
+            ),
+            (
+                "vectordb_inserts_total",
+                "Total vectors inserted.",
+                self.total_inserts,
+            ),
+            (
+                "vectordb_deletes_total",
+                "Total vectors deleted.",
+                self.total_deletes,
+            ),
+            (
+                "vectordb_updates_total",
+                "Total metadata updates.",
+                self.total_updates,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
 
-        let index = ((percentile / 100.0) * (sorted.len() - 1) as f64).round() as usize;
-        sorted[index.min(sorted.len() - 1)]
+        let name = "vectordb_query_latency_seconds";
+        let _ = writeln!(out, "# HELP {} Search query latency.", name);
+        let _ = writeln!(out, "# TYPE {} summary", name);
+        for (quantile, percentile) in [("0.5", 50.0), ("0.95", 95.0), ("0.99", 99.0)] {
+            let seconds = self.percentile_query_latency_us(percentile) / 1e6;
+            let _ = writeln!(out, "{}{{quantile=\"{}\"}} {}", name, quantile, seconds);
+        }
+        let _ = writeln!(out, "{}_sum {}", name, self.total_latency_us / 1e6);
+        let _ = writeln!(out, "{}_count {}", name, self.total_queries);
+        out
     }
 }
 
@@ -88,9 +302,11 @@ mod tests {
         m.record_insert();
         m.record_insert();
         m.record_delete();
+        m.record_update();
 
         assert_eq!(m.total_inserts(), 2);
         assert_eq!(m.total_deletes(), 1);
+        assert_eq!(m.total_updates(), 1);
         assert_eq!(m.total_queries(), 0);
     }
 
@@ -106,10 +322,140 @@ mod tests {
         assert!((m.percentile_query_latency_us(50.0) - 200.0).abs() < 1.0);
     }
 
+    #[test]
+    fn test_metrics_reset() {
+        let mut m = MetricsCollector::new();
+        m.record_query(Duration::from_micros(5000));
+        m.record_query(Duration::from_micros(100));
+        m.record_insert();
+        m.record_delete();
+        m.record_update();
+
+        m.reset();
+        assert_eq!(m.total_queries(), 0);
+        assert_eq!(m.total_inserts(), 0);
+        assert_eq!(m.total_deletes(), 0);
+        assert_eq!(m.total_updates(), 0);
+        assert_eq!(m.avg_query_latency_us(), 0.0);
+        assert_eq!(m.percentile_query_latency_us(99.0), 0.0);
+    }
+
+    #[test]
+    fn test_latency_window_is_bounded() {
+        let mut m = MetricsCollector::with_capacity(10);
+        for i in 0..25 {
+            m.record_query(Duration::from_micros(i * 10));
+        }
+        assert_eq!(m.total_queries(), 25);
+        assert_eq!(m.latency_samples(), 10);
+        // Only the last 10 samples (150..=240us) remain
+        assert_eq!(m.percentile_query_latency_us(0.0), 150.0);
+        assert_eq!(m.percentile_query_latency_us(100.0), 240.0);
+
+        m.reset();
+        for _ in 0..20 {
+            m.record_query(Duration::from_micros(1));
+        }
+        assert_eq!(m.latency_samples(), 10);
+    }
+
+    #[test]
+    fn test_histogram_percentiles_within_error_bound() {
+        let mut m = MetricsCollector::new();
+        let mut exact: Vec<u64> = (0..20_000u64).map(|i| (i * i) % 1_000_003 + 1).collect();
+        for &v in &exact {
+            m.record_query(Duration::from_micros(v));
+        }
+        exact.sort_unstable();
+
+        for p in [50.0, 90.0, 95.0, 99.0, 99.9] {
+            let rank = ((p / 100.0) * (exact.len() - 1) as f64).round() as usize;
+            let truth = exact[rank] as f64;
+            let estimate = m.percentile_query_latency_us(p);
+            assert!(
+                (estimate - truth).abs() / truth <= 0.004,
+                "p{}: {} vs {}",
+                p,
+                estimate,
+                truth
+            );
+        }
+    }
+
+    #[test]
+    fn test_histogram_buckets_roundtrip() {
+        for v in [
+            0u64,
+            1,
+            255,
+            256,
+            257,
+            1000,
+            123_456,
+            u64::MAX / 3,
+            u64::MAX,
+        ] {
+            let bucket = LatencyHistogram::bucket(v);
+            assert!(bucket < HISTOGRAM_BUCKETS);
+            let mid = LatencyHistogram::value_of(bucket);
+            assert!(
+                (mid - v as f64).abs() <= v as f64 / 256.0 + 0.5,
+                "{} -> {}",
+                v,
+                mid
+            );
+        }
+    }
+
+    #[test]
+    fn test_queries_per_second() {
+        let mut m = MetricsCollector::new();
+        let start = m.started;
+        // 200 queries spread evenly over 10 seconds
+        for i in 0..200 {
+            m.record_query_at(
+                Duration::from_micros(10),
+                start + Duration::from_millis(i * 50),
+            );
+        }
+        let qps = m.queries_per_second_at(start + Duration::from_secs(10));
+        assert!((18.0..=22.0).contains(&qps), "qps {}", qps);
+
+        // Long after the burst, the window no longer includes it
+        let later = start + Duration::from_secs(10 + QPS_WINDOW_SECS);
+        assert_eq!(m.queries_per_second_at(later), 0.0);
+        m.record_query_at(Duration::from_micros(10), later);
+        assert!(m.query_seconds.len() == 1);
+    }
+
     #[test]
     fn test_metrics_empty() {
         let m = MetricsCollector::new();
         assert_eq!(m.avg_query_latency_us(), 0.0);
         assert_eq!(m.percentile_query_latency_us(99.0), 0.0);
     }
+
+    #[test]
+    fn test_to_prometheus() {
+        let mut m = MetricsCollector::new();
+        m.record_insert();
+        m.record_query(Duration::from_micros(200));
+
+        let text = m.to_prometheus();
+        for name in [
+            "vectordb_queries_total",
+            "vectordb_inserts_total",
+            "vectordb_deletes_total",
+        ] {
+            assert!(
+                text.contains(&format!("# TYPE {} counter\n", name)),
+                "{}",
+                text
+            );
+        }
+        assert!(text.contains("vectordb_inserts_total 1\n"));
+        assert!(text.contains("# TYPE vectordb_query_latency_seconds summary\n"));
+        assert!(text.contains("vectordb_query_latency_seconds{quantile=\"0.99\"} 0.0002\n"));
+        assert!(text.contains("vectordb_query_latency_seconds_count 1\n"));
+    }
 }
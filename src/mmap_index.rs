@@ -0,0 +1,224 @@
+//! Disk-backed flat index: vectors live in an `MmapVectorStorage` file and are
+//! read through a memory map during search, so only the ID table is in RAM.
+//!
+//! Searches are exact brute-force scans like `FlatIndex`, bounded by disk (or
+//! page cache) throughput rather than memory.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::distance::DistanceMetric;
+use crate::error::Result;
+use crate::index::Index;
+use crate::persistence::mmap::MmapVectorStorage;
+use crate::vector::Vector;
+
+/// A brute-force index over vectors stored in a memory-mapped file.
+///
+/// The file is append-only: removing or overwriting an ID only drops it from
+/// the ID table, and the old slot stays on disk. Because vectors aren't held in
+/// memory, `get_vector` always returns `None`; `reconstruct_vector` reads them
+/// back from the file.
+#[derive(Debug)]
+pub struct MmapFlatIndex {
+    storage: MmapVectorStorage,
+    metric: DistanceMetric,
+    /// Internal ID -> slot in the storage file.
+    slots: HashMap<usize, usize>,
+}
+
+impl MmapFlatIndex {
+    /// Create an empty index backed by a new vector file at `path`.
+    pub fn create(
+        path: impl AsRef<Path>,
+        dimension: usize,
+        metric: DistanceMetric,
+    ) -> Result<Self> {
+        Ok(Self {
+            storage: MmapVectorStorage::create(path, dimension)?,
+            metric,
+            slots: HashMap::new(),
+        })
+    }
+
+    /// Index every vector already in `storage`, using its slot number as the ID.
+    pub fn from_storage(storage: MmapVectorStorage, metric: DistanceMetric) -> Self {
+        let slots = (0..storage.count()).map(|slot| (slot, slot)).collect();
+        Self {
+            storage,
+            metric,
+            slots,
+        }
+    }
+
+    /// The underlying vector file.
+    pub fn storage(&self) -> &MmapVectorStorage {
+        &self.storage
+    }
+}
+
+impl Index for MmapFlatIndex {
+    fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        let slot = self.storage.append(&vector)?;
+        self.slots.insert(id, slot);
+        Ok(())
+    }
+
+    /// One `append_batch` call, so the whole batch shares a single sync.
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        let (ids, vectors): (Vec<usize>, Vec<Vector>) = items.into_iter().unzip();
+        let first_slot = self.storage.append_batch(&vectors)?;
+        for (offset, id) in ids.into_iter().enumerate() {
+            self.slots.insert(id, first_slot + offset);
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: usize) -> Result<()> {
+        self.slots.remove(&id);
+        Ok(())
+    }
+
+    fn get_vector(&self, _id: usize) -> Option<&Vector> {
+        None
+    }
+
+    fn reconstruct_vector(&self, id: usize) -> Option<Vector> {
+        self.storage.get(*self.slots.get(&id)?).ok()
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.search_filtered(query, k, &|_| true, 1)
+    }
+
+    /// Pre-filtering scan, as in `FlatIndex`; rejected IDs are never read from disk.
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        if self.slots.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mapped = self.storage.map()?;
+        let mut results: Vec<(usize, f32)> = self
+            .slots
+            .iter()
+            .filter(|(&id, _)| predicate(id))
+            .filter_map(|(&id, &slot)| Some((id, mapped.get(slot)?)))
+            .map(|(id, vector)| Ok((id, self.metric.distance(query, &vector)?)))
+            .collect::<Result<_>>()?;
+
+        results.sort_by(|a, b| self.metric.compare(a.1, b.1));
+        results.truncate(k);
+        Ok(results)
+    }
+
+    fn prefilters(&self) -> bool {
+        true
+    }
+
+    fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Only the ID table; vector data is on disk.
+    fn memory_usage_bytes(&self) -> usize {
+        self.slots.capacity() * (2 * std::mem::size_of::<usize>() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::random_vectors;
+    use crate::flat_index::FlatIndex;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_matches_in_memory_flat_index() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+        let mut storage = MmapVectorStorage::create(&path, 16).unwrap();
+        for (i, v) in random_vectors(300, 16).into_iter().enumerate() {
+            storage.append(&v).unwrap();
+            flat.add(i, v).unwrap();
+        }
+        let mmap = MmapFlatIndex::from_storage(
+            MmapVectorStorage::open(&path).unwrap(),
+            DistanceMetric::Euclidean,
+        );
+        assert_eq!(mmap.len(), 300);
+
+        for query in random_vectors(10, 16) {
+            let expected = flat.search(&query, 10).unwrap();
+            let actual = mmap.search(&query, 10).unwrap();
+            let ids = |r: &[(usize, f32)]| r.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            assert_eq!(ids(&actual), ids(&expected));
+            for (a, e) in actual.iter().zip(&expected) {
+                assert!((a.1 - e.1).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_remove_and_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let mut index =
+            MmapFlatIndex::create(dir.path().join("v.bin"), 2, DistanceMetric::Euclidean)
+                .unwrap();
+        index.add(0, Vector::new(vec![0.0, 0.0])).unwrap();
+        index.add(1, Vector::new(vec![1.0, 1.0])).unwrap();
+        index.add(0, Vector::new(vec![5.0, 5.0])).unwrap();
+        index.remove(1).unwrap();
+
+        let results = index.search(&Vector::new(vec![0.0, 0.0]), 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 0);
+        assert!((results[0].1 - 50f32.sqrt()).abs() < 1e-5);
+        // Overwritten and removed slots stay in the file
+        assert_eq!(index.storage().count(), 3);
+
+        assert!(index.add(2, Vector::new(vec![1.0, 2.0, 3.0])).is_err());
+
+        let batch = vec![(3, Vector::new(vec![0.0, 1.0])), (4, Vector::new(vec![0.0, 0.5]))];
+        index.add_batch(batch).unwrap();
+        assert_eq!(index.storage().count(), 5);
+        assert_eq!(index.search(&Vector::new(vec![0.0, 0.0]), 1).unwrap()[0].0, 4);
+    }
+
+    #[test]
+    fn test_engine_checkpoint_reads_vectors_from_file() {
+        use crate::persistence::engine::{EngineConfig, StorageEngine};
+
+        let dir = TempDir::new().unwrap();
+        let vectors_path = dir.path().join("vectors.bin");
+        let open = || {
+            let make_index = |metric| MmapFlatIndex::create(&vectors_path, 2, metric).unwrap();
+            let config = EngineConfig::default();
+            StorageEngine::open_with_index(dir.path().join("db"), config, make_index)
+        };
+        {
+            let mut engine = open().unwrap();
+            for i in 0..5 {
+                engine.insert(format!("v{}", i), Vector::new(vec![i as f32, 1.0])).unwrap();
+            }
+            assert_eq!(engine.store().reconstruct("v2").unwrap().as_slice(), &[2.0, 1.0]);
+            engine.checkpoint().unwrap();
+        }
+
+        // The vector file is recreated on open and refilled from the snapshot
+        let engine = open().unwrap();
+        assert_eq!(engine.len(), 5);
+        assert_eq!(engine.store().reconstruct("v4").unwrap().as_slice(), &[4.0, 1.0]);
+        assert_eq!(engine.search(&Vector::new(vec![3.1, 1.0]), 1).unwrap()[0].id, "v3");
+    }
+}
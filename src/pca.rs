@@ -0,0 +1,257 @@
+//! Principal component analysis for reducing vector dimension.
+//!
+//! The covariance matrix is built in f64 (`O(n·d²)`) and its top eigenvectors
+//! found one at a time by power iteration with deflation (`O(d²)` per
+//! iteration). Fine for the handful of components a dimension reduction needs,
+//! without pulling in a linear algebra crate.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Result, VectorDbError};
+use crate::vector::Vector;
+
+/// Power iterations per component before settling for the current estimate.
+const MAX_ITERS: usize = 1000;
+/// Stop iterating once successive estimates differ by less than this.
+const TOLERANCE: f64 = 1e-10;
+
+/// A fitted projection onto the top principal components.
+#[derive(Debug, Clone)]
+pub struct Pca {
+    mean: Vec<f64>,
+    /// Unit-length principal axes, largest variance first.
+    components: Vec<Vec<f64>>,
+    /// Variance along each axis (the covariance eigenvalues).
+    variances: Vec<f64>,
+    /// Total variance of the training data (the covariance trace).
+    total_variance: f64,
+}
+
+impl Pca {
+    /// Find the top `components` principal axes of `vectors`.
+    ///
+    /// Fails if `vectors` is empty, dimensions differ, or `components` isn't
+    /// between 1 and the vector dimension.
+    pub fn fit(vectors: &[Vector], components: usize) -> Result<Self> {
+        let Some(first) = vectors.first() else {
+            return Err(VectorDbError::InvalidVector {
+                reason: "PCA needs at least one vector".to_string(),
+            });
+        };
+        let dim = first.dimension();
+        if components == 0 || components > dim {
+            return Err(VectorDbError::InvalidVector {
+                reason: format!("PCA components must be between 1 and {}, got {}", dim, components),
+            });
+        }
+        if let Some(v) = vectors.iter().find(|v| v.dimension() != dim) {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: dim,
+                actual: v.dimension(),
+            });
+        }
+
+        let n = vectors.len() as f64;
+        let mean: Vec<f64> = (0..dim)
+            .map(|j| vectors.iter().map(|v| f64::from(v.as_slice()[j])).sum::<f64>() / n)
+            .collect();
+
+        // Row-major d x d covariance; only the upper triangle is accumulated
+        let mut cov = vec![0.0f64; dim * dim];
+        let mut centered = vec![0.0f64; dim];
+        for v in vectors {
+            for (c, (&x, &m)) in centered.iter_mut().zip(v.as_slice().iter().zip(&mean)) {
+                *c = f64::from(x) - m;
+            }
+            for i in 0..dim {
+                for j in i..dim {
+                    cov[i * dim + j] += centered[i] * centered[j];
+                }
+            }
+        }
+        for i in 0..dim {
+            for j in i..dim {
+                cov[i * dim + j] /= n;
+                cov[j * dim + i] = cov[i * dim + j];
+            }
+        }
+        let total_variance = (0..dim).map(|i| cov[i * dim + i]).sum();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut axes = Vec::with_capacity(components);
+        let mut variances = Vec::with_capacity(components);
+        for _ in 0..components {
+            let (axis, variance) = top_eigenvector(&cov, dim, &mut rng);
+            // Deflate so the next pass finds the next-largest eigenvalue
+            for i in 0..dim {
+                for j in 0..dim {
+                    cov[i * dim + j] -= variance * axis[i] * axis[j];
+                }
+            }
+            axes.push(axis);
+            variances.push(variance);
+        }
+
+        Ok(Self {
+            mean,
+            components: axes,
+            variances,
+            total_variance,
+        })
+    }
+
+    /// Project `v` onto the principal axes, giving a vector of `components`
+    /// dimensions.
+    pub fn transform(&self, v: &Vector) -> Result<Vector> {
+        if v.dimension() != self.mean.len() {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: self.mean.len(),
+                actual: v.dimension(),
+            });
+        }
+        let projected = self
+            .components
+            .iter()
+            .map(|axis| {
+                v.as_slice()
+                    .iter()
+                    .zip(&self.mean)
+                    .zip(axis)
+                    .map(|((&x, m), a)| (f64::from(x) - m) * a)
+                    .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+        Ok(Vector::from_f64(&projected))
+    }
+
+    /// Number of output dimensions.
+    pub fn components(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Variance of the training data along each principal axis, largest first.
+    pub fn explained_variance(&self) -> &[f64] {
+        &self.variances
+    }
+
+    /// Fraction of the training data's total variance kept by the projection,
+    /// in `[0, 1]`. 1 for data with no variance at all.
+    pub fn variance_retained(&self) -> f64 {
+        if self.total_variance <= 0.0 {
+            return 1.0;
+        }
+        (self.variances.iter().sum::<f64>() / self.total_variance).clamp(0.0, 1.0)
+    }
+}
+
+/// Dominant eigenvector (unit length) and eigenvalue of the symmetric `dim` x
+/// `dim` matrix `m`, by power iteration from a random start.
+fn top_eigenvector(m: &[f64], dim: usize, rng: &mut StdRng) -> (Vec<f64>, f64) {
+    let mut v: Vec<f64> = (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    normalize(&mut v);
+
+    for _ in 0..MAX_ITERS {
+        let mut next = mat_vec(m, dim, &v);
+        if normalize(&mut next) == 0.0 {
+            // Nothing left in this subspace: any direction has zero variance
+            break;
+        }
+        // A negative eigenvalue flips the sign each step; compare up to sign
+        let diff: f64 = next.iter().zip(&v).map(|(a, b)| (a - b).abs()).sum();
+        let flipped: f64 = next.iter().zip(&v).map(|(a, b)| (a + b).abs()).sum();
+        v = next;
+        if diff.min(flipped) < TOLERANCE {
+            break;
+        }
+    }
+
+    let eigenvalue = v.iter().zip(mat_vec(m, dim, &v)).map(|(a, b)| a * b).sum::<f64>();
+    (v, eigenvalue.max(0.0))
+}
+
+fn mat_vec(m: &[f64], dim: usize, v: &[f64]) -> Vec<f64> {
+    m.chunks(dim)
+        .map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// Scale `v` to unit length in place, returning its original norm.
+fn normalize(v: &mut [f64]) -> f64 {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    norm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 200 points spread widely along (1, 1, 0, 0), moderately along
+    /// (0, 0, 1, 0), and barely along the last axis.
+    fn elongated() -> Vec<Vector> {
+        let mut rng = StdRng::seed_from_u64(3);
+        (0..200)
+            .map(|_| {
+                let t: f32 = rng.gen_range(-10.0..10.0);
+                let u: f32 = rng.gen_range(-2.0..2.0);
+                let noise: f32 = rng.gen_range(-0.1..0.1);
+                Vector::new(vec![t + 5.0, t - 1.0, u, noise])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pca_retains_dominant_variance() {
+        let data = elongated();
+        let pca = Pca::fit(&data, 1).unwrap();
+        assert_eq!(pca.components(), 1);
+        assert!(pca.variance_retained() > 0.95, "{}", pca.variance_retained());
+
+        // The first axis is (1, 1, 0, 0) / sqrt(2), up to sign
+        let axis = &pca.components[0];
+        let expected = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((axis[0].abs() - expected).abs() < 1e-3);
+        assert!((axis[1].abs() - expected).abs() < 1e-3);
+        assert!(axis[2].abs() < 1e-2 && axis[3].abs() < 1e-2);
+
+        let two = Pca::fit(&data, 2).unwrap();
+        assert!(two.variance_retained() > 0.999);
+        assert!(two.explained_variance()[0] > two.explained_variance()[1]);
+    }
+
+    #[test]
+    fn test_transform_output_dimension() {
+        let data = elongated();
+        for components in 1..=4 {
+            let pca = Pca::fit(&data, components).unwrap();
+            let projected = pca.transform(&data[0]).unwrap();
+            assert_eq!(projected.dimension(), components);
+        }
+
+        // The training mean maps to the origin
+        let pca = Pca::fit(&data, 2).unwrap();
+        let mean = Vector::mean(&data).unwrap();
+        assert!(pca.transform(&mean).unwrap().norm() < 1e-3);
+        assert!(pca.transform(&Vector::new(vec![1.0; 3])).is_err());
+        assert!((Pca::fit(&data, 4).unwrap().variance_retained() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_errors() {
+        assert!(Pca::fit(&[], 1).is_err());
+        let data = vec![Vector::new(vec![1.0, 2.0]), Vector::new(vec![3.0, 4.0])];
+        assert!(Pca::fit(&data, 0).is_err());
+        assert!(Pca::fit(&data, 3).is_err());
+        let mixed = vec![Vector::new(vec![1.0, 2.0]), Vector::new(vec![3.0])];
+        assert!(matches!(
+            Pca::fit(&mixed, 1),
+            Err(VectorDbError::DimensionMismatch { .. })
+        ));
+        // Constant data has nothing to project but still fits
+        let constant = vec![Vector::new(vec![1.0, 1.0]); 5];
+        assert_eq!(Pca::fit(&constant, 2).unwrap().variance_retained(), 1.0);
+    }
+}
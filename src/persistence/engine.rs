@@ -1,14 +1,18 @@
 //! Storage engine: combines WAL + snapshots for crash-safe persistence.
 
 use crate::distance::DistanceMetric;
-use crate::error::Result;
+use crate::error::{Result, VectorDbError};
 use crate::flat_index::FlatIndex;
+use crate::hnsw::{HnswGraph, HnswIndex};
+use crate::index::Index;
 use crate::persistence::serialization::{DatabaseSnapshot, SerializedVector};
-use crate::persistence::snapshot::SnapshotManager;
+use crate::persistence::snapshot::{SnapshotCodec, SnapshotDelta, SnapshotManager};
 use crate::persistence::wal::{WalEntry, WriteAheadLog};
-use crate::storage::{Metadata, VectorStore};
+use crate::storage::{self, BatchInsertItem, Metadata, Timestamps, VectorStore};
 use crate::vector::Vector;
+use std::any::Any;
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 /// Configuration for the storage engine.
@@ -17,6 +21,14 @@ pub struct EngineConfig {
     pub checkpoint_interval: usize,
     /// Distance metric.
     pub metric: DistanceMetric,
+    /// Start a new WAL segment once the current one reaches this many bytes.
+    pub wal_max_segment_bytes: u64,
+    /// Compression for newly written snapshots.
+    pub snapshot_codec: SnapshotCodec,
+    /// Checkpoint by writing up to this many deltas (just the operations since
+    /// the previous checkpoint) before collapsing them into a new full
+    /// snapshot. 0 writes a full snapshot at every checkpoint.
+    pub max_snapshot_deltas: usize,
 }
 
 impl Default for EngineConfig {
@@ -24,14 +36,22 @@ impl Default for EngineConfig {
         Self {
             checkpoint_interval: 1000,
             metric: DistanceMetric::Euclidean,
+            wal_max_segment_bytes: 64 * 1024 * 1024,
+            snapshot_codec: SnapshotCodec::None,
+            max_snapshot_deltas: 0,
         }
     }
 }
 
 /// Persistent storage engine wrapping a VectorStore with WAL + snapshot.
-pub struct StorageEngine {
-    store: VectorStore<FlatIndex>,
-    wal: WriteAheadLog,
+///
+/// Snapshots store raw vectors, so any index type can be rebuilt from them on
+/// open. An HNSW graph is also saved at every checkpoint and reloaded instead
+/// of rebuilt when it still matches the snapshot.
+pub struct StorageEngine<I: Index = FlatIndex> {
+    store: VectorStore<I>,
+    /// None when opened read-only.
+    wal: Option<WriteAheadLog>,
     snapshot_mgr: SnapshotManager,
     #[allow(dead_code)]
     data_dir: PathBuf,
@@ -39,28 +59,184 @@ pub struct StorageEngine {
     config: EngineConfig,
 }
 
-impl StorageEngine {
-    /// Open or create a persistent database at the given directory.
+impl StorageEngine<FlatIndex> {
+    /// Open or create a persistent database at the given directory, backed by a flat index.
     pub fn open(data_dir: impl AsRef<Path>, config: EngineConfig) -> Result<Self> {
-        let data_dir = data_dir.as_ref().to_path_buf();
-        std::fs::create_dir_all(&data_dir)?;
+        Self::open_with_index(data_dir, config, FlatIndex::new)
+    }
 
-        let snapshot_mgr = SnapshotManager::new(&data_dir)?;
-        let wal = WriteAheadLog::open(data_dir.join("wal.log"))?;
-        let mut store = VectorStore::with_flat_index(config.metric);
+    /// Open an existing database without write access, backed by a flat index.
+    pub fn open_read_only(data_dir: impl AsRef<Path>, config: EngineConfig) -> Result<Self> {
+        Self::open_read_only_with_index(data_dir, config, FlatIndex::new)
+    }
 
-        // Load snapshot if available
-        if let Some(snapshot) = snapshot_mgr.load()? {
-            Self::apply_snapshot(&mut store, &snapshot)?;
+    /// Restore a backup written by `snapshot_to` into a new data directory,
+    /// backed by a flat index.
+    pub fn restore_from(
+        backup_dir: impl AsRef<Path>,
+        target_dir: impl AsRef<Path>,
+        config: EngineConfig,
+    ) -> Result<Self> {
+        Self::restore_from_with_index(backup_dir, target_dir, config, FlatIndex::new)
+    }
+}
+
+impl<I: Index + 'static> StorageEngine<I> {
+    /// Open or create a persistent database at the given directory, building the
+    /// search index with `make_index` from the configured metric.
+    pub fn open_with_index(
+        data_dir: impl AsRef<Path>,
+        config: EngineConfig,
+        make_index: impl FnOnce(DistanceMetric) -> I,
+    ) -> Result<Self> {
+        Self::open_inner(data_dir.as_ref(), config, make_index, false)
+    }
+
+    /// Open an existing database for reading only: the snapshot is loaded and
+    /// the WAL replayed, but nothing in `data_dir` is created or modified.
+    /// Inserts, deletes and checkpoints fail with `IndexError("read-only")`.
+    pub fn open_read_only_with_index(
+        data_dir: impl AsRef<Path>,
+        config: EngineConfig,
+        make_index: impl FnOnce(DistanceMetric) -> I,
+    ) -> Result<Self> {
+        let data_dir = data_dir.as_ref();
+        if !data_dir.is_dir() {
+            return Err(VectorDbError::StorageError(format!(
+                "no database at {}",
+                data_dir.display()
+            )));
+        }
+        Self::open_inner(data_dir, config, make_index, true)
+    }
+
+    /// Restore a backup written by `snapshot_to` into `target_dir` and open it.
+    ///
+    /// The backup's snapshot is loaded (which refuses unknown format versions)
+    /// and checked against its manifest and `config.metric`, and every vector
+    /// must match the recorded dimension. It is then rewritten with
+    /// `config.snapshot_codec` as the base snapshot of `target_dir`, which must
+    /// not exist yet or be empty. The backup itself is only read.
+    pub fn restore_from_with_index(
+        backup_dir: impl AsRef<Path>,
+        target_dir: impl AsRef<Path>,
+        config: EngineConfig,
+        make_index: impl FnOnce(DistanceMetric) -> I,
+    ) -> Result<Self> {
+        let (backup_dir, target_dir) = (backup_dir.as_ref(), target_dir.as_ref());
+        if !backup_dir.is_dir() {
+            return Err(VectorDbError::StorageError(format!(
+                "no backup at {}",
+                backup_dir.display()
+            )));
+        }
+        if target_dir.exists() && std::fs::read_dir(target_dir)?.next().is_some() {
+            return Err(VectorDbError::StorageError(format!(
+                "restore target {} is not empty",
+                target_dir.display()
+            )));
+        }
+
+        let backup = SnapshotManager::new(backup_dir)?;
+        let manifest = backup.manifest()?;
+        if let Some(stored) = manifest.as_ref().and_then(|m| m.metric) {
+            if stored != config.metric {
+                return Err(VectorDbError::MetricMismatch {
+                    stored,
+                    requested: config.metric,
+                });
+            }
         }
+        let Some(snapshot) = backup.load()? else {
+            return Err(VectorDbError::StorageError(format!(
+                "no snapshot in {}",
+                backup_dir.display()
+            )));
+        };
+        if let Some(m) = manifest.filter(|m| m.dimension != snapshot.dimension) {
+            return Err(VectorDbError::StorageError(format!(
+                "backup manifest records dimension {:?} but the snapshot has {:?}",
+                m.dimension, snapshot.dimension
+            )));
+        }
+        if let Some(expected) = snapshot.dimension {
+            // Empty data marks a deleted slot, as in VectorStore::from_snapshot
+            let bad = snapshot
+                .vectors
+                .iter()
+                .find(|sv| !sv.data.is_empty() && sv.data.len() != expected);
+            if let Some(sv) = bad {
+                return Err(VectorDbError::DimensionMismatch {
+                    expected,
+                    actual: sv.data.len(),
+                });
+            }
+        }
+
+        SnapshotManager::with_codec(target_dir, config.snapshot_codec)?
+            .with_metric(config.metric)
+            .save(&snapshot)?;
+        Self::open_inner(target_dir, config, make_index, false)
+    }
 
-        // Replay WAL on top of snapshot
-        let entries = wal.replay()?;
+    fn open_inner(
+        data_dir: &Path,
+        config: EngineConfig,
+        make_index: impl FnOnce(DistanceMetric) -> I,
+        read_only: bool,
+    ) -> Result<Self> {
+        let data_dir = data_dir.to_path_buf();
+        std::fs::create_dir_all(&data_dir)?;
+
+        let snapshot_mgr = SnapshotManager::with_codec(&data_dir, config.snapshot_codec)?
+            .with_metric(config.metric);
+        if let Some(stored) = snapshot_mgr.manifest()?.and_then(|m| m.metric) {
+            if stored != config.metric {
+                return Err(VectorDbError::MetricMismatch {
+                    stored,
+                    requested: config.metric,
+                });
+            }
+        }
+        let wal = if read_only {
+            None
+        } else {
+            Some(WriteAheadLog::open_with_max_segment_bytes(
+                data_dir.join("wal.log"),
+                config.wal_max_segment_bytes,
+            )?)
+        };
+        // Load snapshot if available
+        let mut index = make_index(config.metric);
+        let mut store = match snapshot_mgr.load()? {
+            Some(snapshot) => {
+                if Self::load_saved_graph(&snapshot_mgr, &snapshot, config.metric, &mut index) {
+                    VectorStore::from_snapshot_prebuilt(&snapshot, index)?
+                } else {
+                    VectorStore::from_snapshot(&snapshot, config.metric, |_| index)?
+                }
+            }
+            None => VectorStore::with_index(index),
+        };
+
+        // Replay WAL on top of snapshot. Inserts are validated before they're
+        // logged, but an entry that still fails to apply (e.g. written by an
+        // older version) is skipped rather than leaving the data unopenable.
+        let entries = WriteAheadLog::replay_path(data_dir.join("wal.log"))?;
         for entry in &entries {
-            Self::apply_wal_entry(&mut store, entry)?;
+            if let Err(e) = Self::apply_wal_entry(&mut store, entry) {
+                tracing::warn!(error = %e, "skipping WAL entry that failed to replay");
+            }
         }
 
-        let wal_count = entries.len();
+        // Timestamps ride along with their insert and don't count separately
+        let wal_count = entries
+            .iter()
+            .filter(|e| !matches!(e, WalEntry::Timestamps { .. }))
+            .count();
+        if !read_only && wal_count == 0 && !snapshot_mgr.exists() {
+            snapshot_mgr.ensure_manifest()?;
+        }
 
         Ok(Self {
             store,
@@ -72,55 +248,92 @@ impl StorageEngine {
         })
     }
 
-    /// Apply a snapshot to restore store state.
-    fn apply_snapshot(
-        store: &mut VectorStore<FlatIndex>,
+    /// Swap the HNSW graph saved at the last checkpoint into `index`, if
+    /// `index` is an `HnswIndex` and the graph holds exactly the snapshot's
+    /// vectors. Returns false, leaving `index` alone, when it should be built
+    /// from the snapshot instead: another index type, no saved graph, or one
+    /// that doesn't match (e.g. a crash came between writing the snapshot and
+    /// the graph).
+    fn load_saved_graph(
+        snapshot_mgr: &SnapshotManager,
         snapshot: &DatabaseSnapshot,
-    ) -> Result<()> {
-        for sv in &snapshot.vectors {
-            if !sv.data.is_empty() {
-                let vector = Vector::new(sv.data.clone());
-                store.insert(&sv.string_id, vector)?;
+        metric: DistanceMetric,
+        index: &mut I,
+    ) -> bool {
+        let Some(hnsw) = (index as &mut dyn Any).downcast_mut::<HnswIndex>() else {
+            return false;
+        };
+        let graph = match snapshot_mgr.load_graph().and_then(|graph| {
+            graph.map(HnswGraph::from_serialized).transpose()
+        }) {
+            Ok(Some(graph)) => graph,
+            Ok(None) => return false,
+            Err(e) => {
+                tracing::warn!(error = %e, "rebuilding HNSW graph: saved graph unreadable");
+                return false;
             }
+        };
+
+        let live: Vec<&SerializedVector> =
+            snapshot.vectors.iter().filter(|sv| !sv.data.is_empty()).collect();
+        let matches = graph.metric() == metric
+            && graph.len() == live.len()
+            && live.iter().all(|sv| {
+                graph.get_vector(sv.internal_id).map(Vector::as_slice) == Some(&sv.data[..])
+            });
+        if !matches {
+            tracing::warn!("rebuilding HNSW graph: saved graph doesn't match the snapshot");
+            return false;
         }
-        Ok(())
+        *hnsw = HnswIndex::from_graph(graph);
+        true
     }
 
     /// Apply a single WAL entry to the store.
-    fn apply_wal_entry(store: &mut VectorStore<FlatIndex>, entry: &WalEntry) -> Result<()> {
+    fn apply_wal_entry(store: &mut VectorStore<I>, entry: &WalEntry) -> Result<()> {
         match entry {
             WalEntry::Insert {
-                string_id, data, ..
+                string_id,
+                data,
+                metadata,
+                ..
             } => {
                 let vector = Vector::new(data.clone());
-                store.insert(string_id.as_str(), vector)?;
+                store.insert_with_metadata(
+                    string_id.as_str(),
+                    vector,
+                    Metadata::from(metadata.clone()),
+                )?;
             }
             WalEntry::Delete { string_id } => {
                 let _ = store.delete(string_id);
             }
+            WalEntry::Rename { old_id, new_id } => {
+                let _ = store.rename(old_id, new_id);
+            }
+            WalEntry::Timestamps {
+                string_id,
+                timestamps,
+            } => store.set_timestamps(string_id, *timestamps),
             WalEntry::Checkpoint => {}
         }
         Ok(())
     }
 
-    /// Insert a vector, writing to WAL first.
-    pub fn insert(&mut self, id: impl Into<String>, vector: Vector) -> Result<()> {
-        let id = id.into();
-        let data = vector.as_slice().to_vec();
-
-        // WAL first
-        self.wal.append(&WalEntry::Insert {
-            string_id: id.clone(),
-            internal_id: 0,
-            data,
-        })?;
+    /// Whether this engine was opened with `open_read_only`.
+    pub fn is_read_only(&self) -> bool {
+        self.wal.is_none()
+    }
 
-        // Then apply
-        self.store.insert(&id, vector)?;
-        self.wal_count += 1;
-        self.maybe_checkpoint()?;
+    fn wal_mut(&mut self) -> Result<&mut WriteAheadLog> {
+        self.wal
+            .as_mut()
+            .ok_or_else(|| VectorDbError::IndexError("read-only".to_string()))
+    }
 
-        Ok(())
+    /// Insert a vector, writing to WAL first.
+    pub fn insert(&mut self, id: impl Into<String>, vector: Vector) -> Result<()> {
+        self.insert_with_metadata(id, vector, Metadata::new())
     }
 
     /// Insert a vector with metadata.
@@ -133,22 +346,82 @@ impl StorageEngine {
         let id = id.into();
         let data = vector.as_slice().to_vec();
 
-        self.wal.append(&WalEntry::Insert {
-            string_id: id.clone(),
-            internal_id: 0,
-            data,
-        })?;
+        // Reject before logging: an entry that fails to apply would also fail on replay
+        self.store.validate_insert(&vector)?;
+
+        // WAL first, with the timestamps in the same group commit
+        let timestamps = self.store.next_timestamps(&id);
+        self.wal_mut()?.append_batch(&[
+            WalEntry::Insert {
+                string_id: id.clone(),
+                internal_id: 0,
+                data,
+                metadata: metadata.fields().clone(),
+            },
+            WalEntry::Timestamps {
+                string_id: id.clone(),
+                timestamps,
+            },
+        ])?;
 
+        // Then apply, keeping the logged times so replay reproduces them
         self.store.insert_with_metadata(&id, vector, metadata)?;
+        self.store.set_timestamps(&id, timestamps);
         self.wal_count += 1;
         self.maybe_checkpoint()?;
 
         Ok(())
     }
 
+    /// Insert many vectors, logging them to the WAL with a single fsync.
+    ///
+    /// Items are checked as in `VectorStore::insert_batch_atomic` before
+    /// anything is logged, so a bad item rejects the whole batch instead of
+    /// leaving entries in the WAL that can't replay.
+    pub fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        self.store.validate_batch(&items)?;
+
+        let stamped: Vec<(String, Timestamps)> = items
+            .iter()
+            .map(|item| (item.id.clone(), self.store.next_timestamps(&item.id)))
+            .collect();
+        let entries: Vec<WalEntry> = items
+            .iter()
+            .zip(&stamped)
+            .flat_map(|(item, (id, timestamps))| {
+                [
+                    WalEntry::Insert {
+                        string_id: id.clone(),
+                        internal_id: 0,
+                        data: item.vector.as_slice().to_vec(),
+                        metadata: item.metadata.fields().clone(),
+                    },
+                    WalEntry::Timestamps {
+                        string_id: id.clone(),
+                        timestamps: *timestamps,
+                    },
+                ]
+            })
+            .collect();
+        self.wal_mut()?.append_batch(&entries)?;
+
+        self.store.insert_batch(items)?;
+        for (id, timestamps) in stamped {
+            self.store.set_timestamps(&id, timestamps);
+        }
+        self.wal_count += entries.len() / 2;
+        self.maybe_checkpoint()?;
+
+        Ok(())
+    }
+
     /// Delete a vector, writing to WAL first.
     pub fn delete(&mut self, id: &str) -> Result<Vector> {
-        self.wal.append(&WalEntry::Delete {
+        self.wal_mut()?.append(&WalEntry::Delete {
             string_id: id.to_string(),
         })?;
 
@@ -159,6 +432,46 @@ impl StorageEngine {
         Ok(result)
     }
 
+    /// Delete every expired vector (see `VectorStore::expire_now`), logging
+    /// each delete. Returns how many were removed.
+    pub fn expire_now(&mut self) -> Result<usize> {
+        let expired = self.store.expired_ids();
+        for id in &expired {
+            self.delete(id)?;
+        }
+        Ok(expired.len())
+    }
+
+    /// Change a vector's ID (see `VectorStore::rename`). Checked before
+    /// logging, so a rename that would fail never reaches the WAL.
+    pub fn rename(&mut self, old_id: &str, new_id: &str) -> Result<()> {
+        self.store.check_rename(old_id, new_id)?;
+        self.wal_mut()?.append(&WalEntry::Rename {
+            old_id: old_id.to_string(),
+            new_id: new_id.to_string(),
+        })?;
+
+        self.store.rename(old_id, new_id)?;
+        self.wal_count += 1;
+        self.maybe_checkpoint()
+    }
+
+    /// Merge `metadata` into a vector's metadata (see `VectorStore::update_metadata`).
+    ///
+    /// Logged as a re-insert of the vector with its merged metadata, so replay
+    /// needs no new WAL entry type.
+    pub fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
+        let vector = self
+            .store
+            .reconstruct(id)
+            .ok_or_else(|| VectorDbError::VectorNotFound { id: id.to_string() })?;
+        let mut merged = self.store.get_metadata(id).cloned().unwrap_or_default();
+        for (key, value) in metadata.fields() {
+            merged.insert(key.clone(), value.clone());
+        }
+        self.insert_with_metadata(id, vector, merged)
+    }
+
     /// Search for the k nearest neighbors.
     pub fn search(
         &self,
@@ -168,6 +481,27 @@ impl StorageEngine {
         self.store.search(query, k)
     }
 
+    /// Set or clear the store's dimension limit (see `VectorStore::with_max_dimension`).
+    /// Oversized inserts are rejected before they reach the WAL.
+    pub fn set_max_dimension(&mut self, max: Option<usize>) {
+        self.store.set_max_dimension(max);
+    }
+
+    /// Read-only access to the underlying store, e.g. for filtered search.
+    pub fn store(&self) -> &VectorStore<I> {
+        &self.store
+    }
+
+    /// Get a vector by ID.
+    pub fn get(&self, id: &str) -> Option<&Vector> {
+        self.store.get(id)
+    }
+
+    /// Get metadata for a vector by ID.
+    pub fn get_metadata(&self, id: &str) -> Option<&Metadata> {
+        self.store.get_metadata(id)
+    }
+
     /// Get the number of vectors.
     pub fn len(&self) -> usize {
         self.store.len()
@@ -183,18 +517,87 @@ impl StorageEngine {
         self.store.list_ids()
     }
 
-    /// Force a checkpoint: snapshot + truncate WAL.
+    /// Write every vector as JSON Lines (see `VectorStore::export_jsonl`).
+    pub fn export_jsonl<W: Write>(&self, writer: W) -> Result<()> {
+        self.store.export_jsonl(writer)
+    }
+
+    /// Import a JSON Lines dump through the WAL as one batch.
+    /// Returns the number of records imported.
+    pub fn import_jsonl<R: Read>(&mut self, reader: R) -> Result<usize> {
+        let items = storage::read_jsonl(reader)?;
+        let count = items.len();
+        self.insert_batch(items)?;
+        Ok(count)
+    }
+
+    /// Import a CSV file through the WAL as one batch (see `io::read_csv`).
+    /// Returns the number of rows imported.
+    pub fn import_csv<R: Read>(
+        &mut self,
+        reader: R,
+        id_col: &str,
+        vector_cols: &[&str],
+        meta_cols: &[&str],
+    ) -> Result<usize> {
+        let items = crate::io::read_csv(reader, id_col, vector_cols, meta_cols)?;
+        let count = items.len();
+        self.insert_batch(items)?;
+        Ok(count)
+    }
+
+    /// Force a checkpoint: snapshot (or delta) + truncate WAL.
     pub fn checkpoint(&mut self) -> Result<()> {
-        let snapshot = self.build_snapshot();
-        self.snapshot_mgr.save(&snapshot)?;
+        self.wal_mut()?;
+        let write_delta = self.snapshot_mgr.exists()
+            && self.snapshot_mgr.delta_count()? < self.config.max_snapshot_deltas;
+        if write_delta {
+            // Since the WAL is truncated at every checkpoint, it holds exactly the delta
+            let entries = self
+                .wal_mut()?
+                .replay()?
+                .into_iter()
+                .filter(|entry| !matches!(entry, WalEntry::Checkpoint))
+                .collect();
+            self.snapshot_mgr.save_delta(&SnapshotDelta { entries })?;
+        } else {
+            let snapshot = self.build_snapshot()?;
+            self.snapshot_mgr.save(&snapshot)?;
+        }
+        // Saved after the vectors, so a crash in between leaves a stale graph
+        // that `load_saved_graph` rejects
+        if let Some(hnsw) = HnswIndex::downcast(self.store.index()) {
+            self.snapshot_mgr.save_graph(&hnsw.graph().to_serialized())?;
+        }
 
-        self.wal.append(&WalEntry::Checkpoint)?;
-        self.wal.truncate()?;
+        let wal = self.wal_mut()?;
+        wal.append(&WalEntry::Checkpoint)?;
+        wal.truncate()?;
         self.wal_count = 0;
 
         Ok(())
     }
 
+    /// Write a full snapshot of the current state into `dir` (created if
+    /// missing), e.g. for a backup. Unlike `checkpoint`, the engine's own
+    /// snapshot and WAL are left untouched, and it works on a read-only engine.
+    ///
+    /// `dir` can then be opened as a data directory of its own. Any snapshot
+    /// deltas already there are discarded, but a `wal.log` left by an engine
+    /// that opened it would still be replayed on top, so back up into a fresh
+    /// directory or one only ever written by `snapshot_to`.
+    pub fn snapshot_to(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        if dir.canonicalize()? == self.data_dir.canonicalize()? {
+            return Err(VectorDbError::StorageError(
+                "snapshot_to needs a directory other than the engine's own".to_string(),
+            ));
+        }
+        SnapshotManager::with_codec(dir, self.config.snapshot_codec)?
+            .with_metric(self.config.metric)
+            .save(&self.build_snapshot()?)
+    }
+
     /// Check if we should checkpoint based on WAL size.
     fn maybe_checkpoint(&mut self) -> Result<()> {
         if self.wal_count >= self.config.checkpoint_interval {
@@ -204,27 +607,50 @@ impl StorageEngine {
     }
 
     /// Build a snapshot from current store state, including actual vector data.
-    fn build_snapshot(&self) -> DatabaseSnapshot {
+    ///
+    /// Fails rather than leaving a vector out if the index can't give one
+    /// back, since the WAL is truncated once the snapshot is written.
+    fn build_snapshot(&self) -> Result<DatabaseSnapshot> {
         let id_map = self.store.internal_to_string_ids();
         let index = self.store.index();
 
-        let vectors: Vec<SerializedVector> = index
+        let mut metadata = HashMap::new();
+        let mut timestamps = HashMap::new();
+        let vectors: Vec<SerializedVector> = id_map
             .iter()
-            .filter_map(|(&internal_id, vector)| {
-                id_map.get(&internal_id).map(|string_id| SerializedVector {
+            .map(|(&internal_id, string_id)| {
+                let vector = index.reconstruct_vector(internal_id).ok_or_else(|| {
+                    VectorDbError::StorageError(format!(
+                        "index can't return the vector for {}, refusing to snapshot without it",
+                        string_id
+                    ))
+                })?;
+                if let Some(ts) = self.store.get_timestamps(string_id) {
+                    timestamps.insert(internal_id, ts);
+                }
+                if let Some(meta) = self.store.get_metadata(string_id) {
+                    if !meta.fields().is_empty() {
+                        metadata.insert(internal_id, meta.fields().clone());
+                    }
+                }
+                Ok(SerializedVector {
                     internal_id,
                     string_id: string_id.clone(),
                     data: vector.as_slice().to_vec(),
                 })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
-        DatabaseSnapshot {
+        // Past every live internal ID, so IDs assigned by deltas on top of
+        // this base can't collide with ones in it
+        let next_id = id_map.keys().max().map_or(0, |&max| max + 1);
+        Ok(DatabaseSnapshot {
             vectors,
-            metadata: HashMap::new(),
-            next_id: self.store.len(),
+            metadata,
+            next_id,
             dimension: self.store.dimension(),
-        }
+            timestamps,
+        })
     }
 }
 
@@ -239,6 +665,7 @@ mod tests {
         let config = EngineConfig {
             checkpoint_interval: 100,
             metric: DistanceMetric::Euclidean,
+            ..Default::default()
         };
         let mut engine = StorageEngine::open(dir.path().join("db"), config).unwrap();
 
@@ -256,6 +683,163 @@ mod tests {
         assert_eq!(results[0].id, "v1");
     }
 
+    #[test]
+    fn test_engine_rename_survives_restart() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        {
+            let mut engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+            engine.insert("v1", Vector::new(vec![1.0, 0.0])).unwrap();
+            engine.insert("v2", Vector::new(vec![0.0, 1.0])).unwrap();
+            engine.rename("v1", "a").unwrap();
+            assert!(matches!(
+                engine.rename("a", "v2"),
+                Err(VectorDbError::VectorExists { .. })
+            ));
+        }
+        {
+            // Replayed from the WAL
+            let mut engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+            assert!(engine.store().get("v1").is_none());
+            assert_eq!(engine.store().get("a").unwrap().as_slice(), &[1.0, 0.0]);
+
+            engine.checkpoint().unwrap();
+            engine.rename("a", "b").unwrap();
+            engine.checkpoint().unwrap();
+        }
+        let engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+        assert!(engine.store().get("a").is_none());
+        assert_eq!(engine.store().get("b").unwrap().as_slice(), &[1.0, 0.0]);
+        assert_eq!(engine.store().get("v2").unwrap().as_slice(), &[0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_snapshot_to_leaves_wal_intact() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        let backup = dir.path().join("backup");
+        let mut engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+        for i in 0..10 {
+            let mut meta = Metadata::new();
+            meta.insert("n".to_string(), i.to_string());
+            engine
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32, 1.0]), meta)
+                .unwrap();
+        }
+        engine.delete("v3").unwrap();
+        let wal_before = std::fs::read(db_path.join("wal.log")).unwrap();
+
+        engine.snapshot_to(&backup).unwrap();
+        assert_eq!(std::fs::read(db_path.join("wal.log")).unwrap(), wal_before);
+        assert!(!db_path.join("snapshot.bin").exists());
+        assert!(matches!(
+            engine.snapshot_to(&db_path),
+            Err(VectorDbError::StorageError(_))
+        ));
+
+        let loaded = SnapshotManager::new(&backup).unwrap().load().unwrap().unwrap();
+        assert_eq!(loaded.vectors.len(), 9);
+        let restored = StorageEngine::open(&backup, EngineConfig::default()).unwrap();
+        assert_eq!(restored.len(), 9);
+        assert!(restored.store().get("v3").is_none());
+        assert_eq!(restored.store().get("v7").unwrap().as_slice(), &[7.0, 1.0]);
+        assert_eq!(restored.store().get_metadata("v7").unwrap().get("n").unwrap(), "7");
+
+        // The live engine carries on writing to its own WAL
+        engine.insert("v10", Vector::new(vec![10.0, 1.0])).unwrap();
+        drop(engine);
+        let reopened = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+        assert_eq!(reopened.len(), 10);
+    }
+
+    #[test]
+    fn test_restore_from_backup() {
+        let dir = TempDir::new().unwrap();
+        let backup = dir.path().join("backup");
+        let config = || EngineConfig {
+            snapshot_codec: SnapshotCodec::Zstd,
+            ..EngineConfig::default()
+        };
+        let mut engine = StorageEngine::open(dir.path().join("db"), config()).unwrap();
+        for i in 0..50 {
+            let mut meta = Metadata::new();
+            meta.insert("n".to_string(), i.to_string());
+            let v = Vector::new(vec![i as f32 * 0.37, (i as f32).sin(), 1.0]);
+            engine.insert_with_metadata(format!("v{}", i), v, meta).unwrap();
+        }
+        engine.delete("v10").unwrap();
+        engine.snapshot_to(&backup).unwrap();
+
+        let target = dir.path().join("restored");
+        let restored = StorageEngine::restore_from(&backup, &target, config()).unwrap();
+        assert_eq!(restored.len(), engine.len());
+        let query = Vector::new(vec![5.1, 0.2, 1.0]);
+        let hits = |e: &StorageEngine| -> Vec<(String, f32)> {
+            let results = e.search(&query, 10).unwrap();
+            results.into_iter().map(|r| (r.id, r.distance)).collect()
+        };
+        assert_eq!(hits(&restored), hits(&engine));
+        assert_eq!(restored.get_metadata("v42").unwrap().get("n").unwrap(), "42");
+
+        // The restored directory is a working database of its own
+        drop(restored);
+        let reopened = StorageEngine::open(&target, config()).unwrap();
+        assert_eq!(reopened.len(), 49);
+
+        // Non-empty target, missing backup, and a different metric are refused
+        assert!(StorageEngine::restore_from(&backup, &target, config()).is_err());
+        let missing = dir.path().join("missing");
+        assert!(StorageEngine::restore_from(&missing, dir.path().join("t1"), config()).is_err());
+        let cosine = EngineConfig {
+            metric: DistanceMetric::Cosine,
+            ..config()
+        };
+        assert!(matches!(
+            StorageEngine::restore_from(&backup, dir.path().join("t2"), cosine),
+            Err(VectorDbError::MetricMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_engine_read_only() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        let config = || EngineConfig {
+            checkpoint_interval: 3,
+            ..Default::default()
+        };
+        {
+            let mut engine = StorageEngine::open(&db_path, config()).unwrap();
+            for i in 0..5 {
+                engine
+                    .insert(format!("v{}", i), Vector::new(vec![i as f32, 0.0]))
+                    .unwrap();
+            }
+        }
+        let wal_before = std::fs::read(db_path.join("wal.log")).unwrap();
+
+        // Sees both the snapshot and the WAL tail written after it
+        let mut engine = StorageEngine::open_read_only(&db_path, config()).unwrap();
+        assert!(engine.is_read_only());
+        assert_eq!(engine.len(), 5);
+        let results = engine.search(&Vector::new(vec![4.2, 0.0]), 1).unwrap();
+        assert_eq!(results[0].id, "v4");
+
+        for err in [
+            engine.insert("v9", Vector::new(vec![9.0, 0.0])).unwrap_err(),
+            engine.delete("v0").unwrap_err(),
+            engine.checkpoint().unwrap_err(),
+        ] {
+            assert!(matches!(err, VectorDbError::IndexError(ref msg) if msg == "read-only"));
+        }
+        assert_eq!(engine.len(), 5);
+        assert_eq!(std::fs::read(db_path.join("wal.log")).unwrap(), wal_before);
+
+        let missing = dir.path().join("missing");
+        assert!(StorageEngine::open_read_only(&missing, config()).is_err());
+        assert!(!missing.exists());
+    }
+
     #[test]
     fn test_engine_wal_recovery() {
         let dir = TempDir::new().unwrap();
@@ -266,6 +850,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let mut engine = StorageEngine::open(&db_path, config).unwrap();
             engine
@@ -285,6 +870,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let engine = StorageEngine::open(&db_path, config).unwrap();
             assert_eq!(engine.len(), 3);
@@ -301,6 +887,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 2,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let mut engine = StorageEngine::open(&db_path, config).unwrap();
             engine
@@ -321,6 +908,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let engine = StorageEngine::open(&db_path, config).unwrap();
             assert_eq!(engine.len(), 3);
@@ -336,6 +924,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let mut engine = StorageEngine::open(&db_path, config).unwrap();
             engine
@@ -352,6 +941,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let engine = StorageEngine::open(&db_path, config).unwrap();
             assert_eq!(engine.len(), 1);
@@ -367,6 +957,7 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 500,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let mut engine = StorageEngine::open(&db_path, config).unwrap();
             for i in 0..1000 {
@@ -384,9 +975,486 @@ mod tests {
             let config = EngineConfig {
                 checkpoint_interval: 10000,
                 metric: DistanceMetric::Euclidean,
+                ..Default::default()
             };
             let engine = StorageEngine::open(&db_path, config).unwrap();
             assert_eq!(engine.len(), 1000);
         }
     }
+
+    #[test]
+    fn test_engine_hnsw_checkpoint_and_recovery() {
+        use crate::hnsw::{HnswIndex, HnswParams};
+
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        let make_index =
+            |metric| HnswIndex::with_params(metric, HnswParams::new(4, 32, 16)).unwrap();
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 5,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let mut engine = StorageEngine::open_with_index(&db_path, config, make_index).unwrap();
+            for i in 0..12 {
+                engine
+                    .insert(format!("v{}", i), Vector::new(vec![i as f32, 0.0]))
+                    .unwrap();
+            }
+            engine.delete("v3").unwrap();
+            assert_eq!(engine.len(), 11);
+        }
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 10000,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let engine = StorageEngine::open_with_index(&db_path, config, make_index).unwrap();
+            assert_eq!(engine.len(), 11);
+
+            let results = engine
+                .search(&Vector::new(vec![7.1, 0.0]), 1)
+                .unwrap();
+            assert_eq!(results[0].id, "v7");
+        }
+    }
+
+    #[test]
+    fn test_engine_reloads_saved_hnsw_graph() {
+        use crate::hnsw::{HnswIndex, HnswParams};
+
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        let make_index =
+            |metric| HnswIndex::with_params(metric, HnswParams::new(8, 64, 32)).unwrap();
+        let config = || EngineConfig {
+            checkpoint_interval: 10000,
+            metric: DistanceMetric::Euclidean,
+            ..Default::default()
+        };
+        let query = Vector::new(vec![50.5, 3.0]);
+
+        let (graph_bytes, before) = {
+            let mut engine =
+                StorageEngine::open_with_index(&db_path, config(), make_index).unwrap();
+            for i in 0..200 {
+                engine
+                    .insert(format!("v{}", i), Vector::new(vec![i as f32, (i * 7 % 13) as f32]))
+                    .unwrap();
+            }
+            engine.checkpoint().unwrap();
+            let graph = engine.store().index().graph();
+            (graph.serialize().unwrap(), graph.search_knn(&query, 10, 32).unwrap())
+        };
+
+        {
+            let engine = StorageEngine::open_with_index(&db_path, config(), make_index).unwrap();
+            let graph = engine.store().index().graph();
+            // A rebuilt graph draws fresh random levels; a reloaded one is byte-identical.
+            assert_eq!(graph.serialize().unwrap(), graph_bytes);
+            assert_eq!(graph.search_knn(&query, 10, 32).unwrap(), before);
+        }
+
+        // An unreadable graph falls back to rebuilding from the snapshot.
+        std::fs::write(db_path.join("graph.bin"), b"garbage").unwrap();
+        let engine = StorageEngine::open_with_index(&db_path, config(), make_index).unwrap();
+        assert_eq!(engine.len(), 200);
+        let results = engine.search(&Vector::new(vec![42.0, 8.0]), 1).unwrap();
+        assert_eq!(results[0].id, "v42");
+    }
+
+    #[test]
+    fn test_engine_quantized_checkpoint_keeps_vectors() {
+        use crate::quantized_index::QuantizedFlatIndex;
+
+        let dir = TempDir::new().unwrap();
+        let open = || {
+            let config = EngineConfig::default();
+            StorageEngine::open_with_index(dir.path(), config, QuantizedFlatIndex::new)
+        };
+        let vectors: Vec<Vector> =
+            (0..5).map(|i| Vector::new(vec![i as f32, 1.0, -(i as f32)])).collect();
+        {
+            let mut engine = open().unwrap();
+            for (i, v) in vectors.iter().enumerate() {
+                engine.insert(format!("v{}", i), v.clone()).unwrap();
+            }
+            engine.checkpoint().unwrap();
+        }
+
+        let engine = open().unwrap();
+        assert_eq!(engine.len(), 5);
+        for (i, v) in vectors.iter().enumerate() {
+            let restored = engine.store().reconstruct(&format!("v{}", i)).unwrap();
+            // Within half a quantization step of the original
+            for (a, b) in v.as_slice().iter().zip(restored.as_slice()) {
+                assert!((a - b).abs() <= 8.0 / 255.0 / 2.0 + 1e-6, "{} vs {}", a, b);
+            }
+        }
+        assert_eq!(engine.search(&vectors[3], 1).unwrap()[0].id, "v3");
+    }
+
+    #[test]
+    fn test_engine_metadata_recovery() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 2,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let mut engine = StorageEngine::open(&db_path, config).unwrap();
+            for (id, color) in [("v1", "red"), ("v2", "green"), ("v3", "blue")] {
+                let mut meta = Metadata::new();
+                meta.insert("color".to_string(), color.to_string());
+                engine
+                    .insert_with_metadata(id, Vector::new(vec![1.0, 2.0]), meta)
+                    .unwrap();
+            }
+            // v1 and v2 are in the snapshot, v3 only in the WAL
+        }
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 10000,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let engine = StorageEngine::open(&db_path, config).unwrap();
+            assert_eq!(engine.len(), 3);
+            for (id, color) in [("v1", "red"), ("v2", "green"), ("v3", "blue")] {
+                let meta = engine.get_metadata(id).unwrap();
+                assert_eq!(meta.get("color"), Some(&color.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_engine_metadata_replays_after_crash() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 10000,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let mut engine = StorageEngine::open(&db_path, config).unwrap();
+            let mut meta = Metadata::new();
+            meta.insert("label".to_string(), "cat".to_string());
+            engine
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0]), meta)
+                .unwrap();
+            // Simulate a crash: no checkpoint, no destructor
+            std::mem::forget(engine);
+        }
+
+        let config = EngineConfig {
+            checkpoint_interval: 10000,
+            metric: DistanceMetric::Euclidean,
+            ..Default::default()
+        };
+        let engine = StorageEngine::open(&db_path, config).unwrap();
+        assert_eq!(
+            engine.get_metadata("v1").unwrap().get("label"),
+            Some(&"cat".to_string())
+        );
+    }
+
+    #[test]
+    fn test_engine_update_metadata_on_quantized_index() {
+        use crate::quantized_index::QuantizedFlatIndex;
+
+        let dir = TempDir::new().unwrap();
+        let config = EngineConfig::default();
+        let mut engine =
+            StorageEngine::open_with_index(dir.path(), config, QuantizedFlatIndex::new).unwrap();
+        engine.insert("v1", Vector::new(vec![-1.0, 0.5, 2.0])).unwrap();
+        let before = engine.store().reconstruct("v1").unwrap();
+
+        let mut patch = Metadata::new();
+        patch.insert("owner".to_string(), "ana".to_string());
+        engine.update_metadata("v1", patch).unwrap();
+        assert_eq!(engine.get_metadata("v1").unwrap().get("owner"), Some(&"ana".to_string()));
+        assert_eq!(engine.store().reconstruct("v1").unwrap(), before);
+    }
+
+    #[test]
+    fn test_engine_update_metadata_and_bad_dimension_replay() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+
+        {
+            let mut engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+            let mut meta = Metadata::new();
+            meta.insert("label".to_string(), "cat".to_string());
+            engine
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0]), meta)
+                .unwrap();
+            assert!(engine.insert("v2", Vector::new(vec![1.0, 0.0, 0.0])).is_err());
+
+            let mut patch = Metadata::new();
+            patch.insert("owner".to_string(), "ana".to_string());
+            engine.update_metadata("v1", patch).unwrap();
+            assert!(engine.update_metadata("missing", Metadata::new()).is_err());
+            std::mem::forget(engine);
+        }
+
+        // The rejected insert never reached the WAL, so replay succeeds
+        let engine = StorageEngine::open(&db_path, EngineConfig::default()).unwrap();
+        assert_eq!(engine.len(), 1);
+        let meta = engine.get_metadata("v1").unwrap();
+        assert_eq!(meta.get("label"), Some(&"cat".to_string()));
+        assert_eq!(meta.get("owner"), Some(&"ana".to_string()));
+    }
+
+    #[test]
+    fn test_engine_rejected_index_insert_stays_out_of_wal() {
+        use crate::hnsw::HnswIndex;
+
+        let dir = TempDir::new().unwrap();
+        let config = || EngineConfig {
+            metric: DistanceMetric::Cosine,
+            ..Default::default()
+        };
+        let open = || StorageEngine::open_with_index(dir.path(), config(), HnswIndex::new);
+
+        {
+            let mut engine = open().unwrap();
+            engine.insert("a", Vector::new(vec![1.0, 0.0])).unwrap();
+            // The HNSW index can't measure a zero vector under Cosine
+            assert!(engine.insert("z", Vector::new(vec![0.0, 0.0])).is_err());
+            let batch = vec![BatchInsertItem {
+                id: "z".to_string(),
+                vector: Vector::new(vec![0.0, 0.0]),
+                metadata: Metadata::new(),
+            }];
+            assert!(engine.insert_batch(batch).is_err());
+            std::mem::forget(engine);
+        }
+        let engine = open().unwrap();
+        assert_eq!(engine.len(), 1);
+
+        // An entry that can't apply anyway (logged before this check existed)
+        // is skipped on replay instead of failing the open
+        drop(engine);
+        let mut wal = WriteAheadLog::open(dir.path().join("wal.log")).unwrap();
+        wal.append(&WalEntry::Insert {
+            string_id: "z".to_string(),
+            internal_id: 0,
+            data: vec![0.0, 0.0],
+            metadata: HashMap::new(),
+        })
+        .unwrap();
+        drop(wal);
+        let engine = open().unwrap();
+        assert_eq!(engine.len(), 1);
+        assert!(engine.get("z").is_none());
+    }
+
+    #[test]
+    fn test_engine_insert_batch_recovery() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+
+        {
+            let config = EngineConfig {
+                checkpoint_interval: 10000,
+                metric: DistanceMetric::Euclidean,
+                ..Default::default()
+            };
+            let mut engine = StorageEngine::open(&db_path, config).unwrap();
+            let items = (0..50)
+                .map(|i| BatchInsertItem {
+                    id: format!("v{}", i),
+                    vector: Vector::new(vec![i as f32, 0.0]),
+                    metadata: Metadata::new(),
+                })
+                .collect();
+            engine.insert_batch(items).unwrap();
+
+            let bad = vec![
+                BatchInsertItem {
+                    id: "ok".to_string(),
+                    vector: Vector::new(vec![1.0, 1.0]),
+                    metadata: Metadata::new(),
+                },
+                BatchInsertItem {
+                    id: "bad".to_string(),
+                    vector: Vector::new(vec![1.0, 1.0, 1.0]),
+                    metadata: Metadata::new(),
+                },
+            ];
+            assert!(engine.insert_batch(bad).is_err());
+            assert_eq!(engine.len(), 50);
+        }
+
+        let config = EngineConfig {
+            checkpoint_interval: 10000,
+            metric: DistanceMetric::Euclidean,
+            ..Default::default()
+        };
+        let engine = StorageEngine::open(&db_path, config).unwrap();
+        assert_eq!(engine.len(), 50);
+        assert!(engine.get("v49").is_some());
+        assert!(engine.get("ok").is_none());
+    }
+
+    #[test]
+    fn test_engine_rejects_conflicting_metric() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db");
+        let config = |metric| EngineConfig {
+            metric,
+            ..Default::default()
+        };
+
+        // The metric is on record as soon as the directory is created
+        StorageEngine::open(&db_path, config(DistanceMetric::Cosine)).unwrap();
+        assert!(matches!(
+            StorageEngine::open(&db_path, config(DistanceMetric::Euclidean)),
+            Err(VectorDbError::MetricMismatch {
+                stored: DistanceMetric::Cosine,
+                requested: DistanceMetric::Euclidean,
+            })
+        ));
+
+        let mut engine = StorageEngine::open(&db_path, config(DistanceMetric::Cosine)).unwrap();
+        engine.insert("v1", Vector::new(vec![1.0, 0.0])).unwrap();
+        engine.checkpoint().unwrap();
+        drop(engine);
+        assert!(StorageEngine::open(&db_path, config(DistanceMetric::DotProduct)).is_err());
+    }
+
+    #[test]
+    fn test_timestamps_survive_restart() {
+        let dir = TempDir::new().unwrap();
+        let config = || EngineConfig {
+            checkpoint_interval: 1000,
+            max_snapshot_deltas: 2,
+            ..Default::default()
+        };
+        let (a, b, c) = {
+            let mut engine = StorageEngine::open(dir.path(), config()).unwrap();
+            engine.insert("a", Vector::new(vec![1.0])).unwrap();
+            engine
+                .insert_batch(vec![BatchInsertItem {
+                    id: "b".to_string(),
+                    vector: Vector::new(vec![2.0]),
+                    metadata: Metadata::new(),
+                }])
+                .unwrap();
+            // "a" goes into the base snapshot, "b"'s update into a delta,
+            // "c" stays in the WAL
+            engine.checkpoint().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            let mut meta = Metadata::new();
+            meta.insert("k".to_string(), "v".to_string());
+            engine.update_metadata("b", meta).unwrap();
+            engine.checkpoint().unwrap();
+            engine.insert("c", Vector::new(vec![3.0])).unwrap();
+
+            let b = engine.store().get_timestamps("b").unwrap();
+            assert!(b.updated_at > b.created_at);
+            let ts = |id| engine.store().get_timestamps(id).unwrap();
+            (ts("a"), b, ts("c"))
+        };
+
+        let engine = StorageEngine::open(dir.path(), config()).unwrap();
+        assert_eq!(engine.store().get_timestamps("a"), Some(a));
+        assert_eq!(engine.store().get_timestamps("b"), Some(b));
+        assert_eq!(engine.store().get_timestamps("c"), Some(c));
+    }
+
+    #[test]
+    fn test_delta_after_deletes_reopens() {
+        let dir = TempDir::new().unwrap();
+        let config = || EngineConfig {
+            checkpoint_interval: 1000,
+            max_snapshot_deltas: 3,
+            ..Default::default()
+        };
+        {
+            let mut engine = StorageEngine::open(dir.path(), config()).unwrap();
+            for id in ["a", "b", "c"] {
+                let mut meta = Metadata::new();
+                meta.insert("name".to_string(), id.to_string());
+                engine.insert_with_metadata(id, Vector::new(vec![1.0, 2.0]), meta).unwrap();
+            }
+            engine.delete("a").unwrap();
+            engine.checkpoint().unwrap();
+            // Logged to a delta on top of a base whose live IDs are sparse
+            let mut meta = Metadata::new();
+            meta.insert("name".to_string(), "d".to_string());
+            engine.insert_with_metadata("d", Vector::new(vec![3.0, 4.0]), meta).unwrap();
+            engine.checkpoint().unwrap();
+        }
+        let engine = StorageEngine::open(dir.path(), config()).unwrap();
+        let mut ids = engine.list_ids();
+        ids.sort();
+        assert_eq!(ids, ["b", "c", "d"]);
+        assert_eq!(engine.get("d").unwrap().as_slice(), &[3.0, 4.0]);
+        // "d" must not have taken over the internal ID of "c" in the base
+        let name = engine.get_metadata("c").and_then(|m| m.get("name").cloned());
+        assert_eq!(name.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_engine_delta_recovery_matches_full_snapshots() {
+        let dir = TempDir::new().unwrap();
+        let run = |name: &str, max_snapshot_deltas: usize| {
+            let db_path = dir.path().join(name);
+            let config = || EngineConfig {
+                checkpoint_interval: 7,
+                max_snapshot_deltas,
+                ..Default::default()
+            };
+            {
+                let mut engine = StorageEngine::open(&db_path, config()).unwrap();
+                for i in 0..64 {
+                    let mut meta = Metadata::new();
+                    meta.insert("n".to_string(), i.to_string());
+                    let vector = Vector::new(vec![i as f32, (i % 5) as f32]);
+                    engine
+                        .insert_with_metadata(format!("v{}", i % 25), vector, meta)
+                        .unwrap();
+                    if i % 4 == 0 {
+                        let _ = engine.delete(&format!("v{}", (i + 3) % 25));
+                    }
+                }
+            }
+            let engine = StorageEngine::open(&db_path, config()).unwrap();
+            (db_path, engine)
+        };
+
+        let (_, full) = run("full", 0);
+        let (delta_path, delta) = run("delta", 3);
+
+        // Deltas were written and periodically collapsed into a new base
+        let mgr = SnapshotManager::new(&delta_path).unwrap();
+        assert!((1..=3).contains(&mgr.delta_count().unwrap()));
+
+        let mut ids = full.list_ids();
+        ids.sort();
+        let mut delta_ids = delta.list_ids();
+        delta_ids.sort();
+        assert_eq!(ids, delta_ids);
+        for id in &ids {
+            assert_eq!(full.get(id), delta.get(id));
+            let fields = |e: &StorageEngine| e.get_metadata(id).map(|m| m.fields().clone());
+            assert_eq!(fields(&full), fields(&delta));
+        }
+        let query = Vector::new(vec![30.0, 2.0]);
+        let hits = |engine: &StorageEngine| -> Vec<String> {
+            engine.search(&query, 5).unwrap().into_iter().map(|r| r.id).collect()
+        };
+        assert_eq!(hits(&full), hits(&delta));
+    }
 }
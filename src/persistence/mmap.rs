@@ -3,18 +3,82 @@
 //! Stores vectors in a flat binary file where each vector is stored as
 //! contiguous f32 values. Uses regular file I/O for writes and can optionally
 //! use memory mapping for reads.
+//!
+//! A sidecar `<file>.crc` holds one CRC32 (u32 LE) per vector slot so that
+//! `verify` can detect corrupted vectors. Files written before checksums were
+//! introduced have no sidecar and only get header/length checks, until the
+//! next append starts one covering their existing slots too.
 
 use crate::error::{Result, VectorDbError};
 use crate::vector::Vector;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 /// Header written at the start of the file.
 /// [dimension: u32][count: u32]
 const HEADER_SIZE: usize = 8;
 
+/// Read buffer for `verify`'s streaming scan.
+const VERIFY_BUFFER_BYTES: usize = 1 << 20;
+
+/// Result of an integrity scan over a vector file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Dimension recorded in the header.
+    pub dimension: usize,
+    /// Vector count recorded in the header.
+    pub count: usize,
+    /// Actual size of the data file in bytes.
+    pub file_bytes: u64,
+    /// Size implied by the header (`HEADER_SIZE + count * dimension * 4`).
+    pub expected_bytes: u64,
+    /// Whether a checksum sidecar was found.
+    pub checksums_present: bool,
+    /// Number of slots whose checksum was validated.
+    pub slots_checked: usize,
+    /// The first slot whose data is missing or fails its checksum.
+    pub first_corrupt_slot: Option<usize>,
+}
+
+impl VerifyReport {
+    /// True if the header, file length, and all checksums are consistent.
+    pub fn is_ok(&self) -> bool {
+        self.file_bytes == self.expected_bytes && self.first_corrupt_slot.is_none()
+    }
+}
+
+/// A read-only mapping of a vector file, from `MmapVectorStorage::map`.
+pub struct MappedVectors {
+    mmap: memmap2::Mmap,
+    dimension: usize,
+    count: usize,
+}
+
+impl MappedVectors {
+    /// Decode the vector in `slot`, or `None` if it is out of range.
+    pub fn get(&self, slot: usize) -> Option<Vector> {
+        if slot >= self.count {
+            return None;
+        }
+        let start = HEADER_SIZE + slot * self.dimension * 4;
+        let bytes = self.mmap.get(start..start + self.dimension * 4)?;
+        Some(Vector::new(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+        ))
+    }
+
+    /// Number of slots visible through this mapping.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
 /// Memory-mapped (or file-backed) vector storage.
+#[derive(Debug)]
 pub struct MmapVectorStorage {
     path: PathBuf,
     dimension: usize,
@@ -36,6 +100,8 @@ impl MmapVectorStorage {
         file.write_all(&header)?;
         file.sync_all()?;
 
+        File::create(Self::checksum_path_for(&path))?;
+
         Ok(Self {
             path,
             dimension,
@@ -64,34 +130,68 @@ impl MmapVectorStorage {
 
     /// Append a vector to the file.
     pub fn append(&mut self, vector: &Vector) -> Result<usize> {
-        if vector.dimension() != self.dimension {
+        self.append_batch(std::slice::from_ref(vector))
+    }
+
+    /// Append several vectors, returning the slot of the first. All of them
+    /// share one write and one sync of the data file and of the checksum
+    /// sidecar, so bulk loads should prefer this over repeated `append`s.
+    pub fn append_batch(&mut self, vectors: &[Vector]) -> Result<usize> {
+        if let Some(v) = vectors.iter().find(|v| v.dimension() != self.dimension) {
             return Err(VectorDbError::DimensionMismatch {
                 expected: self.dimension,
-                actual: vector.dimension(),
+                actual: v.dimension(),
             });
         }
+        let first_slot = self.count;
+        if vectors.is_empty() {
+            return Ok(first_slot);
+        }
 
-        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
-
-        // Seek to end of data
+        // Vector data as little-endian f32s, plus one checksum per slot
         let vec_bytes = self.dimension * 4;
-        let offset = (HEADER_SIZE + self.count * vec_bytes) as u64;
-        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::with_capacity(vectors.len() * vec_bytes);
+        let mut crcs = Vec::with_capacity(vectors.len() * 4);
+        for vector in vectors {
+            let start = bytes.len();
+            for &val in vector.as_slice() {
+                bytes.extend_from_slice(&val.to_le_bytes());
+            }
+            crcs.extend_from_slice(&crc32fast::hash(&bytes[start..]).to_le_bytes());
+        }
 
-        // Write vector data as little-endian f32s
-        for &val in vector.as_slice() {
-            file.write_all(&val.to_le_bytes())?;
+        // A file from before checksums has no sidecar; starting one needs the
+        // existing slots' checksums too, or `verify` would flag them as corrupt
+        let mut crc_offset = self.count * 4;
+        if !self.checksum_path().exists() {
+            let mut existing = self.checksum_existing_slots()?;
+            existing.extend_from_slice(&crcs);
+            crcs = existing;
+            crc_offset = 0;
         }
 
-        // Update header count
-        self.count += 1;
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        file.seek(SeekFrom::Start((HEADER_SIZE + self.count * vec_bytes) as u64))?;
+        file.write_all(&bytes)?;
+
+        // Checksums must be durable before the header count makes the new
+        // slots visible
+        let mut crc_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.checksum_path())?;
+        crc_file.seek(SeekFrom::Start(crc_offset as u64))?;
+        crc_file.write_all(&crcs)?;
+        crc_file.sync_data()?;
+
+        self.count += vectors.len();
         let header = Self::encode_header(self.dimension, self.count);
         file.seek(SeekFrom::Start(0))?;
         file.write_all(&header)?;
+        file.sync_data()?;
 
-        file.sync_all()?;
-
-        Ok(self.count - 1)
+        Ok(first_slot)
     }
 
     /// Read a vector by index.
@@ -148,6 +248,105 @@ impl MmapVectorStorage {
         }
     }
 
+    /// Map the file once for reading many slots, e.g. in a full scan.
+    /// Slots appended after mapping are not visible through the view.
+    pub fn map(&self) -> Result<MappedVectors> {
+        let file = File::open(&self.path)?;
+        // SAFETY: the file is only ever appended to, and the header count we
+        // bound reads by was read before mapping.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(MappedVectors {
+            mmap,
+            dimension: self.dimension,
+            count: self.count,
+        })
+    }
+
+    /// Read `len` contiguous vectors starting at `start`, mapping the file once.
+    pub fn get_range(&self, start: usize, len: usize) -> Result<Vec<Vector>> {
+        let end = start.checked_add(len).filter(|&end| end <= self.count);
+        let Some(end) = end else {
+            return Err(VectorDbError::IndexError(format!(
+                "Range {}..{} out of range (count={})",
+                start,
+                start.saturating_add(len),
+                self.count
+            )));
+        };
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mapped = self.map()?;
+        (start..end)
+            .map(|slot| {
+                mapped.get(slot).ok_or_else(|| {
+                    VectorDbError::StorageError(format!("Vector {} missing from file", slot))
+                })
+            })
+            .collect()
+    }
+
+    /// Read every stored vector in slot order.
+    pub fn get_all_mmap(&self) -> Result<Vec<Vector>> {
+        self.get_range(0, self.count)
+    }
+
+    /// Scan the whole file, checking header/length consistency and per-slot checksums.
+    ///
+    /// The data file and sidecar are streamed a slot at a time, so memory use
+    /// doesn't grow with the file.
+    pub fn verify(&self) -> Result<VerifyReport> {
+        let file = File::open(&self.path)?;
+        let file_bytes = file.metadata()?.len();
+        let vec_bytes = self.dimension * 4;
+        let expected_bytes = (HEADER_SIZE + self.count * vec_bytes) as u64;
+
+        let mut data = BufReader::with_capacity(VERIFY_BUFFER_BYTES, file);
+        data.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        let mut checksums = match File::open(self.checksum_path()) {
+            Ok(file) => Some(BufReader::new(file)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let checksums_present = checksums.is_some();
+
+        let mut slots_checked = 0;
+        let mut first_corrupt_slot = None;
+        let mut slot_bytes = vec![0u8; vec_bytes];
+        let mut crc_bytes = [0u8; 4];
+
+        for slot in 0..self.count {
+            if !read_full(&mut data, &mut slot_bytes)? {
+                // Data file is shorter than the header claims
+                first_corrupt_slot = Some(slot);
+                break;
+            }
+
+            if let Some(checksums) = &mut checksums {
+                if !read_full(checksums, &mut crc_bytes)? {
+                    first_corrupt_slot = Some(slot);
+                    break;
+                }
+                slots_checked += 1;
+                if crc32fast::hash(&slot_bytes) != u32::from_le_bytes(crc_bytes) {
+                    first_corrupt_slot = Some(slot);
+                    break;
+                }
+            }
+        }
+
+        Ok(VerifyReport {
+            dimension: self.dimension,
+            count: self.count,
+            file_bytes,
+            expected_bytes,
+            checksums_present,
+            slots_checked,
+            first_corrupt_slot,
+        })
+    }
+
     /// Get the number of stored vectors.
     pub fn count(&self) -> usize {
         self.count
@@ -158,6 +357,35 @@ impl MmapVectorStorage {
         self.dimension
     }
 
+    /// CRC32s (u32 LE) of every slot currently in the data file, streamed
+    /// like `verify`.
+    fn checksum_existing_slots(&self) -> Result<Vec<u8>> {
+        let mut data = BufReader::with_capacity(VERIFY_BUFFER_BYTES, File::open(&self.path)?);
+        data.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        let mut slot_bytes = vec![0u8; self.dimension * 4];
+        let mut crcs = Vec::with_capacity(self.count * 4);
+        for slot in 0..self.count {
+            if !read_full(&mut data, &mut slot_bytes)? {
+                return Err(VectorDbError::StorageError(format!(
+                    "Vector file ends before slot {} of {}",
+                    slot, self.count
+                )));
+            }
+            crcs.extend_from_slice(&crc32fast::hash(&slot_bytes).to_le_bytes());
+        }
+        Ok(crcs)
+    }
+
+    fn checksum_path(&self) -> PathBuf {
+        Self::checksum_path_for(&self.path)
+    }
+
+    fn checksum_path_for(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".crc");
+        PathBuf::from(name)
+    }
+
     fn encode_header(dimension: usize, count: usize) -> [u8; HEADER_SIZE] {
         let mut buf = [0u8; HEADER_SIZE];
         buf[0..4].copy_from_slice(&(dimension as u32).to_le_bytes());
@@ -172,6 +400,15 @@ impl MmapVectorStorage {
     }
 }
 
+/// Fill `buf` from `reader`, or return false if it ends first.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +463,136 @@ mod tests {
         let result = storage.append(&Vector::new(vec![1.0, 2.0]));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_verify_clean_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut storage = MmapVectorStorage::create(&path, 3).unwrap();
+        for i in 0..5 {
+            storage
+                .append(&Vector::new(vec![i as f32, 1.0, 2.0]))
+                .unwrap();
+        }
+
+        let report = MmapVectorStorage::open(&path).unwrap().verify().unwrap();
+        assert!(report.is_ok());
+        assert!(report.checksums_present);
+        assert_eq!(report.slots_checked, 5);
+        assert_eq!(report.first_corrupt_slot, None);
+    }
+
+    #[test]
+    fn test_append_batch() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut storage = MmapVectorStorage::create(&path, 2).unwrap();
+        storage.append(&Vector::new(vec![0.0, 0.5])).unwrap();
+        let batch: Vec<Vector> = (1..4).map(|i| Vector::new(vec![i as f32, 0.5])).collect();
+        assert_eq!(storage.append_batch(&batch).unwrap(), 1);
+        assert_eq!(storage.append_batch(&[]).unwrap(), 4);
+
+        // A mismatch anywhere in the batch writes nothing
+        let mixed = [Vector::new(vec![9.0, 9.0]), Vector::new(vec![1.0])];
+        assert!(storage.append_batch(&mixed).is_err());
+        assert_eq!(storage.count(), 4);
+
+        let reopened = MmapVectorStorage::open(&path).unwrap();
+        assert_eq!(reopened.get(3).unwrap().as_slice(), &[3.0, 0.5]);
+        let report = reopened.verify().unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.slots_checked, 4);
+    }
+
+    #[test]
+    fn test_append_to_file_without_checksums() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        // A file written before checksums existed has no sidecar
+        let mut storage = MmapVectorStorage::create(&path, 2).unwrap();
+        storage.append(&Vector::new(vec![1.0, 2.0])).unwrap();
+        std::fs::remove_file(storage.checksum_path()).unwrap();
+        let mut legacy = MmapVectorStorage::open(&path).unwrap();
+        let report = legacy.verify().unwrap();
+        assert!(report.is_ok());
+        assert!(!report.checksums_present);
+
+        legacy.append(&Vector::new(vec![3.0, 4.0])).unwrap();
+        let report = legacy.verify().unwrap();
+        assert!(report.is_ok());
+        assert!(report.checksums_present);
+        assert_eq!(report.slots_checked, 2);
+        assert_eq!(report.first_corrupt_slot, None);
+    }
+
+    #[test]
+    fn test_verify_detects_flipped_byte() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut storage = MmapVectorStorage::create(&path, 3).unwrap();
+        for i in 0..5 {
+            storage
+                .append(&Vector::new(vec![i as f32, 1.0, 2.0]))
+                .unwrap();
+        }
+
+        // Flip one byte inside slot 3
+        let mut bytes = std::fs::read(&path).unwrap();
+        let offset = HEADER_SIZE + 3 * 12 + 5;
+        bytes[offset] ^= 0xFF;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let report = storage.verify().unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.first_corrupt_slot, Some(3));
+    }
+
+    #[test]
+    fn test_verify_detects_truncated_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut storage = MmapVectorStorage::create(&path, 2).unwrap();
+        storage.append(&Vector::new(vec![1.0, 2.0])).unwrap();
+        storage.append(&Vector::new(vec![3.0, 4.0])).unwrap();
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len((HEADER_SIZE + 8 + 4) as u64).unwrap();
+
+        let report = storage.verify().unwrap();
+        assert!(!report.is_ok());
+        assert_ne!(report.file_bytes, report.expected_bytes);
+        assert_eq!(report.first_corrupt_slot, Some(1));
+    }
+
+    #[test]
+    fn test_get_range_matches_get() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut storage = MmapVectorStorage::create(&path, 4).unwrap();
+        for i in 0..100 {
+            let x = i as f32;
+            storage.append(&Vector::new(vec![x, x + 0.5, -x, x * x])).unwrap();
+        }
+
+        let all = storage.get_range(0, 100).unwrap();
+        assert_eq!(all.len(), 100);
+        for (i, v) in all.iter().enumerate() {
+            assert_eq!(v, &storage.get(i).unwrap());
+        }
+        assert_eq!(storage.get_all_mmap().unwrap(), all);
+        assert_eq!(storage.get_range(40, 10).unwrap(), all[40..50].to_vec());
+        assert!(storage.get_range(100, 0).unwrap().is_empty());
+
+        assert!(matches!(
+            storage.get_range(95, 6),
+            Err(VectorDbError::IndexError(_))
+        ));
+        assert!(storage.get_range(usize::MAX, 2).is_err());
+    }
 }
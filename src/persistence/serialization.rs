@@ -1,6 +1,9 @@
 //! Serialization utilities: bincode for vectors/graph, JSON for metadata/config.
 
+use crate::distance::DistanceMetric;
 use crate::error::{Result, VectorDbError};
+use crate::hnsw::HnswParams;
+use crate::storage::Timestamps;
 use crate::vector::Vector;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -20,6 +23,54 @@ pub struct DatabaseSnapshot {
     pub metadata: HashMap<usize, HashMap<String, String>>,
     pub next_id: usize,
     pub dimension: Option<usize>,
+    /// Keyed by internal ID; vectors without an entry have no known times.
+    pub timestamps: HashMap<usize, Timestamps>,
+}
+
+/// Serializable representation of a single HNSW node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedHnswNode {
+    pub id: usize,
+    pub data: Vec<f32>,
+    /// Neighbor IDs per layer, from layer 0 up to `level`.
+    pub neighbors: Vec<Vec<usize>>,
+    pub level: usize,
+}
+
+/// Serializable representation of a full HNSW graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedHnswGraph {
+    /// Node slots indexed by internal ID; `None` for deleted slots.
+    pub nodes: Vec<Option<SerializedHnswNode>>,
+    pub entry_point: Option<usize>,
+    pub max_level: usize,
+    pub params: HnswParams,
+    pub metric: DistanceMetric,
+    /// Seed the graph was created with, if any.
+    pub seed: Option<u64>,
+}
+
+/// `SerializedHnswGraph` as written before the seed was recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedHnswGraphV1 {
+    pub nodes: Vec<Option<SerializedHnswNode>>,
+    pub entry_point: Option<usize>,
+    pub max_level: usize,
+    pub params: HnswParams,
+    pub metric: DistanceMetric,
+}
+
+impl From<SerializedHnswGraphV1> for SerializedHnswGraph {
+    fn from(graph: SerializedHnswGraphV1) -> Self {
+        Self {
+            nodes: graph.nodes,
+            entry_point: graph.entry_point,
+            max_level: graph.max_level,
+            params: graph.params,
+            metric: graph.metric,
+            seed: None,
+        }
+    }
 }
 
 /// Encode data to bincode bytes.
@@ -95,6 +146,7 @@ mod tests {
             metadata: HashMap::new(),
             next_id: 1,
             dimension: Some(2),
+            timestamps: HashMap::new(),
         };
         let bytes = to_bincode(&snapshot).unwrap();
         let decoded: DatabaseSnapshot = from_bincode(&bytes).unwrap();
@@ -1,21 +1,276 @@
-//! Snapshot: save/load full database state to/from disk.
+//! Snapshot: save/load full database state to/from disk, optionally as a
+//! base snapshot followed by deltas.
 
+use crate::distance::DistanceMetric;
 use crate::error::{Result, VectorDbError};
-use crate::persistence::serialization::{self, DatabaseSnapshot};
-use std::fs;
+use crate::persistence::serialization::{
+    self, DatabaseSnapshot, SerializedHnswGraph, SerializedVector,
+};
+use crate::persistence::wal::WalEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+/// Compression applied to `snapshot.bin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotCodec {
+    /// Plain bincode.
+    #[default]
+    None,
+    /// Bincode compressed with zstd.
+    Zstd,
+}
+
+impl SnapshotCodec {
+    /// Tag stored in the `snapshot.bin` header.
+    fn to_byte(self) -> u8 {
+        match self {
+            SnapshotCodec::None => 0,
+            SnapshotCodec::Zstd => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(SnapshotCodec::None),
+            1 => Ok(SnapshotCodec::Zstd),
+            b => Err(VectorDbError::SerializationError(format!(
+                "unknown snapshot codec {}",
+                b
+            ))),
+        }
+    }
+}
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub vector_count: usize,
+    pub next_id: usize,
+    pub dimension: Option<usize>,
+    /// Codec of the snapshot this manifest was written with. Loading uses the
+    /// one in the snapshot header and only falls back to this for snapshots
+    /// older than version 4. Missing in manifests written before compression
+    /// existed.
+    #[serde(default)]
+    pub codec: SnapshotCodec,
+    /// Metric the database was created with. Missing in older manifests.
+    #[serde(default)]
+    pub metric: Option<DistanceMetric>,
+}
+
+/// Inserts, deletes and renames logged between two checkpoints, stored as
+/// `delta.NNNN.bin` (plain bincode) on top of `snapshot.bin`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    /// Operations in the order they were applied.
+    pub entries: Vec<WalEntry>,
+}
+
+impl SnapshotDelta {
+    /// Replay this delta onto `snapshot`. Re-inserted IDs move to the end
+    /// with a fresh internal ID, as they would in a live store.
+    ///
+    /// Vectors are looked up through an ID -> position map built once per
+    /// delta, and deleted ones are only dropped at the end, so replay is
+    /// linear in the snapshot plus the delta.
+    fn apply_to(&self, snapshot: &mut DatabaseSnapshot) {
+        let mut slots: Vec<Option<SerializedVector>> =
+            std::mem::take(&mut snapshot.vectors).into_iter().map(Some).collect();
+        let mut positions: HashMap<String, usize> = slots
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(pos, v)| (v.string_id.clone(), pos))
+            .collect();
+
+        for entry in &self.entries {
+            match entry {
+                WalEntry::Insert {
+                    string_id,
+                    data,
+                    metadata,
+                    ..
+                } => {
+                    remove_vector(snapshot, &mut slots, &mut positions, string_id);
+                    let internal_id = snapshot.next_id;
+                    snapshot.next_id += 1;
+                    snapshot.dimension.get_or_insert(data.len());
+                    if !metadata.is_empty() {
+                        snapshot.metadata.insert(internal_id, metadata.clone());
+                    }
+                    slots.push(Some(SerializedVector {
+                        internal_id,
+                        string_id: string_id.clone(),
+                        data: data.clone(),
+                    }));
+                    positions.insert(string_id.clone(), slots.len() - 1);
+                }
+                WalEntry::Delete { string_id } => {
+                    remove_vector(snapshot, &mut slots, &mut positions, string_id)
+                }
+                WalEntry::Rename { old_id, new_id } => {
+                    if let Some(pos) = positions.remove(old_id) {
+                        if let Some(v) = &mut slots[pos] {
+                            v.string_id = new_id.clone();
+                        }
+                        positions.insert(new_id.clone(), pos);
+                    }
+                }
+                WalEntry::Timestamps {
+                    string_id,
+                    timestamps,
+                } => {
+                    if let Some(Some(v)) = positions.get(string_id).map(|&pos| &slots[pos]) {
+                        snapshot.timestamps.insert(v.internal_id, *timestamps);
+                    }
+                }
+                WalEntry::Checkpoint => {}
+            }
+        }
+        snapshot.vectors = slots.into_iter().flatten().collect();
+    }
+}
+
+fn remove_vector(
+    snapshot: &mut DatabaseSnapshot,
+    slots: &mut [Option<SerializedVector>],
+    positions: &mut HashMap<String, usize>,
+    string_id: &str,
+) {
+    if let Some(removed) = positions.remove(string_id).and_then(|pos| slots[pos].take()) {
+        snapshot.metadata.remove(&removed.internal_id);
+        snapshot.timestamps.remove(&removed.internal_id);
+    }
+}
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Magic bytes at the start of every versioned `snapshot.bin`.
+const SNAPSHOT_MAGIC: &[u8; 4] = b"VDBS";
+
+/// Layout version of `snapshot.bin` written by this build. Bump it when
+/// the snapshot layout changes and teach `migrate` to read the old one.
+///
+/// Version 3 adds the number of the last delta folded into the base to the
+/// header, and version 4 the codec; the payload is the same as version 2.
+pub const SNAPSHOT_VERSION: u16 = 4;
+
+/// Longest header `split_header` reads: magic, version, codec, last folded delta.
+const HEADER_LEN: usize = 4 + 2 + 1 + 8;
+
+/// `DatabaseSnapshot` layout of versions 0 and 1, before timestamps.
+#[derive(Serialize, Deserialize)]
+struct SnapshotV1 {
+    vectors: Vec<SerializedVector>,
+    metadata: HashMap<usize, HashMap<String, String>>,
+    next_id: usize,
+    dimension: Option<usize>,
+}
+
+impl From<SnapshotV1> for DatabaseSnapshot {
+    fn from(old: SnapshotV1) -> Self {
+        Self {
+            vectors: old.vectors,
+            metadata: old.metadata,
+            next_id: old.next_id,
+            dimension: old.dimension,
+            timestamps: HashMap::new(),
+        }
+    }
+}
+
+/// Fields of the `snapshot.bin` header.
+#[derive(Debug)]
+struct SnapshotHeader {
+    version: u16,
+    /// Codec of the payload; None before version 4, where only the manifest
+    /// records it.
+    codec: Option<SnapshotCodec>,
+    /// Number of the last delta folded into the base; 0 before version 3.
+    folded_deltas: usize,
+}
+
+/// Split `snapshot.bin` into its header and payload. Files written before
+/// the header existed have no magic and are reported as version 0. Only the
+/// version is read from files newer than this build.
+fn split_header(data: &[u8]) -> Result<(SnapshotHeader, &[u8])> {
+    let mut header = SnapshotHeader {
+        version: 0,
+        codec: None,
+        folded_deltas: 0,
+    };
+    let rest = match data.strip_prefix(SNAPSHOT_MAGIC.as_slice()) {
+        Some(rest) if rest.len() >= 2 => rest,
+        _ => return Ok((header, data)),
+    };
+    header.version = u16::from_le_bytes([rest[0], rest[1]]);
+    let mut rest = &rest[2..];
+    if header.version > SNAPSHOT_VERSION {
+        return Ok((header, rest));
+    }
+
+    let truncated = || VectorDbError::SerializationError("truncated snapshot header".to_string());
+    if header.version >= 4 {
+        let (&codec, tail) = rest.split_first().ok_or_else(truncated)?;
+        header.codec = Some(SnapshotCodec::from_byte(codec)?);
+        rest = tail;
+    }
+    if header.version >= 3 {
+        let folded = rest.get(..8).ok_or_else(truncated)?;
+        header.folded_deltas = u64::from_le_bytes(folded.try_into().expect("8 bytes")) as usize;
+        rest = &rest[8..];
+    }
+    Ok((header, rest))
+}
+
+fn unsupported_version(version: u16) -> VectorDbError {
+    VectorDbError::SerializationError(format!("unsupported snapshot version {}", version))
+}
+
+/// Decode a snapshot payload written with layout `version`, upgrading older
+/// layouts to the current `DatabaseSnapshot`.
+fn migrate(version: u16, payload: &[u8]) -> Result<DatabaseSnapshot> {
+    match version {
+        // Version 0 predates the header; its layout is the same as version 1.
+        0 | 1 => Ok(serialization::from_bincode::<SnapshotV1>(payload)?.into()),
+        2..=SNAPSHOT_VERSION => serialization::from_bincode(payload),
+        v => Err(unsupported_version(v)),
+    }
+}
+
 /// Manages saving and loading database snapshots.
 pub struct SnapshotManager {
     dir: PathBuf,
+    codec: SnapshotCodec,
+    metric: Option<DistanceMetric>,
 }
 
 impl SnapshotManager {
-    /// Create a snapshot manager for the given directory.
+    /// Create a snapshot manager for the given directory that writes uncompressed snapshots.
     pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::with_codec(dir, SnapshotCodec::None)
+    }
+
+    /// Create a snapshot manager that compresses new snapshots with `codec`.
+    /// Existing snapshots load regardless of the codec they were written with.
+    pub fn with_codec(dir: impl AsRef<Path>, codec: SnapshotCodec) -> Result<Self> {
         let dir = dir.as_ref().to_path_buf();
         fs::create_dir_all(&dir)?;
-        Ok(Self { dir })
+        Ok(Self {
+            dir,
+            codec,
+            metric: None,
+        })
+    }
+
+    /// Record `metric` in every manifest this manager writes.
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = Some(metric);
+        self
     }
 
     fn snapshot_path(&self) -> PathBuf {
@@ -26,35 +281,217 @@ impl SnapshotManager {
         self.dir.join("manifest.json")
     }
 
-    /// Save a database snapshot to disk.
+    fn delta_path(&self, n: usize) -> PathBuf {
+        self.dir.join(format!("delta.{:04}.bin", n))
+    }
+
+    /// Sequence numbers and paths of all delta files on disk, in the order
+    /// they were written, including any already folded into the base.
+    fn delta_paths(&self) -> Result<Vec<(usize, PathBuf)>> {
+        let mut deltas = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            let n = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("delta."))
+                .and_then(|rest| rest.strip_suffix(".bin"))
+                .and_then(|n| n.parse::<usize>().ok());
+            if let Some(n) = n {
+                deltas.push((n, path));
+            }
+        }
+        deltas.sort_by_key(|(n, _)| *n);
+        Ok(deltas)
+    }
+
+    /// Number of the last delta folded into `snapshot.bin`, read from its header.
+    fn folded_deltas(&self) -> Result<usize> {
+        let path = self.snapshot_path();
+        if !path.exists() {
+            return Ok(0);
+        }
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        File::open(path)?.take(HEADER_LEN as u64).read_to_end(&mut header)?;
+        Ok(split_header(&header)?.0.folded_deltas)
+    }
+
+    /// Deltas written on top of the current base, in order. Deltas a crash
+    /// left behind after they were folded into the base are skipped.
+    fn live_delta_paths(&self) -> Result<Vec<(usize, PathBuf)>> {
+        let folded = self.folded_deltas()?;
+        let mut deltas = self.delta_paths()?;
+        deltas.retain(|(n, _)| *n > folded);
+        Ok(deltas)
+    }
+
+    /// Number of deltas written since the last full snapshot.
+    pub fn delta_count(&self) -> Result<usize> {
+        Ok(self.live_delta_paths()?.len())
+    }
+
+    /// Replace `path` with `bytes` atomically: write and sync a temp file,
+    /// rename it over `path`, then sync the directory so the rename sticks.
+    fn write_atomic(&self, path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        self.sync_dir()
+    }
+
+    fn sync_dir(&self) -> Result<()> {
+        // Directories can't be opened for syncing on every platform
+        #[cfg(unix)]
+        File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+
+    /// Save a database snapshot to disk as the new base, discarding any deltas
+    /// it supersedes.
+    ///
+    /// The snapshot and manifest are each replaced atomically, and deltas are
+    /// only removed once both are in place. The header records the last delta
+    /// the new base covers, so deltas a crash leaves behind aren't replayed
+    /// on top of it.
     pub fn save(&self, snapshot: &DatabaseSnapshot) -> Result<()> {
-        // Write snapshot data (bincode)
+        let deltas = self.delta_paths()?;
+        let folded = deltas.last().map_or(0, |(n, _)| *n).max(self.folded_deltas()?);
+
+        // Write snapshot data (version header, then bincode, optionally compressed)
         let data = serialization::to_bincode(snapshot)?;
-        fs::write(self.snapshot_path(), &data)?;
+        let data = match self.codec {
+            SnapshotCodec::None => data,
+            SnapshotCodec::Zstd => zstd::encode_all(data.as_slice(), ZSTD_LEVEL)?,
+        };
+        let mut file = Vec::with_capacity(HEADER_LEN + data.len());
+        file.extend_from_slice(SNAPSHOT_MAGIC);
+        file.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        file.push(self.codec.to_byte());
+        file.extend_from_slice(&(folded as u64).to_le_bytes());
+        file.extend_from_slice(&data);
+        self.write_atomic(&self.snapshot_path(), &file)?;
 
         // Write manifest (JSON) for human-readable metadata
-        let manifest = serde_json::json!({
-            "vector_count": snapshot.vectors.len(),
-            "next_id": snapshot.next_id,
-            "dimension": snapshot.dimension,
-        });
-        let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        self.write_manifest(&SnapshotManifest {
+            vector_count: snapshot.vectors.len(),
+            next_id: snapshot.next_id,
+            dimension: snapshot.dimension,
+            codec: self.codec,
+            metric: self.metric,
+        })?;
+
+        for (_, path) in deltas {
+            fs::remove_file(path)?;
+        }
+        self.sync_dir()
+    }
+
+    /// Write the operations since the last checkpoint as the next delta.
+    /// Returns the delta's sequence number, which keeps counting up from the
+    /// deltas folded into the base.
+    pub fn save_delta(&self, delta: &SnapshotDelta) -> Result<usize> {
+        let last = self.delta_paths()?.last().map_or(0, |(n, _)| *n);
+        let n = last.max(self.folded_deltas()?) + 1;
+        self.write_atomic(&self.delta_path(n), &serialization::to_bincode(delta)?)?;
+        Ok(n)
+    }
+
+    /// Write a manifest for an empty database if none exists yet, so the
+    /// metric is on record before the first snapshot.
+    pub fn ensure_manifest(&self) -> Result<()> {
+        if self.manifest_path().exists() {
+            return Ok(());
+        }
+        self.write_manifest(&SnapshotManifest {
+            vector_count: 0,
+            next_id: 0,
+            dimension: None,
+            codec: self.codec,
+            metric: self.metric,
+        })
+    }
+
+    fn write_manifest(&self, manifest: &SnapshotManifest) -> Result<()> {
+        let manifest_bytes = serde_json::to_vec_pretty(manifest)
             .map_err(|e| VectorDbError::SerializationError(e.to_string()))?;
-        fs::write(self.manifest_path(), &manifest_bytes)?;
+        self.write_atomic(&self.manifest_path(), &manifest_bytes)
+    }
 
-        Ok(())
+    /// Read `manifest.json`, or return None if it doesn't exist.
+    pub fn manifest(&self) -> Result<Option<SnapshotManifest>> {
+        let path = self.manifest_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(&path)?;
+        let manifest = serde_json::from_slice(&bytes)
+            .map_err(|e| VectorDbError::SerializationError(e.to_string()))?;
+        Ok(Some(manifest))
     }
 
-    /// Load a database snapshot from disk, or return None if no snapshot exists.
+    /// Load a database snapshot from disk with every delta applied in order,
+    /// or return None if no snapshot exists.
+    ///
+    /// The codec is read from the snapshot header; snapshots older than
+    /// version 4 take it from the manifest, or are treated as uncompressed
+    /// without one. Snapshots from a newer, unknown layout version fail with
+    /// a `SerializationError`.
     pub fn load(&self) -> Result<Option<DatabaseSnapshot>> {
+        let Some(mut snapshot) = self.load_base()? else {
+            return Ok(None);
+        };
+        for (_, path) in self.live_delta_paths()? {
+            let delta: SnapshotDelta = serialization::from_bincode(&fs::read(path)?)?;
+            delta.apply_to(&mut snapshot);
+        }
+        Ok(Some(snapshot))
+    }
+
+    fn load_base(&self) -> Result<Option<DatabaseSnapshot>> {
         let path = self.snapshot_path();
         if !path.exists() {
             return Ok(None);
         }
 
+        let file = fs::read(&path)?;
+        let (header, data) = split_header(&file)?;
+        // Reject unknown versions before trying to decompress them
+        if header.version > SNAPSHOT_VERSION {
+            return Err(unsupported_version(header.version));
+        }
+        let codec = match header.codec {
+            Some(codec) => codec,
+            None => self.manifest()?.map(|m| m.codec).unwrap_or_default(),
+        };
+        let data = match codec {
+            SnapshotCodec::None => data.to_vec(),
+            SnapshotCodec::Zstd => zstd::decode_all(data)?,
+        };
+        Ok(Some(migrate(header.version, &data)?))
+    }
+
+    fn graph_path(&self) -> PathBuf {
+        self.dir.join("graph.bin")
+    }
+
+    /// Save an HNSW graph snapshot to disk alongside the vector snapshot,
+    /// replacing any earlier one atomically.
+    pub fn save_graph(&self, graph: &SerializedHnswGraph) -> Result<()> {
+        self.write_atomic(&self.graph_path(), &serialization::to_bincode(graph)?)
+    }
+
+    /// Load an HNSW graph snapshot, or return None if none was saved.
+    pub fn load_graph(&self) -> Result<Option<SerializedHnswGraph>> {
+        let path = self.graph_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
         let data = fs::read(&path)?;
-        let snapshot: DatabaseSnapshot = serialization::from_bincode(&data)?;
-        Ok(Some(snapshot))
+        Ok(Some(serialization::from_bincode(&data)?))
     }
 
     /// Check if a snapshot exists.
@@ -91,6 +528,7 @@ mod tests {
             metadata: HashMap::new(),
             next_id: 2,
             dimension: Some(3),
+            timestamps: HashMap::new(),
         };
 
         mgr.save(&snapshot).unwrap();
@@ -104,6 +542,251 @@ mod tests {
         assert_eq!(loaded.vectors[1].data, vec![4.0, 5.0, 6.0]);
     }
 
+    fn repetitive_snapshot() -> DatabaseSnapshot {
+        DatabaseSnapshot {
+            vectors: (0..500)
+                .map(|i| SerializedVector {
+                    internal_id: i,
+                    string_id: format!("v{}", i),
+                    data: vec![(i % 4) as f32; 64],
+                })
+                .collect(),
+            metadata: HashMap::new(),
+            next_id: 500,
+            dimension: Some(64),
+            timestamps: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_zstd_roundtrip_is_smaller() {
+        let dir = TempDir::new().unwrap();
+        let plain = SnapshotManager::new(dir.path().join("plain")).unwrap();
+        let packed =
+            SnapshotManager::with_codec(dir.path().join("zstd"), SnapshotCodec::Zstd).unwrap();
+
+        let snapshot = repetitive_snapshot();
+        plain.save(&snapshot).unwrap();
+        packed.save(&snapshot).unwrap();
+
+        let plain_len = fs::metadata(plain.snapshot_path()).unwrap().len();
+        let packed_len = fs::metadata(packed.snapshot_path()).unwrap().len();
+        assert!(
+            packed_len < plain_len / 4,
+            "{} vs {}",
+            packed_len,
+            plain_len
+        );
+        assert_eq!(
+            packed.manifest().unwrap().unwrap().codec,
+            SnapshotCodec::Zstd
+        );
+
+        // The codec comes from the snapshot header, not the manager doing the
+        // loading, and a stale manifest (e.g. from a crash mid-save) can't
+        // contradict it
+        let reader = SnapshotManager::new(dir.path().join("zstd")).unwrap();
+        fs::write(
+            reader.manifest_path(),
+            r#"{"vector_count": 500, "next_id": 500, "dimension": 64, "codec": "none"}"#,
+        )
+        .unwrap();
+        assert_eq!(reader.manifest().unwrap().unwrap().codec, SnapshotCodec::None);
+        let loaded = reader.load().unwrap().unwrap();
+        assert_eq!(loaded.vectors.len(), 500);
+        assert_eq!(loaded.vectors[7].data, vec![3.0; 64]);
+    }
+
+    #[test]
+    fn test_load_version_3_snapshot_uses_manifest_codec() {
+        let dir = TempDir::new().unwrap();
+        let mgr =
+            SnapshotManager::with_codec(dir.path().join("db"), SnapshotCodec::Zstd).unwrap();
+        mgr.save(&repetitive_snapshot()).unwrap();
+
+        // Version 3 had no codec byte; the manifest says zstd
+        let mut file = fs::read(mgr.snapshot_path()).unwrap();
+        file[4..6].copy_from_slice(&3u16.to_le_bytes());
+        file.remove(6);
+        fs::write(mgr.snapshot_path(), &file).unwrap();
+
+        let reader = SnapshotManager::new(dir.path().join("db")).unwrap();
+        assert_eq!(reader.load().unwrap().unwrap().vectors.len(), 500);
+    }
+
+    #[test]
+    fn test_load_legacy_manifest_without_codec() {
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        mgr.save(&repetitive_snapshot()).unwrap();
+
+        // Manifests written before compression existed have no codec field
+        fs::write(
+            mgr.manifest_path(),
+            r#"{"vector_count": 500, "next_id": 500, "dimension": 64}"#,
+        )
+        .unwrap();
+
+        assert_eq!(mgr.manifest().unwrap().unwrap().codec, SnapshotCodec::None);
+        assert_eq!(mgr.load().unwrap().unwrap().vectors.len(), 500);
+    }
+
+    #[test]
+    fn test_deltas_apply_in_order_and_collapse() {
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        mgr.save(&repetitive_snapshot()).unwrap();
+
+        let insert = |id: &str, x: f32| WalEntry::Insert {
+            string_id: id.to_string(),
+            internal_id: 0,
+            data: vec![x; 64],
+            metadata: HashMap::from([("tag".to_string(), id.to_string())]),
+        };
+        let delete = |id: &str| WalEntry::Delete {
+            string_id: id.to_string(),
+        };
+        let first = SnapshotDelta {
+            entries: vec![insert("new", 1.0), delete("v0"), insert("v1", 9.0)],
+        };
+        let rename = WalEntry::Rename {
+            old_id: "v2".to_string(),
+            new_id: "moved".to_string(),
+        };
+        let second = SnapshotDelta {
+            entries: vec![delete("new"), insert("new", 2.0), rename],
+        };
+        assert_eq!(mgr.save_delta(&first).unwrap(), 1);
+        assert_eq!(mgr.save_delta(&second).unwrap(), 2);
+        assert!(mgr.delta_path(2).exists());
+
+        let loaded = mgr.load().unwrap().unwrap();
+        assert_eq!(loaded.vectors.len(), 500);
+        assert!(loaded.vectors.iter().all(|v| v.string_id != "v0"));
+        let find = |id: &str| loaded.vectors.iter().find(|v| v.string_id == id).unwrap();
+        assert_eq!(find("new").data, vec![2.0; 64]);
+        assert_eq!(find("v1").data, vec![9.0; 64]);
+        assert_eq!(loaded.metadata[&find("v1").internal_id]["tag"], "v1");
+        assert!(loaded.vectors.iter().all(|v| v.string_id != "v2"));
+        assert_eq!(find("moved").internal_id, 2);
+
+        // A new base absorbs the deltas
+        mgr.save(&loaded).unwrap();
+        assert_eq!(mgr.delta_count().unwrap(), 0);
+        assert_eq!(mgr.load().unwrap().unwrap().vectors.len(), 500);
+    }
+
+    #[test]
+    fn test_deltas_left_by_crash_are_not_replayed() {
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        mgr.save(&repetitive_snapshot()).unwrap();
+
+        let rename = |old_id: &str, new_id: &str| WalEntry::Rename {
+            old_id: old_id.to_string(),
+            new_id: new_id.to_string(),
+        };
+        // Replaying these twice would move v1's vector onto "a" as well
+        let delta = SnapshotDelta {
+            entries: vec![rename("v0", "a"), rename("v1", "v0"), rename("a", "v1")],
+        };
+        assert_eq!(mgr.save_delta(&delta).unwrap(), 1);
+        let delta_bytes = fs::read(mgr.delta_path(1)).unwrap();
+        let folded = mgr.load().unwrap().unwrap();
+
+        // Crash after the new base is in place but before the delta is removed
+        mgr.save(&folded).unwrap();
+        fs::write(mgr.delta_path(1), &delta_bytes).unwrap();
+
+        assert_eq!(mgr.delta_count().unwrap(), 0);
+        let loaded = mgr.load().unwrap().unwrap();
+        let find = |id: &str| loaded.vectors.iter().find(|v| v.string_id == id).unwrap();
+        assert_eq!(find("v0").internal_id, 1);
+        assert_eq!(find("v1").internal_id, 0);
+        assert!(loaded.vectors.iter().all(|v| v.string_id != "a"));
+
+        // New deltas number past the folded one and are replayed
+        let next = SnapshotDelta {
+            entries: vec![rename("v2", "b")],
+        };
+        assert_eq!(mgr.save_delta(&next).unwrap(), 2);
+        assert_eq!(mgr.delta_count().unwrap(), 1);
+        let loaded = mgr.load().unwrap().unwrap();
+        assert!(loaded.vectors.iter().any(|v| v.string_id == "b"));
+
+        mgr.save(&loaded).unwrap();
+        let names: Vec<_> = fs::read_dir(dir.path().join("db"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert!(names.iter().all(|name| !name.starts_with("delta.") && !name.ends_with(".tmp")));
+    }
+
+    #[test]
+    fn test_snapshot_version_header() {
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        mgr.save(&repetitive_snapshot()).unwrap();
+
+        let mut file = fs::read(mgr.snapshot_path()).unwrap();
+        assert_eq!(&file[..4], SNAPSHOT_MAGIC);
+        assert_eq!(split_header(&file).unwrap().0.version, SNAPSHOT_VERSION);
+        assert_eq!(mgr.load().unwrap().unwrap().vectors.len(), 500);
+
+        // A snapshot from a newer build is refused with a clear message
+        let next = SNAPSHOT_VERSION + 1;
+        file[4..6].copy_from_slice(&next.to_le_bytes());
+        fs::write(mgr.snapshot_path(), &file).unwrap();
+        match mgr.load() {
+            Err(VectorDbError::SerializationError(msg)) => {
+                assert_eq!(msg, format!("unsupported snapshot version {}", next));
+            }
+            other => panic!("expected a version error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_load_version_1_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        let old = SnapshotV1 {
+            vectors: vec![SerializedVector {
+                internal_id: 0,
+                string_id: "v0".to_string(),
+                data: vec![1.0, 2.0],
+            }],
+            metadata: HashMap::new(),
+            next_id: 1,
+            dimension: Some(2),
+        };
+        let mut file = SNAPSHOT_MAGIC.to_vec();
+        file.extend_from_slice(&1u16.to_le_bytes());
+        file.extend_from_slice(&serialization::to_bincode(&old).unwrap());
+        fs::write(mgr.snapshot_path(), file).unwrap();
+
+        let loaded = mgr.load().unwrap().unwrap();
+        assert_eq!(loaded.vectors[0].string_id, "v0");
+        assert!(loaded.timestamps.is_empty());
+    }
+
+    #[test]
+    fn test_load_headerless_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let plain = SnapshotManager::new(dir.path().join("plain")).unwrap();
+        let packed =
+            SnapshotManager::with_codec(dir.path().join("zstd"), SnapshotCodec::Zstd).unwrap();
+
+        // Snapshots written before the version header existed still load
+        for mgr in [&plain, &packed] {
+            mgr.save(&repetitive_snapshot()).unwrap();
+            let file = fs::read(mgr.snapshot_path()).unwrap();
+            fs::write(mgr.snapshot_path(), &file[HEADER_LEN..]).unwrap();
+            let loaded = mgr.load().unwrap().unwrap();
+            assert_eq!(loaded.vectors.len(), 500);
+            assert_eq!(loaded.vectors[7].data, vec![3.0; 64]);
+        }
+    }
+
     #[test]
     fn test_load_nonexistent() {
         let dir = TempDir::new().unwrap();
@@ -111,4 +794,30 @@ mod tests {
         assert!(!mgr.exists());
         assert!(mgr.load().unwrap().is_none());
     }
+
+    #[test]
+    fn test_save_and_load_graph() {
+        use crate::distance::DistanceMetric;
+        use crate::hnsw::{HnswGraph, HnswParams};
+        use crate::vector::Vector;
+
+        let dir = TempDir::new().unwrap();
+        let mgr = SnapshotManager::new(dir.path().join("db")).unwrap();
+        assert!(mgr.load_graph().unwrap().is_none());
+
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean, HnswParams::new(4, 32, 16));
+        for i in 0..30 {
+            graph
+                .insert(i, Vector::new(vec![i as f32, (i * 3 % 11) as f32]))
+                .unwrap();
+        }
+        mgr.save_graph(&graph.to_serialized()).unwrap();
+
+        let loaded = HnswGraph::from_serialized(mgr.load_graph().unwrap().unwrap()).unwrap();
+        let query = Vector::new(vec![12.5, 4.0]);
+        assert_eq!(
+            graph.search_knn(&query, 5, 16).unwrap(),
+            loaded.search_knn(&query, 5, 16).unwrap()
+        );
+    }
 }
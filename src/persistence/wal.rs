@@ -1,11 +1,17 @@
 //! Write-Ahead Log (WAL) for crash recovery.
 //!
 //! Each entry is written as: [length: u32][crc32: u32][payload: bincode(WalEntry)]
-//! The WAL is append-only and fsynced after each write.
+//! The WAL is append-only and fsynced after each write; `append_batch` writes
+//! several records and fsyncs once (group commit).
+//!
+//! Logs written before inserts carried metadata are still readable: payloads
+//! that don't decode as the current `WalEntry` are retried as `LegacyWalEntry`.
 
-use crate::error::{Result, VectorDbError};
+use crate::error::Result;
 use crate::persistence::serialization;
+use crate::storage::Timestamps;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
@@ -13,6 +19,33 @@ use std::path::{Path, PathBuf};
 /// A single WAL entry.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum WalEntry {
+    Insert {
+        string_id: String,
+        internal_id: usize,
+        data: Vec<f32>,
+        metadata: HashMap<String, String>,
+    },
+    Delete {
+        string_id: String,
+    },
+    Checkpoint,
+    /// Added after `Checkpoint` so older logs keep their variant indices.
+    Rename {
+        old_id: String,
+        new_id: String,
+    },
+    /// Creation and update times for the vector just inserted as `string_id`,
+    /// logged in the same group commit as its `Insert`. Inserts from before
+    /// timestamps were recorded have none and get the replay time instead.
+    Timestamps {
+        string_id: String,
+        timestamps: Timestamps,
+    },
+}
+
+/// WAL entry layout from before `Insert` carried metadata.
+#[derive(Debug, Serialize, Deserialize)]
+enum LegacyWalEntry {
     Insert {
         string_id: String,
         internal_id: usize,
@@ -24,33 +57,125 @@ pub enum WalEntry {
     Checkpoint,
 }
 
+impl From<LegacyWalEntry> for WalEntry {
+    fn from(entry: LegacyWalEntry) -> Self {
+        match entry {
+            LegacyWalEntry::Insert {
+                string_id,
+                internal_id,
+                data,
+            } => WalEntry::Insert {
+                string_id,
+                internal_id,
+                data,
+                metadata: HashMap::new(),
+            },
+            LegacyWalEntry::Delete { string_id } => WalEntry::Delete { string_id },
+            LegacyWalEntry::Checkpoint => WalEntry::Checkpoint,
+        }
+    }
+}
+
+/// Decode a WAL payload, falling back to the legacy layout for old logs.
+fn decode_entry(payload: &[u8]) -> Result<WalEntry> {
+    serialization::from_bincode::<WalEntry>(payload).or_else(|e| {
+        serialization::from_bincode::<LegacyWalEntry>(payload)
+            .map(WalEntry::from)
+            .map_err(|_| e)
+    })
+}
+
+/// Frame a payload as `[length][crc32][payload]`.
+fn encode_record(payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(8 + payload.len());
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    record.extend_from_slice(payload);
+    record
+}
+
 /// Write-Ahead Log file manager.
+///
+/// The log is split into segments: the first lives at the path given to
+/// `open`, later ones alongside it as `<stem>.00002.<ext>`, `<stem>.00003.<ext>`
+/// and so on. A new segment is started once the current one reaches
+/// `max_segment_bytes`.
 pub struct WriteAheadLog {
     path: PathBuf,
     file: File,
+    segment: u32,
+    segment_bytes: u64,
+    max_segment_bytes: u64,
 }
 
 impl WriteAheadLog {
-    /// Open (or create) a WAL file at the given path.
+    /// Open (or create) a WAL file at the given path, without size-based rotation.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_max_segment_bytes(path, u64::MAX)
+    }
+
+    /// Open (or create) a WAL that rotates to a new segment once the current
+    /// one holds at least `max_segment_bytes`.
+    pub fn open_with_max_segment_bytes(
+        path: impl AsRef<Path>,
+        max_segment_bytes: u64,
+    ) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
+        let segment = Self::existing_segments(&path)?
+            .last()
+            .map(|(n, _)| *n)
+            .unwrap_or(1);
         let file = OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&path)?;
-        Ok(Self { path, file })
+            .open(segment_path(&path, segment))?;
+        let segment_bytes = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            segment,
+            segment_bytes,
+            max_segment_bytes,
+        })
     }
 
     /// Append an entry to the WAL and fsync.
     pub fn append(&mut self, entry: &WalEntry) -> Result<()> {
         let payload = serialization::to_bincode(entry)?;
-        let crc = crc32fast::hash(&payload);
-        let len = payload.len() as u32;
+        self.write_records(&encode_record(&payload))
+    }
+
+    /// Append several entries with a single fsync.
+    ///
+    /// Records are framed individually, so a crash mid-batch leaves a torn
+    /// tail that replay stops at, keeping every fully written record before it.
+    pub fn append_batch(&mut self, entries: &[WalEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
 
-        self.file.write_all(&len.to_le_bytes())?;
-        self.file.write_all(&crc.to_le_bytes())?;
-        self.file.write_all(&payload)?;
+        let mut buf = Vec::new();
+        for entry in entries {
+            let payload = serialization::to_bincode(entry)?;
+            buf.extend_from_slice(&encode_record(&payload));
+        }
+        self.write_records(&buf)
+    }
+
+    /// Write already-framed records, fsync, and rotate if the segment is full.
+    fn write_records(&mut self, buf: &[u8]) -> Result<()> {
+        self.file.write_all(buf)?;
         self.sync()?;
+        self.segment_bytes += buf.len() as u64;
+
+        if self.segment_bytes >= self.max_segment_bytes {
+            self.segment += 1;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(segment_path(&self.path, self.segment))?;
+            self.segment_bytes = 0;
+        }
 
         Ok(())
     }
@@ -61,63 +186,242 @@ impl WriteAheadLog {
         Ok(())
     }
 
-    /// Replay all valid entries from the WAL.
-    /// Stops at the first corrupted or incomplete entry (crash tolerance).
-    pub fn replay(&self) -> Result<Vec<WalEntry>> {
-        let file = File::open(&self.path)?;
-        let mut reader = BufReader::new(file);
-        let mut entries = Vec::new();
-
-        loop {
-            // Read length
-            let mut len_buf = [0u8; 4];
-            match reader.read_exact(&mut len_buf) {
-                Ok(()) => {}
-                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-                Err(e) => return Err(VectorDbError::IoError(e)),
-            }
-            let len = u32::from_le_bytes(len_buf) as usize;
+    /// Number of the segment currently being appended to (the first is 1).
+    pub fn current_segment(&self) -> u32 {
+        self.segment
+    }
 
-            // Read CRC
-            let mut crc_buf = [0u8; 4];
-            match reader.read_exact(&mut crc_buf) {
-                Ok(()) => {}
-                Err(_) => break, // Truncated — stop
-            }
-            let expected_crc = u32::from_le_bytes(crc_buf);
+    /// Paths of all segment files on disk, in replay order.
+    pub fn segment_paths(&self) -> Result<Vec<PathBuf>> {
+        Ok(Self::existing_segments(&self.path)?
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect())
+    }
 
-            // Read payload
-            let mut payload = vec![0u8; len];
-            match reader.read_exact(&mut payload) {
-                Ok(()) => {}
-                Err(_) => break, // Truncated — stop
-            }
+    /// Replay all valid entries from the WAL, reading segments in order.
+    /// Stops at the first corrupted or incomplete entry (crash tolerance).
+    pub fn replay(&self) -> Result<Vec<WalEntry>> {
+        Self::replay_path(&self.path)
+    }
 
-            // Verify CRC
-            let actual_crc = crc32fast::hash(&payload);
-            if actual_crc != expected_crc {
-                break; // Corrupted — stop
+    /// Like `replay`, for the WAL at `path`, without opening it for writing.
+    pub fn replay_path(path: impl AsRef<Path>) -> Result<Vec<WalEntry>> {
+        let mut entries = Vec::new();
+        for (_, path) in Self::existing_segments(path.as_ref())? {
+            if read_segment(&path, &mut |entry| entries.push(entry))?.is_some() {
+                break;
             }
+        }
+        Ok(entries)
+    }
 
-            // Deserialize
-            match serialization::from_bincode::<WalEntry>(&payload) {
-                Ok(entry) => entries.push(entry),
-                Err(_) => break, // Corrupted — stop
+    /// Scan every segment without applying anything, reporting how many
+    /// entries are valid and where (and why) replay would stop.
+    pub fn verify(&self) -> Result<WalReport> {
+        let paths = self.segment_paths()?;
+        let mut report = WalReport {
+            segments: paths.len(),
+            valid_entries: 0,
+            total_bytes: 0,
+            stop: None,
+        };
+        for path in &paths {
+            report.total_bytes += std::fs::metadata(path)?.len();
+        }
+        for path in paths {
+            if let Some((offset, reason)) =
+                read_segment(&path, &mut |_| report.valid_entries += 1)?
+            {
+                report.stop = Some(WalStop {
+                    segment: path,
+                    offset,
+                    reason,
+                });
+                break;
             }
         }
-
-        Ok(entries)
+        Ok(report)
     }
 
-    /// Truncate the WAL file (after a successful checkpoint).
+    /// Truncate the WAL (after a successful checkpoint): later segments are
+    /// deleted and appends restart in the first one.
     pub fn truncate(&mut self) -> Result<()> {
+        for (n, path) in Self::existing_segments(&self.path)? {
+            if n > 1 {
+                std::fs::remove_file(path)?;
+            }
+        }
         self.file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
             .open(&self.path)?;
+        self.segment = 1;
+        self.segment_bytes = 0;
         Ok(())
     }
+
+    /// Find segment files belonging to the WAL at `base`, sorted by number.
+    fn existing_segments(base: &Path) -> Result<Vec<(u32, PathBuf)>> {
+        let mut segments = Vec::new();
+        if base.exists() {
+            segments.push((1, base.to_path_buf()));
+        }
+
+        let dir = match base.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if dir.is_dir() {
+            for dir_entry in std::fs::read_dir(dir)? {
+                let path = dir_entry?.path();
+                if let Some(n) = segment_number(base, &path) {
+                    segments.push((n, path));
+                }
+            }
+        }
+
+        segments.sort_by_key(|(n, _)| *n);
+        Ok(segments)
+    }
+}
+
+/// Path of segment `n` for the WAL at `base`. Segment 1 is `base` itself.
+fn segment_path(base: &Path, n: u32) -> PathBuf {
+    if n <= 1 {
+        return base.to_path_buf();
+    }
+    let stem = base
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match base.extension() {
+        Some(ext) => format!("{}.{:05}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}.{:05}", stem, n),
+    };
+    base.with_file_name(name)
+}
+
+/// Parse the segment number from `path` if it is a rotated segment of `base`.
+fn segment_number(base: &Path, path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?;
+    let stem = base.file_stem()?.to_str()?;
+    let rest = name.strip_prefix(stem)?.strip_prefix('.')?;
+    let digits = match base.extension() {
+        Some(ext) => rest.strip_suffix(ext.to_str()?)?.strip_suffix('.')?,
+        None => rest,
+    };
+    if digits.len() != 5 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|&n| n > 1)
+}
+
+/// Why reading a WAL stopped before the end of the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalStopReason {
+    /// The file ends partway through a record (e.g. a torn write).
+    Truncated,
+    /// The payload doesn't match its stored checksum.
+    CrcMismatch,
+    /// The checksum matches but the payload isn't a valid entry.
+    BadEntry,
+}
+
+impl std::fmt::Display for WalStopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalStopReason::Truncated => write!(f, "truncated record"),
+            WalStopReason::CrcMismatch => write!(f, "CRC mismatch"),
+            WalStopReason::BadEntry => write!(f, "bad deserialization"),
+        }
+    }
+}
+
+/// The first unreadable record in a WAL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalStop {
+    /// Segment file holding the record.
+    pub segment: PathBuf,
+    /// Byte offset of the record within that segment.
+    pub offset: u64,
+    pub reason: WalStopReason,
+}
+
+/// Result of `WriteAheadLog::verify`.
+#[derive(Debug, Clone)]
+pub struct WalReport {
+    /// Number of segment files on disk.
+    pub segments: usize,
+    /// Entries that replay would apply.
+    pub valid_entries: usize,
+    /// Combined size of all segment files.
+    pub total_bytes: u64,
+    /// Where replay stops, or `None` if every record is intact.
+    pub stop: Option<WalStop>,
+}
+
+impl WalReport {
+    /// True if replay reads every byte of the log.
+    pub fn is_clean(&self) -> bool {
+        self.stop.is_none()
+    }
+}
+
+/// Fill as much of `buf` as the reader allows, returning the bytes read.
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Pass the valid entries of one segment to `on_entry`. Returns the offset
+/// and reason if reading stopped at a corrupted or incomplete record.
+fn read_segment(
+    path: &Path,
+    on_entry: &mut impl FnMut(WalEntry),
+) -> Result<Option<(u64, WalStopReason)>> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::new(file);
+    let mut offset = 0u64;
+
+    loop {
+        // Read the header: length, then CRC
+        let mut header = [0u8; 8];
+        match read_up_to(&mut reader, &mut header)? {
+            0 => return Ok(None),
+            8 => {}
+            _ => return Ok(Some((offset, WalStopReason::Truncated))),
+        }
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let expected_crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+
+        // Read payload
+        let mut payload = vec![0u8; len];
+        if read_up_to(&mut reader, &mut payload)? < len {
+            return Ok(Some((offset, WalStopReason::Truncated)));
+        }
+
+        // Verify CRC
+        if crc32fast::hash(&payload) != expected_crc {
+            return Ok(Some((offset, WalStopReason::CrcMismatch)));
+        }
+
+        // Deserialize
+        match decode_entry(&payload) {
+            Ok(entry) => on_entry(entry),
+            Err(_) => return Ok(Some((offset, WalStopReason::BadEntry))),
+        }
+        offset += 8 + len as u64;
+    }
 }
 
 #[cfg(test)]
@@ -136,12 +440,14 @@ mod tests {
                 string_id: "v1".to_string(),
                 internal_id: 0,
                 data: vec![1.0, 2.0, 3.0],
+                metadata: HashMap::new(),
             })
             .unwrap();
             wal.append(&WalEntry::Insert {
                 string_id: "v2".to_string(),
                 internal_id: 1,
                 data: vec![4.0, 5.0, 6.0],
+                metadata: HashMap::new(),
             })
             .unwrap();
             wal.append(&WalEntry::Delete {
@@ -171,6 +477,7 @@ mod tests {
                 string_id: "v1".to_string(),
                 internal_id: 0,
                 data: vec![1.0],
+                metadata: HashMap::new(),
             })
             .unwrap();
         }
@@ -186,6 +493,161 @@ mod tests {
         assert_eq!(entries.len(), 1); // Only the valid entry
     }
 
+    #[test]
+    fn test_wal_replays_legacy_insert() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        // Hand-write records in the pre-metadata layout
+        {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&wal_path)
+                .unwrap();
+            for entry in [
+                LegacyWalEntry::Insert {
+                    string_id: "old".to_string(),
+                    internal_id: 0,
+                    data: vec![1.0, 2.0],
+                },
+                LegacyWalEntry::Delete {
+                    string_id: "gone".to_string(),
+                },
+            ] {
+                let payload = serialization::to_bincode(&entry).unwrap();
+                file.write_all(&encode_record(&payload)).unwrap();
+            }
+        }
+
+        // New-format entries can follow in the same log
+        let mut wal = WriteAheadLog::open(&wal_path).unwrap();
+        let mut metadata = HashMap::new();
+        metadata.insert("color".to_string(), "red".to_string());
+        wal.append(&WalEntry::Insert {
+            string_id: "new".to_string(),
+            internal_id: 1,
+            data: vec![3.0, 4.0],
+            metadata,
+        })
+        .unwrap();
+
+        let entries = wal.replay().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(
+            &entries[0],
+            WalEntry::Insert { string_id, data, metadata, .. }
+                if string_id == "old" && data == &vec![1.0, 2.0] && metadata.is_empty()
+        ));
+        assert!(matches!(&entries[1], WalEntry::Delete { string_id } if string_id == "gone"));
+        assert!(matches!(
+            &entries[2],
+            WalEntry::Insert { string_id, metadata, .. }
+                if string_id == "new" && metadata.get("color") == Some(&"red".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_wal_append_batch_torn_tail() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let entries: Vec<WalEntry> = (0..5)
+            .map(|i| WalEntry::Insert {
+                string_id: format!("v{}", i),
+                internal_id: i,
+                data: vec![i as f32; 4],
+                metadata: HashMap::new(),
+            })
+            .collect();
+
+        {
+            let mut wal = WriteAheadLog::open(&wal_path).unwrap();
+            wal.append_batch(&entries).unwrap();
+            assert_eq!(wal.replay().unwrap().len(), 5);
+        }
+
+        // Chop a few bytes off the last record, as if the batch write was torn
+        let len = std::fs::metadata(&wal_path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+        file.set_len(len - 3).unwrap();
+        drop(file);
+
+        let wal = WriteAheadLog::open(&wal_path).unwrap();
+        let replayed = wal.replay().unwrap();
+        assert_eq!(replayed.len(), 4);
+        assert!(matches!(
+            &replayed[3],
+            WalEntry::Insert { string_id, .. } if string_id == "v3"
+        ));
+    }
+
+    #[test]
+    fn test_wal_rotates_at_threshold() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+
+        let entry = WalEntry::Insert {
+            string_id: "v".to_string(),
+            internal_id: 0,
+            data: vec![0.0; 8],
+            metadata: HashMap::new(),
+        };
+        let record_len = encode_record(&serialization::to_bincode(&entry).unwrap()).len() as u64;
+
+        // Room for two records per segment
+        let mut wal =
+            WriteAheadLog::open_with_max_segment_bytes(&wal_path, record_len * 2).unwrap();
+        wal.append(&entry).unwrap();
+        assert_eq!(wal.current_segment(), 1);
+        wal.append(&entry).unwrap();
+        assert_eq!(wal.current_segment(), 2);
+        assert!(dir.path().join("wal.00002.log").exists());
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), record_len * 2);
+    }
+
+    #[test]
+    fn test_wal_replay_across_segments() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+
+        {
+            let mut wal = WriteAheadLog::open_with_max_segment_bytes(&wal_path, 64).unwrap();
+            for i in 0..20 {
+                wal.append(&WalEntry::Insert {
+                    string_id: format!("v{}", i),
+                    internal_id: i,
+                    data: vec![i as f32; 4],
+                    metadata: HashMap::new(),
+                })
+                .unwrap();
+            }
+            assert!(wal.segment_paths().unwrap().len() > 2);
+        }
+
+        // Reopening resumes the last segment and replays everything in order
+        let mut wal = WriteAheadLog::open_with_max_segment_bytes(&wal_path, 64).unwrap();
+        wal.append(&WalEntry::Delete {
+            string_id: "v0".to_string(),
+        })
+        .unwrap();
+
+        let entries = wal.replay().unwrap();
+        assert_eq!(entries.len(), 21);
+        for (i, entry) in entries.iter().take(20).enumerate() {
+            assert!(matches!(
+                entry,
+                WalEntry::Insert { string_id, .. } if *string_id == format!("v{}", i)
+            ));
+        }
+        assert!(matches!(&entries[20], WalEntry::Delete { string_id } if string_id == "v0"));
+
+        // Truncate removes the rotated segments
+        wal.truncate().unwrap();
+        assert_eq!(wal.segment_paths().unwrap(), vec![wal_path.clone()]);
+        assert!(wal.replay().unwrap().is_empty());
+    }
+
     #[test]
     fn test_wal_truncate() {
         let dir = TempDir::new().unwrap();
@@ -200,4 +662,84 @@ mod tests {
         let wal = WriteAheadLog::open(&wal_path).unwrap();
         assert_eq!(wal.replay().unwrap().len(), 0);
     }
+
+    fn insert_entry(i: usize) -> WalEntry {
+        WalEntry::Insert {
+            string_id: format!("v{}", i),
+            internal_id: i,
+            data: vec![i as f32; 4],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_wal_verify_clean() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+        let mut wal = WriteAheadLog::open_with_max_segment_bytes(&wal_path, 64).unwrap();
+        for i in 0..10 {
+            wal.append(&insert_entry(i)).unwrap();
+        }
+
+        let report = wal.verify().unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.valid_entries, 10);
+        assert!(report.segments > 1);
+        let on_disk: u64 = wal
+            .segment_paths()
+            .unwrap()
+            .iter()
+            .map(|p| std::fs::metadata(p).unwrap().len())
+            .sum();
+        assert_eq!(report.total_bytes, on_disk);
+    }
+
+    #[test]
+    fn test_wal_verify_crc_mismatch() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+        let mut wal = WriteAheadLog::open(&wal_path).unwrap();
+        for i in 0..3 {
+            wal.append(&insert_entry(i)).unwrap();
+        }
+        let record_len = std::fs::metadata(&wal_path).unwrap().len() / 3;
+
+        // Flip a payload byte in the second record
+        let mut bytes = std::fs::read(&wal_path).unwrap();
+        bytes[record_len as usize + 10] ^= 0xFF;
+        std::fs::write(&wal_path, &bytes).unwrap();
+
+        let report = wal.verify().unwrap();
+        assert_eq!(report.valid_entries, 1);
+        assert_eq!(
+            report.stop,
+            Some(WalStop {
+                segment: wal_path.clone(),
+                offset: record_len,
+                reason: WalStopReason::CrcMismatch,
+            })
+        );
+        assert_eq!(wal.replay().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_wal_verify_torn_tail() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+        let mut wal = WriteAheadLog::open(&wal_path).unwrap();
+        wal.append_batch(&[insert_entry(0), insert_entry(1)]).unwrap();
+        let len = std::fs::metadata(&wal_path).unwrap().len();
+
+        // A header cut short is as torn as a payload cut short
+        for cut in [len - 3, len / 2 + 5] {
+            let file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+            file.set_len(cut).unwrap();
+            let report = wal.verify().unwrap();
+            assert_eq!(report.valid_entries, 1);
+            assert_eq!(report.total_bytes, cut);
+            let stop = report.stop.unwrap();
+            assert_eq!(stop.offset, len / 2);
+            assert_eq!(stop.reason, WalStopReason::Truncated);
+        }
+    }
 }
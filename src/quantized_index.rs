@@ -0,0 +1,246 @@
+//! Flat index over int8-quantized vectors — ~4x less memory than `FlatIndex`,
+//! at the cost of approximate distances.
+
+use std::collections::HashMap;
+
+use crate::distance::DistanceMetric;
+use crate::error::Result;
+use crate::index::{self, Index};
+use crate::vector::Vector;
+
+/// A vector quantized to one `i8` per component.
+///
+/// Each vector gets its own affine mapping: component `x` is stored as the
+/// nearest of 256 evenly spaced levels between the vector's min and max.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedVector {
+    codes: Vec<i8>,
+    min: f32,
+    scale: f32,
+}
+
+impl QuantizedVector {
+    /// Quantize a full-precision vector.
+    pub fn quantize(vector: &Vector) -> Self {
+        let data = vector.as_slice();
+        let min = data.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = data.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let scale = if max > min { (max - min) / 255.0 } else { 0.0 };
+
+        let codes = data
+            .iter()
+            .map(|&x| {
+                let level = if scale > 0.0 {
+                    ((x - min) / scale).round()
+                } else {
+                    0.0
+                };
+                (level as i32 - 128) as i8
+            })
+            .collect();
+
+        Self {
+            codes,
+            min: if min.is_finite() { min } else { 0.0 },
+            scale,
+        }
+    }
+
+    /// Reconstruct an approximate full-precision vector.
+    pub fn dequantize(&self) -> Vector {
+        Vector::new(
+            self.codes
+                .iter()
+                .map(|&c| self.min + (c as i32 + 128) as f32 * self.scale)
+                .collect(),
+        )
+    }
+
+    /// Number of components.
+    pub fn dimension(&self) -> usize {
+        self.codes.len()
+    }
+}
+
+/// A brute-force index that stores vectors int8-quantized and dequantizes
+/// them on the fly to compute distances.
+///
+/// Full-precision vectors are not kept, so `Index::get_vector` returns `None`.
+/// `Index::reconstruct_vector` (and [`QuantizedFlatIndex::dequantize`]) give
+/// the dequantized approximation back, which quantizes to the same codes, so
+/// snapshots taken from this index reload into the same state.
+#[derive(Debug)]
+pub struct QuantizedFlatIndex {
+    vectors: HashMap<usize, QuantizedVector>,
+    metric: DistanceMetric,
+    /// Vector dimension, fixed by the first insert.
+    dimension: Option<usize>,
+}
+
+impl QuantizedFlatIndex {
+    /// Create a new empty quantized index with the given distance metric.
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self {
+            vectors: HashMap::new(),
+            metric,
+            dimension: None,
+        }
+    }
+
+    /// Get the quantized form of a vector by internal ID.
+    pub fn get_quantized(&self, id: usize) -> Option<&QuantizedVector> {
+        self.vectors.get(&id)
+    }
+
+    /// Approximate reconstruction of a stored vector.
+    pub fn dequantize(&self, id: usize) -> Option<Vector> {
+        self.vectors.get(&id).map(QuantizedVector::dequantize)
+    }
+}
+
+impl Index for QuantizedFlatIndex {
+    fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+        index::check_dimension(self.dimension, &vector)?;
+        self.dimension = Some(vector.dimension());
+        self.vectors.insert(id, QuantizedVector::quantize(&vector));
+        Ok(())
+    }
+
+    fn add_batch(&mut self, items: Vec<(usize, Vector)>) -> Result<()> {
+        self.dimension = index::check_batch_dimension(self.dimension, &items)?;
+        for (id, vector) in items {
+            self.vectors.insert(id, QuantizedVector::quantize(&vector));
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: usize) -> Result<()> {
+        self.vectors.remove(&id);
+        Ok(())
+    }
+
+    fn get_vector(&self, _id: usize) -> Option<&Vector> {
+        None
+    }
+
+    fn reconstruct_vector(&self, id: usize) -> Option<Vector> {
+        self.dequantize(id)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        self.search_filtered(query, k, &|_| true, 1)
+    }
+
+    fn search_filtered(
+        &self,
+        query: &Vector,
+        k: usize,
+        predicate: &dyn Fn(usize) -> bool,
+        _over_fetch: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        let mut results: Vec<(usize, f32)> = self
+            .vectors
+            .iter()
+            .filter(|(&id, _)| predicate(id))
+            .map(|(&id, qv)| {
+                let distance = self.metric.distance(query, &qv.dequantize())?;
+                Ok((id, distance))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        results.sort_by(|a, b| self.metric.compare(a.1, b.1));
+        results.truncate(k);
+        Ok(results)
+    }
+
+    fn prefilters(&self) -> bool {
+        true
+    }
+
+    fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        let table = self.vectors.capacity()
+            * (std::mem::size_of::<usize>() + std::mem::size_of::<QuantizedVector>() + 1);
+        let codes: usize = self.vectors.values().map(|v| v.dimension()).sum();
+        table + codes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_roundtrip_error() {
+        let v = Vector::new(vec![-1.0, -0.25, 0.0, 0.3, 2.0]);
+        let q = QuantizedVector::quantize(&v);
+        let max_err = 3.0 / 255.0 / 2.0 + 1e-6;
+        for (a, b) in v.as_slice().iter().zip(q.dequantize().as_slice()) {
+            assert!((a - b).abs() <= max_err, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_quantize_constant_vector() {
+        let v = Vector::new(vec![0.5; 4]);
+        assert_eq!(QuantizedVector::quantize(&v).dequantize(), v);
+    }
+
+    #[test]
+    fn test_quantized_index_search() {
+        let mut index = QuantizedFlatIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![1.0, 0.0, 0.0])).unwrap();
+        index.add(1, Vector::new(vec![0.0, 1.0, 0.0])).unwrap();
+        index.add(2, Vector::new(vec![1.0, 1.0, 0.0])).unwrap();
+
+        let results = index.search(&Vector::new(vec![1.0, 0.0, 0.0]), 2).unwrap();
+        assert_eq!(results[0].0, 0);
+        assert_eq!(results[1].0, 2);
+        assert!(index.get_vector(0).is_none());
+        assert!(index.dequantize(1).is_some());
+    }
+
+    #[test]
+    fn test_store_reads_back_dequantized_vectors() {
+        use crate::storage::VectorStore;
+
+        let mut store = VectorStore::with_index(QuantizedFlatIndex::new(DistanceMetric::Euclidean));
+        let v = Vector::new(vec![-1.0, 0.5, 2.0]);
+        store.insert("a", v.clone()).unwrap();
+
+        // Dequantizing and quantizing again lands on the same codes
+        let restored = store.reconstruct("a").unwrap();
+        assert_eq!(QuantizedVector::quantize(&restored), QuantizedVector::quantize(&v));
+        assert_eq!(store.iter().next().unwrap().1.as_ref(), &restored);
+        assert_eq!(store.get_many(&["a".to_string()])[0].1.as_ref(), Some(&restored));
+        let mut out = Vec::new();
+        store.export_jsonl(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+        assert!(store.get("a").is_none());
+    }
+
+    #[test]
+    fn test_quantized_index_rejects_mismatched_dimension() {
+        use crate::error::VectorDbError;
+
+        let mut index = QuantizedFlatIndex::new(DistanceMetric::Euclidean);
+        index.add(0, Vector::new(vec![1.0, 0.0, 0.0])).unwrap();
+        assert!(matches!(
+            index.add(1, Vector::new(vec![1.0, 0.0])),
+            Err(VectorDbError::DimensionMismatch { expected: 3, actual: 2 })
+        ));
+        let batch = vec![
+            (1, Vector::new(vec![0.0, 1.0, 0.0])),
+            (2, Vector::new(vec![1.0, 0.0])),
+        ];
+        assert!(index.add_batch(batch).is_err());
+        assert_eq!(index.len(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! Gaussian random projection: a cheap, data-independent alternative to PCA
+//! for cutting vector dimension.
+//!
+//! By the Johnson–Lindenstrauss lemma, projecting `n` points onto
+//! `O(log(n) / ε²)` random Gaussian directions keeps every pairwise Euclidean
+//! distance within a factor of `1 ± ε` with high probability, whatever the
+//! input dimension. In practice a few hundred output dimensions preserve
+//! distances to within 10–20%, which is plenty for candidate generation
+//! followed by an exact rerank. Nothing is learned from the data, so building
+//! one costs only the `out_dim × in_dim` random draws.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Result, VectorDbError};
+use crate::vector::Vector;
+
+/// A fixed random linear map from `in_dim` to `out_dim` dimensions.
+#[derive(Debug, Clone)]
+pub struct RandomProjection {
+    in_dim: usize,
+    out_dim: usize,
+    /// Row-major `out_dim × in_dim` matrix of N(0, 1/out_dim) entries, scaled
+    /// so squared norms are preserved in expectation.
+    matrix: Vec<f32>,
+}
+
+impl RandomProjection {
+    /// Draw a projection matrix from a seeded RNG; the same arguments always
+    /// give the same projection. Fails if either dimension is 0.
+    pub fn new(in_dim: usize, out_dim: usize, seed: u64) -> Result<Self> {
+        if in_dim == 0 || out_dim == 0 {
+            return Err(VectorDbError::InvalidVector {
+                reason: format!(
+                    "random projection dimensions must be at least 1, got {} -> {}",
+                    in_dim, out_dim
+                ),
+            });
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let scale = 1.0 / (out_dim as f32).sqrt();
+        // Box-Muller, as for the LSH hyperplanes
+        let mut gaussian = move || {
+            let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+            let u2: f32 = rng.gen();
+            (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+        };
+        let matrix = (0..in_dim * out_dim).map(|_| gaussian() * scale).collect();
+
+        Ok(Self {
+            in_dim,
+            out_dim,
+            matrix,
+        })
+    }
+
+    /// Map `v` to `out_dim` dimensions.
+    pub fn project(&self, v: &Vector) -> Result<Vector> {
+        if v.dimension() != self.in_dim {
+            return Err(VectorDbError::DimensionMismatch {
+                expected: self.in_dim,
+                actual: v.dimension(),
+            });
+        }
+        let projected = self
+            .matrix
+            .chunks(self.in_dim)
+            .map(|row| row.iter().zip(v.as_slice()).map(|(a, x)| a * x).sum())
+            .collect();
+        Ok(Vector::new(projected))
+    }
+
+    /// Input dimension.
+    pub fn in_dim(&self) -> usize {
+        self.in_dim
+    }
+
+    /// Output dimension.
+    pub fn out_dim(&self) -> usize {
+        self.out_dim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::euclidean_distance;
+
+    #[test]
+    fn test_projection_preserves_pairwise_distances() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let vectors: Vec<Vector> = (0..20)
+            .map(|_| Vector::new((0..1000).map(|_| rng.gen_range(-1.0..1.0)).collect()))
+            .collect();
+
+        let rp = RandomProjection::new(1000, 256, 5).unwrap();
+        let projected: Vec<Vector> = vectors.iter().map(|v| rp.project(v).unwrap()).collect();
+        assert!(projected.iter().all(|p| p.dimension() == 256));
+
+        for i in 0..vectors.len() {
+            for j in i + 1..vectors.len() {
+                let before = euclidean_distance(&vectors[i], &vectors[j]);
+                let after = euclidean_distance(&projected[i], &projected[j]);
+                let ratio = after / before;
+                assert!((0.75..1.25).contains(&ratio), "pair ({}, {}): {}", i, j, ratio);
+            }
+        }
+    }
+
+    #[test]
+    fn test_projection_is_seeded() {
+        let v = Vector::new(vec![1.0, -2.0, 3.0, 0.5]);
+        let a = RandomProjection::new(4, 2, 9).unwrap();
+        let b = RandomProjection::new(4, 2, 9).unwrap();
+        let c = RandomProjection::new(4, 2, 10).unwrap();
+        assert_eq!(a.project(&v).unwrap(), b.project(&v).unwrap());
+        assert_ne!(a.project(&v).unwrap(), c.project(&v).unwrap());
+
+        assert!(a.project(&Vector::new(vec![1.0])).is_err());
+        assert!(RandomProjection::new(0, 2, 0).is_err());
+        assert!(RandomProjection::new(4, 0, 0).is_err());
+    }
+}
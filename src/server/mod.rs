@@ -1,33 +1,297 @@
 //! HTTP API server for the vector database.
 
 pub mod routes;
+pub mod store;
 
+use crate::database::{Database, FromMetric};
 use crate::distance::DistanceMetric;
+use crate::flat_index::FlatIndex;
 use crate::hnsw::{HnswIndex, HnswParams};
 use crate::index::Index;
 use crate::metrics::MetricsCollector;
+use crate::persistence::engine::{EngineConfig, StorageEngine};
 use crate::storage::VectorStore;
+use std::path::Path;
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+pub use store::{ServerStore, StoreWrite};
+
+/// Server-wide configuration options.
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// Enable the `/admin/*` endpoints (e.g. self-benchmark).
+    pub admin_enabled: bool,
+    /// When set, every endpoint except `/health` requires
+    /// `Authorization: Bearer <api_key>`.
+    pub api_key: Option<String>,
+    /// How often a background task deletes expired vectors. `None` leaves
+    /// them in place; searches skip them either way.
+    pub expiry_interval: Option<Duration>,
+    /// Reject vectors (inserts and queries, in every collection) with more
+    /// components than this, with a 400.
+    pub max_dimension: Option<usize>,
+    /// Largest JSON request body accepted, in bytes; larger ones get a 413.
+    /// `None` uses `DEFAULT_MAX_BODY_BYTES`.
+    pub max_body_bytes: Option<usize>,
+    /// Search requests still running after this long are answered with a
+    /// 408. `None` lets them run as long as they take.
+    pub search_timeout: Option<Duration>,
+    /// Most searches running on the blocking pool at once; more wait for a
+    /// slot (counted against `search_timeout`). A search keeps its slot until
+    /// it finishes, even after its client got a 408. `None` means no cap.
+    pub max_concurrent_searches: Option<usize>,
+}
+
+/// Request body limit when `ServerConfig::max_body_bytes` is unset (axum's own default).
+pub const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Body size limit layer for `config`. NDJSON streaming inserts read the raw
+/// body and are bounded per line instead.
+pub fn body_limit(config: &ServerConfig) -> DefaultBodyLimit {
+    DefaultBodyLimit::max(config.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES))
+}
 
 /// Shared application state for the HTTP server.
 pub struct AppState<I: Index> {
-    pub store: RwLock<VectorStore<I>>,
+    /// Searches and other reads share the read lock and run in parallel;
+    /// writes take the write lock, so they wait for in-flight reads and
+    /// block new ones until they finish.
+    pub store: RwLock<ServerStore<I>>,
+    /// Named collections served under `/collections/:name/...`.
+    pub collections: RwLock<Database<I>>,
     pub metrics: RwLock<MetricsCollector>,
+    pub config: ServerConfig,
+    /// Requests currently being handled, for reporting during shutdown.
+    pub in_flight: AtomicUsize,
+    /// Slots for `ServerConfig::max_concurrent_searches`.
+    pub search_slots: Option<Arc<Semaphore>>,
+}
+
+impl<I: FromMetric + 'static> AppState<I> {
+    /// Create application state wrapping the given store, with no collections.
+    pub fn new(store: VectorStore<I>, config: ServerConfig) -> Self {
+        Self::with_store(ServerStore::Memory(store), config)
+    }
+
+    /// Create application state whose default store writes through `engine`.
+    /// Collections stay in memory.
+    pub fn persistent(engine: StorageEngine<I>, config: ServerConfig) -> Self {
+        Self::with_store(ServerStore::Persistent(engine), config)
+    }
+
+    fn with_store(mut store: ServerStore<I>, config: ServerConfig) -> Self {
+        if config.max_dimension.is_some() {
+            store.set_max_dimension(config.max_dimension);
+        }
+        let search_slots = config
+            .max_concurrent_searches
+            .map(|n| Arc::new(Semaphore::new(n)));
+        Self {
+            store: RwLock::new(store),
+            collections: RwLock::new(Database::new()),
+            metrics: RwLock::new(MetricsCollector::new()),
+            config,
+            in_flight: AtomicUsize::new(0),
+            search_slots,
+        }
+    }
+}
+
+/// Middleware rejecting requests without the configured bearer token.
+/// Passes everything through when no API key is configured.
+pub async fn require_api_key<I: Index>(
+    State(state): State<Arc<AppState<I>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = state.config.api_key.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let authorized = provided.is_some_and(|p| constant_time_eq(p.as_bytes(), expected.as_bytes()));
+    if authorized {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(routes::ErrorResponse {
+                error: "Missing or invalid API key".to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Compare two byte strings in time that depends only on their lengths, so a
+/// rejected API key doesn't reveal how many leading bytes were right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Decrements the in-flight counter when a request finishes or is dropped.
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Middleware counting requests in flight.
+pub async fn track_in_flight<I: Index>(
+    State(state): State<Arc<AppState<I>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    state.in_flight.fetch_add(1, Ordering::SeqCst);
+    let _guard = InFlightGuard(&state.in_flight);
+    next.run(request).await
+}
+
+/// Middleware logging one `tracing` event per request with its method, path,
+/// response status and latency. Nothing is printed unless a subscriber is
+/// installed (the CLI installs one filtered by `RUST_LOG`).
+pub async fn log_request(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    tracing::info!(
+        %method,
+        %path,
+        status = response.status().as_u16(),
+        latency_ms = start.elapsed().as_secs_f64() * 1000.0,
+        "request"
+    );
+    response
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM on Unix.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Serve `state` on `listener` until `shutdown` resolves, then stop accepting
+/// connections, wait for in-flight requests to finish, and checkpoint a
+/// persistent store before returning.
+pub async fn serve<I, F>(
+    listener: tokio::net::TcpListener,
+    state: Arc<AppState<I>>,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    I: Index + Send + Sync + std::fmt::Debug + 'static,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let app = routes::create_router(state.clone());
+    let expiry = state
+        .config
+        .expiry_interval
+        .map(|interval| tokio::spawn(expire_periodically(state.clone(), interval)));
+    let draining = state.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            println!(
+                "Shutting down, draining {} in-flight request(s)",
+                draining.in_flight.load(Ordering::SeqCst)
+            );
+        })
+        .await?;
+    if let Some(task) = expiry {
+        task.abort();
+    }
+
+    let mut store = state
+        .store
+        .write()
+        .map_err(|_| anyhow::anyhow!("store lock poisoned"))?;
+    if store.is_persistent() {
+        store.checkpoint()?;
+        println!("Final checkpoint written");
+    }
+    println!("Server stopped");
+    Ok(())
+}
+
+/// Delete expired vectors from the default store and all collections every
+/// `interval`, until the task is aborted.
+async fn expire_periodically<I: Index + 'static>(state: Arc<AppState<I>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let removed = match state.store.write() {
+            Ok(mut store) => store.expire_now().unwrap_or_else(|e| {
+                eprintln!("Expiry failed: {}", e);
+                0
+            }),
+            Err(_) => 0,
+        };
+        let removed = removed
+            + state
+                .collections
+                .write()
+                .map_or(0, |mut collections| collections.expire_now());
+        if removed > 0 {
+            println!("Expired {} vector(s)", removed);
+        }
+    }
 }
 
 /// Start the HTTP server with a flat index.
-pub async fn start_flat(addr: &str, metric: DistanceMetric) -> anyhow::Result<()> {
+pub async fn start_flat(
+    addr: &str,
+    metric: DistanceMetric,
+    config: ServerConfig,
+) -> anyhow::Result<()> {
     let store = VectorStore::with_flat_index(metric);
-    let state = Arc::new(AppState {
-        store: RwLock::new(store),
-        metrics: RwLock::new(MetricsCollector::new()),
-    });
+    let state = Arc::new(AppState::new(store, config));
 
-    let app = routes::create_router(state);
     let listener = tokio::net::TcpListener::bind(addr).await?;
     println!("Server listening on {}", addr);
-    axum::serve(listener, app).await?;
-    Ok(())
+    serve(listener, state, shutdown_signal()).await
 }
 
 /// Start the HTTP server with an HNSW index.
@@ -35,17 +299,102 @@ pub async fn start_hnsw(
     addr: &str,
     metric: DistanceMetric,
     params: HnswParams,
+    config: ServerConfig,
 ) -> anyhow::Result<()> {
-    let index = HnswIndex::with_params(metric, params);
+    let index = HnswIndex::with_params(metric, params)?;
     let store = VectorStore::with_index(index);
-    let state = Arc::new(AppState {
-        store: RwLock::new(store),
-        metrics: RwLock::new(MetricsCollector::new()),
-    });
+    let state = Arc::new(AppState::new(store, config));
 
-    let app = routes::create_router(state);
     let listener = tokio::net::TcpListener::bind(addr).await?;
     println!("Server listening on {}", addr);
-    axum::serve(listener, app).await?;
-    Ok(())
+    serve(listener, state, shutdown_signal()).await
+}
+
+/// Start the HTTP server with a flat index persisted under `data_dir`.
+/// Inserts, deletes and metadata updates are WAL-logged before they apply.
+pub async fn start_persistent(
+    addr: &str,
+    data_dir: impl AsRef<Path>,
+    engine_config: EngineConfig,
+    config: ServerConfig,
+) -> anyhow::Result<()> {
+    start_persistent_with_index(addr, data_dir, engine_config, FlatIndex::new, config).await
+}
+
+/// Start the HTTP server persisted under `data_dir`, building the search
+/// index with `make_index` from the stored metric.
+pub async fn start_persistent_with_index<I>(
+    addr: &str,
+    data_dir: impl AsRef<Path>,
+    engine_config: EngineConfig,
+    make_index: impl FnOnce(DistanceMetric) -> I,
+    config: ServerConfig,
+) -> anyhow::Result<()>
+where
+    I: FromMetric + Send + Sync + std::fmt::Debug + 'static,
+{
+    let engine = StorageEngine::open_with_index(data_dir, engine_config, make_index)?;
+    println!("Loaded {} vectors", engine.len());
+    let state = Arc::new(AppState::persistent(engine, config));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Server listening on {}", addr);
+    serve(listener, state, shutdown_signal()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_returns_ok() {
+        let store = VectorStore::new(DistanceMetric::Euclidean);
+        let state = Arc::new(AppState::new(store, ServerConfig::default()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, state.clone(), async {
+            let _ = rx.await;
+        }));
+
+        // A request completes normally while the server is up
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        tokio::io::AsyncWriteExt::write_all(
+            &mut stream,
+            b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+        let mut response = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut response)
+            .await
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        tx.send(()).unwrap();
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .expect("server did not shut down")
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(state.in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_checkpoints_persistent_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut engine = StorageEngine::open(dir.path(), EngineConfig::default()).unwrap();
+        engine
+            .insert("v1", crate::vector::Vector::new(vec![1.0, 2.0]))
+            .unwrap();
+        assert!(!dir.path().join("snapshot.bin").exists());
+
+        let state = Arc::new(AppState::persistent(engine, ServerConfig::default()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        serve(listener, state, async {}).await.unwrap();
+
+        assert!(dir.path().join("snapshot.bin").exists());
+        assert_eq!(std::fs::metadata(dir.path().join("wal.log")).unwrap().len(), 0);
+    }
 }
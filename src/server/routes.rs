@@ -1,20 +1,39 @@
 //! HTTP route handlers for the vector database API.
 
-use crate::index::Index;
-use crate::server::AppState;
-use crate::storage::{BatchInsertItem, Metadata, MetadataFilter};
+use crate::bench::{self, BenchConfig, BenchReport};
+use crate::distance::DistanceMetric;
+use crate::error::VectorDbError;
+#[cfg(feature = "eval-endpoint")]
+use crate::eval;
+use crate::hnsw::{HnswIndex, HnswStats};
+#[cfg(feature = "graph-debug")]
+use crate::hnsw::NodeInfo;
+use crate::index::{Index, DEFAULT_OVER_FETCH};
+use crate::server::{
+    body_limit, log_request, require_api_key, track_in_flight, AppState, StoreWrite,
+};
+use crate::storage::{
+    unix_now, BatchInsertItem, Metadata, MetadataFilter, ScoreKind, SearchPage, SearchResult,
+    Timestamps, VectorStore, EXPIRES_AT_KEY,
+};
 use crate::vector::Vector;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Json},
+    routing::{delete, get, post, put},
     Router,
 };
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Instant;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::timeout::TimeoutLayer;
 
 // --- Request/Response types ---
 
@@ -24,6 +43,24 @@ pub struct InsertRequest {
     pub vector: Vec<f32>,
     #[serde(default)]
     pub metadata: Option<HashMap<String, String>>,
+    /// Seconds until the vector expires and drops out of search results.
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct GetManyRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMetadataRequest {
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct RenameRequest {
+    pub new_id: String,
 }
 
 #[derive(Deserialize)]
@@ -32,11 +69,32 @@ pub struct SearchRequest {
     pub k: Option<usize>,
     #[serde(default)]
     pub filter: Option<MetadataFilter>,
+    /// HNSW search-beam width for this query; ignored by flat indexes and
+    /// when `filter` is set.
+    #[serde(default)]
+    pub ef: Option<usize>,
+    /// Report `"distance"` (default) or `"similarity"` for each result.
+    #[serde(default)]
+    pub score_kind: ScoreKind,
+    /// Candidates fetched per requested result when `filter` is set on an
+    /// index that post-filters (e.g. HNSW). Defaults to 3.
+    #[serde(default)]
+    pub over_fetch: Option<usize>,
+    /// Skip this many of the best matches, for paging (default 0).
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Page size; overrides `k` when set.
+    #[serde(default)]
+    pub limit: Option<usize>,
 }
 
 #[derive(Deserialize)]
 pub struct BatchInsertRequest {
     pub vectors: Vec<BatchInsertItemRequest>,
+    /// Insert every valid item and report the failures, instead of
+    /// rejecting the request at the first bad item.
+    #[serde(default)]
+    pub continue_on_error: bool,
 }
 
 #[derive(Deserialize)]
@@ -60,10 +118,54 @@ pub struct BatchSearchQuery {
     pub k: Option<usize>,
 }
 
+#[derive(Deserialize)]
+pub struct CreateCollectionRequest {
+    pub name: String,
+    #[serde(default = "default_collection_metric")]
+    pub metric: DistanceMetric,
+}
+
+fn default_collection_metric() -> DistanceMetric {
+    DistanceMetric::Euclidean
+}
+
+/// One search hit; exactly one of `distance` and `similarity` is present,
+/// depending on the request's `score_kind`.
 #[derive(Serialize)]
 pub struct SearchResultResponse {
     pub id: String,
-    pub distance: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+}
+
+impl SearchResultResponse {
+    /// Wrap a result whose `distance` holds a `kind` score.
+    fn new(result: SearchResult, kind: ScoreKind) -> Self {
+        let (distance, similarity) = match kind {
+            ScoreKind::Distance => (Some(result.distance), None),
+            ScoreKind::Similarity => (None, Some(result.distance)),
+        };
+        Self {
+            id: result.id,
+            distance,
+            similarity,
+        }
+    }
+}
+
+/// Response of the search endpoints: the hits alone, or a page of them when
+/// the request set `offset` or `limit`.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum SearchResponse {
+    Hits(Vec<SearchResultResponse>),
+    Page {
+        results: Vec<SearchResultResponse>,
+        /// Offset of the following page, as in `SearchPage::next_offset`.
+        next_offset: Option<usize>,
+    },
 }
 
 #[derive(Serialize)]
@@ -73,12 +175,57 @@ pub struct VectorResponse {
     pub vector: Vec<f32>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub metadata: HashMap<String, String>,
+    /// `created_at` and `updated_at` in unix milliseconds, when known.
+    #[serde(flatten)]
+    pub timestamps: Option<Timestamps>,
+}
+
+/// Vectors found by `POST /vectors/get`, in request order, plus the IDs that
+/// weren't.
+#[derive(Serialize)]
+pub struct GetManyResponse {
+    pub vectors: Vec<VectorResponse>,
+    pub missing: Vec<String>,
+}
+
+/// Paging for list endpoints: skip `offset` entries, return at most `limit`.
+#[derive(Deserialize)]
+pub struct PageParams {
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// One page of `GET /metadata`, keyed (and ordered) by vector ID.
+#[derive(Serialize)]
+pub struct MetadataPageResponse {
+    pub metadata: BTreeMap<String, HashMap<String, String>>,
+    /// Number of vectors across all pages.
+    pub total: usize,
+}
+
+/// Response of `GET /metadata/fields/:field/distinct`.
+#[derive(Serialize)]
+pub struct DistinctValuesResponse {
+    /// Distinct values, sorted.
+    pub values: Vec<String>,
+    /// Number of vectors with each value.
+    pub counts: HashMap<String, usize>,
 }
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub vector_count: usize,
+    /// Metric of the default store, so clients know how to read scores.
+    /// Omitted only if the store lock is poisoned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metric: Option<DistanceMetric>,
+    /// Dimension of the default store; null until the first insert.
+    pub dimension: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -86,10 +233,47 @@ pub struct MetricsResponse {
     pub total_queries: u64,
     pub total_inserts: u64,
     pub total_deletes: u64,
+    pub total_updates: u64,
+    /// Queries per second over the last minute.
+    pub qps: f64,
     pub avg_query_latency_us: f64,
     pub p50_query_latency_us: f64,
     pub p95_query_latency_us: f64,
     pub p99_query_latency_us: f64,
+    pub index_memory_bytes: usize,
+    /// Graph summary, present only when the index is HNSW.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw: Option<HnswStats>,
+}
+
+#[derive(Serialize)]
+pub struct StreamInsertError {
+    /// 1-based line number in the request body.
+    pub line: usize,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+pub struct BatchInsertError {
+    pub id: String,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+pub struct BatchInsertResponse {
+    pub inserted: usize,
+    /// Failed items in request order; only present with `continue_on_error`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<BatchInsertError>>,
+}
+
+#[derive(Serialize)]
+pub struct StreamInsertResponse {
+    pub inserted: usize,
+    /// Per-line failures, capped at `STREAM_MAX_REPORTED_ERRORS`.
+    pub errors: Vec<StreamInsertError>,
+    /// Total failed lines, including any beyond the reported cap.
+    pub error_count: usize,
 }
 
 #[derive(Serialize)]
@@ -97,626 +281,2749 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
+#[cfg(feature = "eval-endpoint")]
+#[derive(Deserialize)]
+pub struct EvalRecallRequest {
+    /// Number of stored vectors to sample as queries.
+    #[serde(default = "default_eval_queries")]
+    pub queries: usize,
+    pub k: Option<usize>,
+}
+
+#[cfg(feature = "eval-endpoint")]
+fn default_eval_queries() -> usize {
+    100
+}
+
+#[cfg(feature = "eval-endpoint")]
+#[derive(Serialize)]
+pub struct EvalRecallResponse {
+    /// Mean recall@k of the served index against an exact flat scan.
+    pub recall: f64,
+    pub k: usize,
+    pub queries: usize,
+    pub vectors: usize,
+}
+
+#[cfg(feature = "graph-debug")]
+#[derive(Serialize)]
+pub struct GraphNodeResponse {
+    pub id: String,
+    pub internal_id: usize,
+    #[serde(flatten)]
+    pub node: NodeInfo,
+}
+
+/// Upper bounds on self-benchmark parameters so the endpoint can't be used to exhaust the host.
+const SELFBENCH_MAX_N: usize = 100_000;
+const SELFBENCH_MAX_DIM: usize = 4096;
+const SELFBENCH_MAX_QUERIES: usize = 10_000;
+
+/// Most stored vectors `/eval/recall` will use as queries.
+#[cfg(feature = "eval-endpoint")]
+const EVAL_MAX_QUERIES: usize = 10_000;
+
+/// A single NDJSON line longer than this aborts a streaming insert.
+const STREAM_MAX_LINE_BYTES: usize = 1 << 20;
+const STREAM_MAX_REPORTED_ERRORS: usize = 100;
+/// Responses smaller than this go out uncompressed; a few hundred bytes of
+/// JSON aren't worth the CPU or the gzip header.
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
 // --- Router ---
 
 pub fn create_router<I: Index + Send + Sync + std::fmt::Debug + 'static>(
     state: Arc<AppState<I>>,
 ) -> Router {
-    Router::new()
+    let router = Router::new()
         .route("/vectors", post(insert_vector::<I>).get(list_vectors::<I>))
         .route(
             "/vectors/batch",
             post(batch_insert::<I>),
         )
+        .route("/vectors/stream", post(stream_insert::<I>))
+        .route("/vectors/get", post(get_many_vectors::<I>))
         .route(
             "/vectors/:id",
-            get(get_vector::<I>).delete(delete_vector::<I>),
+            get(get_vector::<I>)
+                .patch(update_metadata::<I>)
+                .delete(delete_vector::<I>),
+        )
+        .route("/vectors/:id/rename", put(rename_vector::<I>))
+        .route("/metadata", get(list_metadata::<I>))
+        .route(
+            "/metadata/fields/:field/distinct",
+            get(distinct_values::<I>),
         )
-        .route("/search", post(search_vectors::<I>))
-        .route("/search/batch", post(batch_search::<I>))
-        .route("/health", get(health::<I>))
         .route("/metrics", get(get_metrics::<I>))
+        .route("/metrics/prometheus", get(get_metrics_prometheus::<I>))
+        .route("/metrics/reset", post(reset_metrics::<I>))
+        .route("/admin/selfbench", post(self_bench::<I>))
+        .route(
+            "/collections",
+            get(list_collections::<I>).post(create_collection::<I>),
+        )
+        .route("/collections/:name", delete(drop_collection::<I>))
+        .route(
+            "/collections/:name/vectors",
+            post(collection_insert::<I>).get(collection_list::<I>),
+        )
+        .route(
+            "/collections/:name/vectors/batch",
+            post(collection_batch_insert::<I>),
+        )
+        .route(
+            "/collections/:name/vectors/get",
+            post(collection_get_many::<I>),
+        )
+        .route(
+            "/collections/:name/vectors/:id",
+            get(collection_get::<I>)
+                .patch(collection_update_metadata::<I>)
+                .delete(collection_delete::<I>),
+        )
+        .route(
+            "/collections/:name/vectors/:id/rename",
+            put(collection_rename::<I>),
+        )
+        .route("/collections/:name/metadata", get(collection_metadata::<I>))
+        .route(
+            "/collections/:name/metadata/fields/:field/distinct",
+            get(collection_distinct_values::<I>),
+        )
+        .merge(search_routes(&state));
+    #[cfg(feature = "eval-endpoint")]
+    let router = router.route("/eval/recall", post(eval_recall::<I>));
+    #[cfg(feature = "graph-debug")]
+    let router = router.route("/vectors/:id/graph", get(graph_node::<I>));
+
+    router
+        // Only routes added above this layer require the API key
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_api_key::<I>,
+        ))
+        .route("/health", get(health::<I>))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            track_in_flight::<I>,
+        ))
+        .layer(body_limit(&state.config))
+        .layer(middleware::from_fn(log_request))
+        // gzip or deflate, per the request's Accept-Encoding
+        .layer(CompressionLayer::new().compress_when(
+            DefaultPredicate::new().and(SizeAbove::new(COMPRESSION_MIN_BYTES)),
+        ))
         .with_state(state)
 }
 
-fn hashmap_to_metadata(map: Option<HashMap<String, String>>) -> Metadata {
+/// The search endpoints, behind `ServerConfig::search_timeout` when one is
+/// set. The handlers run on the blocking pool via `run_search`, so an expired
+/// search stops holding up its client but runs on until it next checks its
+/// `SearchDeadline`.
+fn search_routes<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    state: &AppState<I>,
+) -> Router<Arc<AppState<I>>> {
+    let router = Router::new()
+        .route("/search", post(search_vectors::<I>))
+        .route("/search/batch", post(batch_search::<I>))
+        .route("/collections/:name/search", post(collection_search::<I>))
+        .route(
+            "/collections/:name/search/batch",
+            post(collection_batch_search::<I>),
+        );
+    match state.config.search_timeout {
+        Some(timeout) => router.route_layer(TimeoutLayer::new(timeout)),
+        None => router,
+    }
+}
+
+/// Metadata from a request body. `_expires_at` is set only through
+/// `ttl_seconds`, so a client can't write or PATCH it directly.
+fn request_metadata(map: Option<HashMap<String, String>>) -> Result<Metadata, ApiError> {
     let mut meta = Metadata::new();
     if let Some(fields) = map {
         for (k, v) in fields {
+            if k == EXPIRES_AT_KEY {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    format!("metadata key {} is reserved; use ttl_seconds", EXPIRES_AT_KEY),
+                ));
+            }
             meta.insert(k, v);
         }
     }
-    meta
+    Ok(meta)
 }
 
 // --- Handlers ---
 
-async fn insert_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Json(req): Json<InsertRequest>,
-) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<ErrorResponse>)> {
-    let vector = Vector::new(req.vector);
-    let metadata = hashmap_to_metadata(req.metadata);
-
-    let mut store = state.store.write().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
+type ApiError = (StatusCode, Json<ErrorResponse>);
 
-    store
-        .insert_with_metadata(req.id.clone(), vector, metadata)
-        .map_err(|e| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            )
-        })?;
+/// Run lock-holding, CPU-bound work on the blocking pool instead of an async
+/// worker.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?
+}
 
-    if let Ok(mut metrics) = state.metrics.write() {
-        metrics.record_insert();
+/// When a search's `ServerConfig::search_timeout` runs out, if one is set.
+#[derive(Clone, Copy)]
+struct SearchDeadline(Option<Instant>);
+
+impl SearchDeadline {
+    /// A 408 once the deadline has passed. The client has already had its 408
+    /// from the timeout layer by then; this just stops the work.
+    fn check(self) -> Result<(), ApiError> {
+        match self.0 {
+            Some(deadline) if Instant::now() >= deadline => Err(api_error(
+                StatusCode::REQUEST_TIMEOUT,
+                "search timed out",
+            )),
+            _ => Ok(()),
+        }
     }
+}
 
-    Ok((
-        StatusCode::CREATED,
-        Json(serde_json::json!({"id": req.id, "status": "inserted"})),
-    ))
+/// `run_blocking` for searches: waits for a `max_concurrent_searches` slot,
+/// held until `work` returns even if the request times out first, and hands
+/// `work` a deadline to check once it has the lock it waited on.
+async fn run_search<I: Index + Send + Sync + 'static, T: Send + 'static>(
+    state: Arc<AppState<I>>,
+    work: impl FnOnce(&AppState<I>, SearchDeadline) -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    let deadline = SearchDeadline(state.config.search_timeout.map(|t| Instant::now() + t));
+    let permit = match &state.search_slots {
+        Some(slots) => Some(
+            slots
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?,
+        ),
+        None => None,
+    };
+    run_blocking(move || {
+        let _permit = permit;
+        deadline.check()?;
+        work(&state, deadline)
+    })
+    .await
 }
 
-async fn get_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Path(id): Path<String>,
-) -> Result<Json<VectorResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let store = state.store.read().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
+fn api_error(status: StatusCode, error: impl ToString) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+}
 
-    let vector = store.get(&id).ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Vector not found: {}", id),
-            }),
-        )
+fn lock_poisoned<T>(_: T) -> ApiError {
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Lock poisoned")
+}
+
+// Store-level operations shared by the default store and named collections.
+
+/// Validate a vector from a request body (see `Vector::try_new`).
+fn request_vector(data: Vec<f32>) -> Result<Vector, ApiError> {
+    Vector::try_new(data).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))
+}
+
+fn insert_into(store: &mut impl StoreWrite, req: InsertRequest) -> Result<(), ApiError> {
+    let vector = request_vector(req.vector)?;
+    let mut metadata = request_metadata(req.metadata)?;
+    if let Some(ttl) = req.ttl_seconds {
+        metadata.set_expires_at(unix_now().saturating_add(ttl));
+    }
+    store
+        .insert_with_metadata(req.id, vector, metadata)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))
+}
+
+/// 404 if the vector is missing, 409 if `new_id` is taken.
+fn rename_in(
+    store: &mut impl StoreWrite,
+    id: &str,
+    req: RenameRequest,
+) -> Result<serde_json::Value, ApiError> {
+    store.rename(id, &req.new_id).map_err(|e| match e {
+        VectorDbError::VectorExists { .. } => api_error(StatusCode::CONFLICT, e),
+        _ => api_error(StatusCode::NOT_FOUND, e),
     })?;
+    Ok(serde_json::json!({"id": req.new_id, "previous_id": id, "status": "renamed"}))
+}
+
+fn vector_response<I: Index>(
+    store: &VectorStore<I>,
+    id: String,
+) -> Result<VectorResponse, ApiError> {
+    let vector = store
+        .reconstruct(&id)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, format!("Vector not found: {}", id)))?;
 
     let metadata = store
         .get_metadata(&id)
         .map(|m| m.fields().clone())
         .unwrap_or_default();
 
-    Ok(Json(VectorResponse {
+    Ok(VectorResponse {
         dimension: vector.dimension(),
         vector: vector.as_slice().to_vec(),
+        timestamps: store.get_timestamps(&id),
         id,
         metadata,
-    }))
+    })
 }
 
-async fn delete_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
-    let mut store = state.store.write().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
+fn metadata_page_in<I: Index>(store: &VectorStore<I>, page: PageParams) -> MetadataPageResponse {
+    let all = store.all_metadata();
+    let total = all.len();
+    let metadata = all
+        .into_iter()
+        .skip(page.offset)
+        .take(page.limit.unwrap_or(DEFAULT_PAGE_LIMIT))
+        .map(|(id, metadata)| (id, metadata.fields().clone()))
+        .collect();
+    MetadataPageResponse { metadata, total }
+}
 
-    store.delete(&id).map_err(|e| {
-        (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
+fn distinct_values_in<I: Index>(store: &VectorStore<I>, field: &str) -> DistinctValuesResponse {
+    let counts = store.value_counts(field);
+    let mut values: Vec<String> = counts.keys().cloned().collect();
+    values.sort();
+    DistinctValuesResponse { values, counts }
+}
 
-    if let Ok(mut metrics) = state.metrics.write() {
-        metrics.record_delete();
+fn get_many_in<I: Index>(store: &VectorStore<I>, req: GetManyRequest) -> GetManyResponse {
+    let mut response = GetManyResponse {
+        vectors: Vec::new(),
+        missing: Vec::new(),
+    };
+    for (id, vector) in store.get_many(&req.ids) {
+        match vector {
+            Some(vector) => {
+                let metadata = store
+                    .get_metadata(&id)
+                    .map(|m| m.fields().clone())
+                    .unwrap_or_default();
+                response.vectors.push(VectorResponse {
+                    dimension: vector.dimension(),
+                    vector: vector.as_slice().to_vec(),
+                    timestamps: store.get_timestamps(&id),
+                    id,
+                    metadata,
+                });
+            }
+            None => response.missing.push(id),
+        }
     }
-
-    Ok(Json(serde_json::json!({"id": id, "status": "deleted"})))
+    response
 }
 
-async fn search_vectors<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Json(req): Json<SearchRequest>,
-) -> Result<Json<Vec<SearchResultResponse>>, (StatusCode, Json<ErrorResponse>)> {
-    let query = Vector::new(req.vector);
-    let k = req.k.unwrap_or(10);
-
-    let start = Instant::now();
-
-    let store = state.store.read().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
-
-    let results = if let Some(filter) = &req.filter {
-        store.search_with_filter(&query, k, filter)
+fn search_in<I: Index>(
+    store: &VectorStore<I>,
+    req: SearchRequest,
+) -> Result<SearchResponse, ApiError> {
+    let query = request_vector(req.vector)?;
+    let paged = req.offset.is_some() || req.limit.is_some();
+    let offset = req.offset.unwrap_or(0);
+    let limit = req.limit.or(req.k).unwrap_or(10);
+
+    // A page is the tail of a search for everything up to its end
+    let k = offset.saturating_add(limit);
+    let page = if let Some(filter) = &req.filter {
+        let over_fetch = req.over_fetch.unwrap_or(DEFAULT_OVER_FETCH);
+        store
+            .search_with_filter_over_fetch(&query, k, filter, over_fetch)
+            .map(|ranked| SearchPage::from_ranking(ranked, offset, limit))
+    } else if let Some(ef) = req.ef {
+        store
+            .search_with_ef(&query, k, ef)
+            .map(|ranked| SearchPage::from_ranking(ranked, offset, limit))
     } else {
-        store.search(&query, k)
-    }
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
-
-    let elapsed = start.elapsed();
-
-    if let Ok(mut metrics) = state.metrics.write() {
-        metrics.record_query(elapsed);
+        store.search_page(&query, offset, limit)
     }
+    .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
 
-    let response: Vec<SearchResultResponse> = results
+    let results = store
+        .rescore(page.results, req.score_kind)
         .into_iter()
-        .map(|r| SearchResultResponse {
-            id: r.id,
-            distance: r.distance,
-        })
+        .map(|r| SearchResultResponse::new(r, req.score_kind))
         .collect();
-
-    Ok(Json(response))
+    Ok(if paged {
+        SearchResponse::Page {
+            results,
+            next_offset: page.next_offset,
+        }
+    } else {
+        SearchResponse::Hits(results)
+    })
 }
 
-async fn batch_insert<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Json(req): Json<BatchInsertRequest>,
-) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<ErrorResponse>)> {
-    let items: Vec<BatchInsertItem> = req
+fn batch_insert_into(
+    store: &mut impl StoreWrite,
+    req: BatchInsertRequest,
+) -> Result<BatchInsertResponse, ApiError> {
+    if req.continue_on_error {
+        return Ok(batch_insert_lenient_into(store, req));
+    }
+
+    let items = req
         .vectors
         .into_iter()
-        .map(|item| BatchInsertItem {
-            id: item.id,
-            vector: Vector::new(item.vector),
-            metadata: hashmap_to_metadata(item.metadata),
+        .map(|item| {
+            Ok(BatchInsertItem {
+                id: item.id,
+                vector: request_vector(item.vector)?,
+                metadata: request_metadata(item.metadata)?,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<_>, ApiError>>()?;
 
     let count = items.len();
+    store
+        .insert_batch(items)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    Ok(BatchInsertResponse {
+        inserted: count,
+        errors: None,
+    })
+}
 
-    let mut store = state.store.write().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
-
-    store.insert_batch(items).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
+fn batch_insert_lenient_into(
+    store: &mut impl StoreWrite,
+    req: BatchInsertRequest,
+) -> BatchInsertResponse {
+    // Items that fail validation never reach the store; keep their positions
+    // so all errors come back in request order
+    let mut errors: Vec<(usize, BatchInsertError)> = Vec::new();
+    let mut positions = Vec::new();
+    let mut items = Vec::new();
+    for (pos, item) in req.vectors.into_iter().enumerate() {
+        let checked = request_vector(item.vector)
+            .and_then(|vector| Ok((vector, request_metadata(item.metadata)?)));
+        match checked {
+            Ok((vector, metadata)) => {
+                positions.push(pos);
+                items.push(BatchInsertItem {
+                    id: item.id,
+                    vector,
+                    metadata,
+                });
+            }
+            Err((_, e)) => {
+                let error = BatchInsertError {
+                    id: item.id,
+                    error: e.0.error,
+                };
+                errors.push((pos, error));
+            }
+        }
+    }
 
-    if let Ok(mut metrics) = state.metrics.write() {
-        for _ in 0..count {
-            metrics.record_insert();
+    let ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+    let results = store.insert_batch_lenient(items);
+    let mut inserted = 0;
+    for ((pos, id), result) in positions.into_iter().zip(ids).zip(results) {
+        match result {
+            Ok(()) => inserted += 1,
+            Err(e) => {
+                let error = BatchInsertError {
+                    id,
+                    error: e.to_string(),
+                };
+                errors.push((pos, error));
+            }
         }
     }
+    errors.sort_by_key(|(pos, _)| *pos);
 
-    Ok((
-        StatusCode::CREATED,
-        Json(serde_json::json!({"inserted": count})),
-    ))
+    BatchInsertResponse {
+        inserted,
+        errors: Some(errors.into_iter().map(|(_, error)| error).collect()),
+    }
 }
 
-async fn batch_search<I: Index + Send + Sync + std::fmt::Debug + 'static>(
-    State(state): State<Arc<AppState<I>>>,
-    Json(req): Json<BatchSearchRequest>,
-) -> Result<Json<Vec<Vec<SearchResultResponse>>>, (StatusCode, Json<ErrorResponse>)> {
-    let queries: Vec<(Vector, usize)> = req
+fn batch_search_in<I: Index>(
+    store: &VectorStore<I>,
+    req: BatchSearchRequest,
+) -> Result<Vec<Vec<SearchResultResponse>>, ApiError> {
+    let queries = req
         .queries
-        .iter()
-        .map(|q| (Vector::new(q.vector.clone()), q.k.unwrap_or(10)))
-        .collect();
-
-    let start = Instant::now();
-
-    let store = state.store.read().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
+        .into_iter()
+        .map(|q| Ok((request_vector(q.vector)?, q.k.unwrap_or(10))))
+        .collect::<Result<Vec<(Vector, usize)>, ApiError>>()?;
 
     let all_results = if let Some(filter) = &req.filter {
         store.search_batch_with_filter(&queries, filter)
     } else {
         store.search_batch(&queries)
     }
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
-
-    let elapsed = start.elapsed();
-
-    if let Ok(mut metrics) = state.metrics.write() {
-        metrics.record_query(elapsed);
-    }
+    .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
 
-    let response: Vec<Vec<SearchResultResponse>> = all_results
+    Ok(all_results
         .into_iter()
         .map(|results| {
             results
                 .into_iter()
-                .map(|r| SearchResultResponse {
-                    id: r.id,
-                    distance: r.distance,
-                })
+                .map(|r| SearchResultResponse::new(r, ScoreKind::Distance))
                 .collect()
         })
-        .collect();
-
-    Ok(Json(response))
+        .collect())
 }
 
-async fn list_vectors<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+async fn insert_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
     State(state): State<Arc<AppState<I>>>,
-) -> Result<Json<Vec<String>>, (StatusCode, Json<ErrorResponse>)> {
-    let store = state.store.read().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Lock poisoned".to_string(),
-            }),
-        )
-    })?;
+    Json(req): Json<InsertRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let id = req.id.clone();
+    let mut store = state.store.write().map_err(lock_poisoned)?;
+    insert_into(&mut *store, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_insert();
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({"id": id, "status": "inserted"})),
+    ))
+}
+
+async fn get_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(id): Path<String>,
+) -> Result<Json<VectorResponse>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    Ok(Json(vector_response(&store, id)?))
+}
+
+async fn get_many_vectors<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<GetManyRequest>,
+) -> Result<Json<GetManyResponse>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    Ok(Json(get_many_in(&store, req)))
+}
+
+async fn delete_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut store = state.store.write().map_err(lock_poisoned)?;
+    store
+        .delete(&id)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e))?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_delete();
+    }
+
+    Ok(Json(serde_json::json!({"id": id, "status": "deleted"})))
+}
+
+async fn update_metadata<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateMetadataRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let metadata = request_metadata(Some(req.metadata))?;
+    let mut store = state.store.write().map_err(lock_poisoned)?;
+    store
+        .update_metadata(&id, metadata)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e))?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_update();
+    }
+
+    Ok(Json(serde_json::json!({"id": id, "status": "updated"})))
+}
+
+async fn rename_vector<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(id): Path<String>,
+    Json(req): Json<RenameRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut store = state.store.write().map_err(lock_poisoned)?;
+    let body = rename_in(&mut *store, &id, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_update();
+    }
+
+    Ok(Json(body))
+}
+
+async fn search_vectors<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<SearchRequest>,
+) -> Result<Json<SearchResponse>, ApiError> {
+    let start = Instant::now();
+    run_search(state, move |state, deadline| {
+        let store = state.store.read().map_err(lock_poisoned)?;
+        deadline.check()?;
+        let response = search_in(&store, req)?;
+
+        if let Ok(mut metrics) = state.metrics.write() {
+            metrics.record_query(start.elapsed());
+        }
+
+        Ok(Json(response))
+    })
+    .await
+}
+
+async fn batch_insert<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<BatchInsertRequest>,
+) -> Result<(StatusCode, Json<BatchInsertResponse>), ApiError> {
+    let mut store = state.store.write().map_err(lock_poisoned)?;
+    let response = batch_insert_into(&mut *store, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        for _ in 0..response.inserted {
+            metrics.record_insert();
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Insert newline-delimited `InsertRequest` objects as the body arrives.
+/// Each complete line is applied on its own, so a bad line is reported and
+/// skipped rather than failing the whole upload.
+async fn stream_insert<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    body: Body,
+) -> Result<Json<StreamInsertResponse>, ApiError> {
+    let mut stream = body.into_data_stream();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut line_no = 0;
+    let mut summary = StreamInsertResponse {
+        inserted: 0,
+        errors: Vec::new(),
+        error_count: 0,
+    };
+
+    loop {
+        let chunk = stream
+            .next()
+            .await
+            .transpose()
+            .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+        let finished = chunk.is_none();
+        match chunk {
+            Some(bytes) => pending.extend_from_slice(&bytes),
+            // Treat a trailing line without a newline as complete
+            None => pending.push(b'\n'),
+        }
+
+        let Some(last_newline) = pending.iter().rposition(|&b| b == b'\n') else {
+            if pending.len() > STREAM_MAX_LINE_BYTES {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    format!("Line {} exceeds {} bytes", line_no + 1, STREAM_MAX_LINE_BYTES),
+                ));
+            }
+            continue;
+        };
+        let rest = pending.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut pending, rest);
+
+        let mut store = state.store.write().map_err(lock_poisoned)?;
+        // Drop the final newline so split() doesn't yield an empty tail
+        for line in complete[..complete.len() - 1].split(|&b| b == b'\n') {
+            line_no += 1;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let result = serde_json::from_slice::<InsertRequest>(line)
+                .map_err(|e| e.to_string())
+                .and_then(|req| insert_into(&mut *store, req).map_err(|(_, e)| e.0.error));
+            match result {
+                Ok(()) => summary.inserted += 1,
+                Err(error) => {
+                    summary.error_count += 1;
+                    if summary.errors.len() < STREAM_MAX_REPORTED_ERRORS {
+                        summary.errors.push(StreamInsertError {
+                            line: line_no,
+                            error,
+                        });
+                    }
+                }
+            }
+        }
+        drop(store);
+
+        if finished {
+            break;
+        }
+    }
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        for _ in 0..summary.inserted {
+            metrics.record_insert();
+        }
+    }
+
+    Ok(Json(summary))
+}
+
+async fn batch_search<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<BatchSearchRequest>,
+) -> Result<Json<Vec<Vec<SearchResultResponse>>>, ApiError> {
+    let start = Instant::now();
+    run_search(state, move |state, deadline| {
+        let store = state.store.read().map_err(lock_poisoned)?;
+        deadline.check()?;
+        let response = batch_search_in(&store, req)?;
+
+        if let Ok(mut metrics) = state.metrics.write() {
+            metrics.record_query(start.elapsed());
+        }
+
+        Ok(Json(response))
+    })
+    .await
+}
+
+async fn list_vectors<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    Ok(Json(store.list_ids()))
+}
+
+async fn list_metadata<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<MetadataPageResponse>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    Ok(Json(metadata_page_in(&store, page)))
+}
+
+async fn distinct_values<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(field): Path<String>,
+) -> Result<Json<DistinctValuesResponse>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    Ok(Json(distinct_values_in(&store, &field)))
+}
+
+// --- Collection handlers ---
+
+fn collection_not_found(name: &str) -> ApiError {
+    api_error(
+        StatusCode::NOT_FOUND,
+        VectorDbError::CollectionNotFound {
+            name: name.to_string(),
+        },
+    )
+}
+
+async fn list_collections<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    Ok(Json(db.list_collections()))
+}
+
+async fn create_collection<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<CreateCollectionRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    db.create_collection(req.name.clone(), req.metric)
+        .map_err(|e| api_error(StatusCode::CONFLICT, e))?
+        .set_max_dimension(state.config.max_dimension);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({"name": req.name, "status": "created"})),
+    ))
+}
+
+async fn drop_collection<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    db.drop_collection(&name)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e))?;
+
+    Ok(Json(serde_json::json!({"name": name, "status": "dropped"})))
+}
+
+async fn collection_insert<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Json(req): Json<InsertRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let id = req.id.clone();
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    let store = db
+        .collection_mut(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    insert_into(store, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_insert();
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({"id": id, "status": "inserted"})),
+    ))
+}
 
+async fn collection_list<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    let store = db
+        .collection(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
     Ok(Json(store.list_ids()))
 }
 
+async fn collection_get<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<VectorResponse>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    let store = db
+        .collection(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    Ok(Json(vector_response(store, id)?))
+}
+
+async fn collection_get_many<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Json(req): Json<GetManyRequest>,
+) -> Result<Json<GetManyResponse>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    let store = db
+        .collection(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    Ok(Json(get_many_in(store, req)))
+}
+
+async fn collection_metadata<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<MetadataPageResponse>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    let store = db
+        .collection(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    Ok(Json(metadata_page_in(store, page)))
+}
+
+async fn collection_distinct_values<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path((name, field)): Path<(String, String)>,
+) -> Result<Json<DistinctValuesResponse>, ApiError> {
+    let db = state.collections.read().map_err(lock_poisoned)?;
+    let store = db
+        .collection(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    Ok(Json(distinct_values_in(store, &field)))
+}
+
+async fn collection_delete<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    let store = db
+        .collection_mut(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    store
+        .delete(&id)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e))?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_delete();
+    }
+
+    Ok(Json(serde_json::json!({"id": id, "status": "deleted"})))
+}
+
+async fn collection_update_metadata<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path((name, id)): Path<(String, String)>,
+    Json(req): Json<UpdateMetadataRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let metadata = request_metadata(Some(req.metadata))?;
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    let store = db
+        .collection_mut(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    store
+        .update_metadata(&id, metadata)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e))?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_update();
+    }
+
+    Ok(Json(serde_json::json!({"id": id, "status": "updated"})))
+}
+
+async fn collection_rename<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path((name, id)): Path<(String, String)>,
+    Json(req): Json<RenameRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    let store = db
+        .collection_mut(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    let body = rename_in(store, &id, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        metrics.record_update();
+    }
+
+    Ok(Json(body))
+}
+
+async fn collection_search<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Json(req): Json<SearchRequest>,
+) -> Result<Json<SearchResponse>, ApiError> {
+    let start = Instant::now();
+    run_search(state, move |state, deadline| {
+        let db = state.collections.read().map_err(lock_poisoned)?;
+        deadline.check()?;
+        let store = db
+            .collection(&name)
+            .ok_or_else(|| collection_not_found(&name))?;
+        let response = search_in(store, req)?;
+
+        if let Ok(mut metrics) = state.metrics.write() {
+            metrics.record_query(start.elapsed());
+        }
+
+        Ok(Json(response))
+    })
+    .await
+}
+
+async fn collection_batch_insert<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Json(req): Json<BatchInsertRequest>,
+) -> Result<(StatusCode, Json<BatchInsertResponse>), ApiError> {
+    let mut db = state.collections.write().map_err(lock_poisoned)?;
+    let store = db
+        .collection_mut(&name)
+        .ok_or_else(|| collection_not_found(&name))?;
+    let response = batch_insert_into(store, req)?;
+
+    if let Ok(mut metrics) = state.metrics.write() {
+        for _ in 0..response.inserted {
+            metrics.record_insert();
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn collection_batch_search<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(name): Path<String>,
+    Json(req): Json<BatchSearchRequest>,
+) -> Result<Json<Vec<Vec<SearchResultResponse>>>, ApiError> {
+    let start = Instant::now();
+    run_search(state, move |state, deadline| {
+        let db = state.collections.read().map_err(lock_poisoned)?;
+        deadline.check()?;
+        let store = db
+            .collection(&name)
+            .ok_or_else(|| collection_not_found(&name))?;
+        let response = batch_search_in(store, req)?;
+
+        if let Ok(mut metrics) = state.metrics.write() {
+            metrics.record_query(start.elapsed());
+        }
+
+        Ok(Json(response))
+    })
+    .await
+}
+
 async fn health<I: Index + Send + Sync + std::fmt::Debug + 'static>(
     State(state): State<Arc<AppState<I>>>,
 ) -> Json<HealthResponse> {
-    let count = state
+    let (count, metric, dimension) = state
         .store
         .read()
-        .map(|s| s.len())
-        .unwrap_or(0);
+        .map(|s| (s.len(), Some(s.metric()), s.dimension()))
+        .unwrap_or((0, None, None));
 
     Json(HealthResponse {
         status: "ok".to_string(),
         vector_count: count,
+        metric,
+        dimension,
     })
 }
 
 async fn get_metrics<I: Index + Send + Sync + std::fmt::Debug + 'static>(
     State(state): State<Arc<AppState<I>>>,
 ) -> Json<MetricsResponse> {
+    let (index_memory_bytes, hnsw) = state
+        .store
+        .read()
+        .map(|s| {
+            let hnsw = HnswIndex::downcast(s.index()).map(HnswIndex::stats);
+            (s.memory_usage_bytes(), hnsw)
+        })
+        .unwrap_or((0, None));
     let metrics = state.metrics.read().unwrap();
 
     Json(MetricsResponse {
         total_queries: metrics.total_queries(),
         total_inserts: metrics.total_inserts(),
         total_deletes: metrics.total_deletes(),
+        total_updates: metrics.total_updates(),
+        qps: metrics.queries_per_second(),
         avg_query_latency_us: metrics.avg_query_latency_us(),
         p50_query_latency_us: metrics.percentile_query_latency_us(50.0),
         p95_query_latency_us: metrics.percentile_query_latency_us(95.0),
         p99_query_latency_us: metrics.percentile_query_latency_us(99.0),
+        index_memory_bytes,
+        hnsw,
     })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::flat_index::FlatIndex;
-    use crate::metrics::MetricsCollector;
-    use crate::storage::VectorStore;
-    use crate::distance::DistanceMetric;
-    use axum::body::Body;
-    use axum::http::Request;
-    use std::sync::RwLock;
-    use tower::ServiceExt;
+async fn get_metrics_prometheus<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+) -> impl IntoResponse {
+    let (vector_count, index_memory_bytes) = state
+        .store
+        .read()
+        .map(|s| (s.len(), s.memory_usage_bytes()))
+        .unwrap_or((0, 0));
+    let mut body = state.metrics.read().unwrap().to_prometheus();
+    body.push_str(&format!(
+        "# HELP vectordb_vectors Vectors in the default store.\n\
+         # TYPE vectordb_vectors gauge\n\
+         vectordb_vectors {}\n\
+         # HELP vectordb_index_memory_bytes Estimated index memory.\n\
+         # TYPE vectordb_index_memory_bytes gauge\n\
+         vectordb_index_memory_bytes {}\n",
+        vector_count, index_memory_bytes
+    ));
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    )
+}
 
-    fn test_app() -> (Router, Arc<AppState<FlatIndex>>) {
-        let store = VectorStore::new(DistanceMetric::Euclidean);
-        let state = Arc::new(AppState {
-            store: RwLock::new(store),
-            metrics: RwLock::new(MetricsCollector::new()),
-        });
-        let app = create_router(state.clone());
-        (app, state)
+async fn reset_metrics<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state.metrics.write().map_err(lock_poisoned)?.reset();
+    Ok(Json(serde_json::json!({"status": "reset"})))
+}
+
+/// Report where a vector sits in the default store's HNSW graph. 404 for an
+/// unknown ID, 400 if the store isn't HNSW-backed.
+#[cfg(feature = "graph-debug")]
+async fn graph_node<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Path(id): Path<String>,
+) -> Result<Json<GraphNodeResponse>, ApiError> {
+    let store = state.store.read().map_err(lock_poisoned)?;
+    let internal_id = store.internal_id(&id).ok_or_else(|| {
+        api_error(StatusCode::NOT_FOUND, VectorDbError::VectorNotFound { id: id.clone() })
+    })?;
+    let hnsw = HnswIndex::downcast(store.index()).ok_or_else(|| {
+        api_error(StatusCode::BAD_REQUEST, "the served index is not an HNSW graph")
+    })?;
+    let node = hnsw.node_info(internal_id).ok_or_else(|| {
+        api_error(StatusCode::NOT_FOUND, VectorDbError::VectorNotFound { id: id.clone() })
+    })?;
+    Ok(Json(GraphNodeResponse {
+        id,
+        internal_id,
+        node,
+    }))
+}
+
+/// Measure the default store's recall@k against exact search, using a random
+/// sample of stored vectors as queries.
+#[cfg(feature = "eval-endpoint")]
+async fn eval_recall<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<EvalRecallRequest>,
+) -> Result<Json<EvalRecallResponse>, ApiError> {
+    use rand::seq::IteratorRandom;
+
+    let k = req.k.unwrap_or(10);
+    if k == 0 || req.queries > EVAL_MAX_QUERIES {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            format!("k must be at least 1 and queries at most {}", EVAL_MAX_QUERIES),
+        ));
     }
 
-    async fn body_to_json(body: Body) -> serde_json::Value {
-        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
-        serde_json::from_slice(&bytes).unwrap()
+    tokio::task::spawn_blocking(move || {
+        let store = state.store.read().map_err(lock_poisoned)?;
+        let ground_truth = eval::flat_copy(store.index(), store.internal_to_string_ids().keys())
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let queries: Vec<Vector> = ground_truth
+            .iter()
+            .map(|(_, v)| v.clone())
+            .choose_multiple(&mut rand::thread_rng(), req.queries);
+        let recall = eval::evaluate_recall(store.index(), &ground_truth, &queries, k)
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(Json(EvalRecallResponse {
+            recall,
+            k,
+            queries: queries.len(),
+            vectors: ground_truth.len(),
+        }))
+    })
+    .await
+    .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?
+}
+
+async fn self_bench<I: Index + Send + Sync + std::fmt::Debug + 'static>(
+    State(state): State<Arc<AppState<I>>>,
+    Json(req): Json<BenchConfig>,
+) -> Result<Json<BenchReport>, (StatusCode, Json<ErrorResponse>)> {
+    if !state.config.admin_enabled {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Admin endpoints are disabled".to_string(),
+            }),
+        ));
+    }
+
+    if req.n == 0
+        || req.dim == 0
+        || req.k == 0
+        || req.queries == 0
+        || req.n > SELFBENCH_MAX_N
+        || req.dim > SELFBENCH_MAX_DIM
+        || req.queries > SELFBENCH_MAX_QUERIES
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Parameters out of range (1..={} vectors, 1..={} dims, 1..={} queries, k >= 1)",
+                    SELFBENCH_MAX_N, SELFBENCH_MAX_DIM, SELFBENCH_MAX_QUERIES
+                ),
+            }),
+        ));
+    }
+
+    // Only the metric is read from the live store; the benchmark runs on its own index.
+    let metric = state
+        .store
+        .read()
+        .map(|s| s.metric())
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "Lock poisoned".to_string(),
+                }),
+            )
+        })?;
+
+    let report = tokio::task::spawn_blocking(move || bench::run(&req, metric))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?;
+
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flat_index::FlatIndex;
+    use crate::server::ServerConfig;
+    use crate::hnsw::{HnswIndex, HnswParams};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn test_app() -> (Router, Arc<AppState<FlatIndex>>) {
+        test_app_with_config(ServerConfig::default())
+    }
+
+    fn test_app_with_config(config: ServerConfig) -> (Router, Arc<AppState<FlatIndex>>) {
+        let store = VectorStore::new(DistanceMetric::Euclidean);
+        let state = Arc::new(AppState::new(store, config));
+        let app = create_router(state.clone());
+        (app, state)
+    }
+
+    async fn body_to_json(body: Body) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_insert_with_metadata() {
+        let (app, _) = test_app();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/vectors")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "id": "v1",
+                    "vector": [1.0, 2.0, 3.0],
+                    "metadata": {"color": "red", "size": "large"}
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_insert_without_metadata_backward_compat() {
+        let (app, _) = test_app();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/vectors")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "id": "v1",
+                    "vector": [1.0, 2.0, 3.0]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_get_vector_returns_data() {
+        let (app, state) = test_app();
+
+        // Insert a vector with metadata
+        {
+            let mut store = state.store.write().unwrap();
+            let mut meta = Metadata::new();
+            meta.insert("color".to_string(), "red".to_string());
+            store
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0, 3.0]), meta)
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/vectors/v1")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["id"], "v1");
+        assert_eq!(body["dimension"], 3);
+        assert_eq!(body["vector"], serde_json::json!([1.0, 2.0, 3.0]));
+        assert_eq!(body["metadata"]["color"], "red");
+        assert!(body["created_at"].as_u64().unwrap() > 0);
+        assert_eq!(body["created_at"], body["updated_at"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_with_filter() {
+        let (app, state) = test_app();
+
+        {
+            let mut store = state.store.write().unwrap();
+            let mut m1 = Metadata::new();
+            m1.insert("color".to_string(), "red".to_string());
+            store
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0, 0.0]), m1)
+                .unwrap();
+
+            let mut m2 = Metadata::new();
+            m2.insert("color".to_string(), "blue".to_string());
+            store
+                .insert_with_metadata("v2", Vector::new(vec![0.9, 0.1, 0.0]), m2)
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vector": [1.0, 0.0, 0.0],
+                    "k": 10,
+                    "filter": {"op": "eq", "field": "color", "value": "red"}
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["id"], "v1");
+    }
+
+    // Holding the read guard across the awaits is the point of the test
+    #[allow(clippy::await_holding_lock)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_searches_share_the_read_lock() {
+        let (app, state) = test_app();
+        {
+            let mut store = state.store.write().unwrap();
+            for i in 0..100 {
+                store
+                    .insert(format!("v{}", i), Vector::new(vec![i as f32, 0.0]))
+                    .unwrap();
+            }
+        }
+
+        // With a reader already holding the lock, searches still get through
+        let _reader = state.store.read().unwrap();
+        let searches = (0..16).map(|i| {
+            let app = app.clone();
+            tokio::spawn(async move {
+                let req = Request::builder()
+                    .method("POST")
+                    .uri("/search")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"vector": [i as f32, 0.0], "k": 1}).to_string(),
+                    ))
+                    .unwrap();
+                let resp = app.oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+                let body = body_to_json(resp.into_body()).await;
+                assert_eq!(body[0]["id"], format!("v{}", i));
+            })
+        });
+        let handles: Vec<_> = searches.collect();
+        let all = async {
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), all)
+            .await
+            .expect("searches blocked on the store lock");
+    }
+
+    // An otherwise unused write guard is held across awaits to stall the search
+    #[allow(clippy::await_holding_lock, clippy::readonly_write_lock)]
+    #[tokio::test]
+    async fn test_search_timeout() {
+        let (app, state) = test_app_with_config(ServerConfig {
+            search_timeout: Some(std::time::Duration::from_millis(50)),
+            ..Default::default()
+        });
+        state
+            .store
+            .write()
+            .unwrap()
+            .insert("v1", Vector::new(vec![1.0, 0.0]))
+            .unwrap();
+        let search = || json_request("POST", "/search", serde_json::json!({"vector": [1.0, 0.0]}));
+
+        let resp = app.clone().oneshot(search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // A writer holding the lock makes the search wait past the timeout
+        let writer = state.store.write().unwrap();
+        let resp = app.clone().oneshot(search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
+        // Collection searches don't touch the default store's lock
+        let req = json_request("POST", "/collections", serde_json::json!({"name": "c"}));
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let req = json_request(
+            "POST",
+            "/collections/c/search",
+            serde_json::json!({"vector": [1.0, 0.0]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
+
+        drop(writer);
+        let resp = app.oneshot(search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    // As above, the write guard stalls the first search on the store lock
+    #[allow(clippy::await_holding_lock, clippy::readonly_write_lock)]
+    #[tokio::test]
+    async fn test_timed_out_search_keeps_its_slot_until_it_stops() {
+        let (app, state) = test_app_with_config(ServerConfig {
+            search_timeout: Some(std::time::Duration::from_millis(50)),
+            max_concurrent_searches: Some(1),
+            ..Default::default()
+        });
+        state
+            .store
+            .write()
+            .unwrap()
+            .insert("v1", Vector::new(vec![1.0, 0.0]))
+            .unwrap();
+        let req = json_request("POST", "/collections", serde_json::json!({"name": "c"}));
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let search = || json_request("POST", "/search", serde_json::json!({"vector": [1.0, 0.0]}));
+        let collection_search = || {
+            json_request(
+                "POST",
+                "/collections/c/search",
+                serde_json::json!({"vector": [1.0, 0.0]}),
+            )
+        };
+
+        let writer = state.store.write().unwrap();
+        let resp = app.clone().oneshot(search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
+        // The stalled search still holds the only slot, so this one can't start
+        let resp = app.clone().oneshot(collection_search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
+        // Once it gets the lock it sees its deadline has passed and frees the slot
+        drop(writer);
+        let slots = state.search_slots.as_ref().unwrap();
+        for _ in 0..100 {
+            if slots.available_permits() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(slots.available_permits(), 1);
+        let resp = app.clone().oneshot(collection_search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = app.oneshot(search()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_search_over_fetch_on_hnsw() {
+        let mut store = VectorStore::with_index(HnswIndex::new(DistanceMetric::Euclidean));
+        for i in 0..100 {
+            let mut meta = Metadata::new();
+            let tag = if i % 20 == 19 { "rare" } else { "common" };
+            meta.insert("tag".to_string(), tag.to_string());
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+        let state = Arc::new(AppState::new(store, ServerConfig::default()));
+        let app = create_router(state);
+
+        let search = |over_fetch: Option<usize>| {
+            let mut body = serde_json::json!({
+                "vector": [0.0],
+                "k": 5,
+                "filter": {"op": "eq", "field": "tag", "value": "rare"}
+            });
+            if let Some(over_fetch) = over_fetch {
+                body["over_fetch"] = over_fetch.into();
+            }
+            Request::builder()
+                .method("POST")
+                .uri("/search")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let resp = app.clone().oneshot(search(None)).await.unwrap();
+        let default_len = body_to_json(resp.into_body()).await.as_array().unwrap().len();
+        let resp = app.oneshot(search(Some(20))).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_to_json(resp.into_body()).await;
+        // 3x over-fetch stops at v14, before the first match
+        assert_eq!(default_len, 0);
+        assert_eq!(body.as_array().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_search_offset_and_limit() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        for i in 0..10 {
+            store.insert(format!("v{}", i), Vector::new(vec![i as f32])).unwrap();
+        }
+        let state = Arc::new(AppState::new(store, ServerConfig::default()));
+        let app = create_router(state);
+
+        let ids = |body: &serde_json::Value| -> Vec<String> {
+            let results = body["results"].as_array().unwrap();
+            results.iter().map(|r| r["id"].as_str().unwrap().to_string()).collect()
+        };
+        let body = serde_json::json!({"vector": [0.0], "offset": 3, "limit": 4});
+        let resp = app
+            .clone()
+            .oneshot(json_request("POST", "/search", body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let page = body_to_json(resp.into_body()).await;
+        assert_eq!(ids(&page), ["v3", "v4", "v5", "v6"]);
+        assert_eq!(page["next_offset"], 7);
+
+        // Without `limit`, `k` is the page size; the last page comes up short
+        let body = serde_json::json!({"vector": [0.0], "k": 4, "offset": 8});
+        let resp = app
+            .clone()
+            .oneshot(json_request("POST", "/search", body))
+            .await
+            .unwrap();
+        let page = body_to_json(resp.into_body()).await;
+        assert_eq!(ids(&page), ["v8", "v9"]);
+        assert!(page["next_offset"].is_null());
+
+        // Filtered searches page the same way
+        let body = serde_json::json!({
+            "vector": [0.0],
+            "filter": {"op": "ne", "field": "tag", "value": "x"},
+            "offset": 2,
+            "limit": 2
+        });
+        let resp = app
+            .clone()
+            .oneshot(json_request("POST", "/search", body))
+            .await
+            .unwrap();
+        let page = body_to_json(resp.into_body()).await;
+        assert_eq!(ids(&page), ["v2", "v3"]);
+        assert_eq!(page["next_offset"], 4);
+
+        // Without paging fields the response stays a plain array
+        let body = serde_json::json!({"vector": [0.0], "k": 2});
+        let resp = app.oneshot(json_request("POST", "/search", body)).await.unwrap();
+        assert_eq!(body_to_json(resp.into_body()).await.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_with_ef_on_hnsw() {
+        let index =
+            HnswIndex::with_params(DistanceMetric::Euclidean, HnswParams::new(8, 64, 16)).unwrap();
+        let mut store = VectorStore::with_index(index);
+        for i in 0..200 {
+            store
+                .insert(format!("v{}", i), Vector::new(vec![i as f32, (i % 7) as f32]))
+                .unwrap();
+        }
+        let state = Arc::new(AppState::new(store, ServerConfig::default()));
+        let app = create_router(state);
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({"vector": [50.0, 1.0], "k": 10, "ef": 200}).to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[0]["id"], "v50");
+    }
+
+    #[tokio::test]
+    async fn test_search_without_filter_backward_compat() {
+        let (app, state) = test_app();
+
+        {
+            let mut store = state.store.write().unwrap();
+            store
+                .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vector": [1.0, 0.0, 0.0],
+                    "k": 10
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_insert_endpoint() {
+        let (app, state) = test_app();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/vectors/batch")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": [
+                        {"id": "v1", "vector": [1.0, 0.0, 0.0]},
+                        {"id": "v2", "vector": [0.0, 1.0, 0.0], "metadata": {"color": "blue"}}
+                    ]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["inserted"], 2);
+
+        let store = state.store.read().unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(body.get("errors").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_insert_continue_on_error() {
+        let (app, state) = test_app();
+        let vectors = serde_json::json!([
+            {"id": "v1", "vector": [1.0, 0.0, 0.0]},
+            {"id": "short", "vector": [0.0, 1.0]},
+            {"id": "inf", "vector": [1e39, 0.0, 0.0]},
+            {"id": "v2", "vector": [0.0, 1.0, 0.0]}
+        ]);
+
+        // Without the flag the first bad item fails the request
+        let req = json_request("POST", "/vectors/batch", serde_json::json!({"vectors": vectors}));
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(state.store.read().unwrap().is_empty());
+
+        let req = json_request(
+            "POST",
+            "/vectors/batch",
+            serde_json::json!({"vectors": vectors, "continue_on_error": true}),
+        );
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["inserted"], 2);
+        let errors = body["errors"].as_array().unwrap();
+        let ids: Vec<&str> = errors.iter().map(|e| e["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["short", "inf"]);
+        assert!(errors[0]["error"].as_str().unwrap().contains("Dimension mismatch"));
+
+        let store = state.store.read().unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(store.get("v2").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_large_responses_are_compressed() {
+        let (app, state) = test_app();
+        {
+            let mut store = state.store.write().unwrap();
+            for i in 0..500 {
+                store
+                    .insert(format!("vector-{}", i), Vector::new(vec![i as f32]))
+                    .unwrap();
+            }
+        }
+        let get = |uri: &str, encoding: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(header::ACCEPT_ENCODING, encoding)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let resp = app.clone().oneshot(get("/vectors", "gzip")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CONTENT_ENCODING], "gzip");
+        let resp = app.clone().oneshot(get("/vectors", "deflate")).await.unwrap();
+        assert_eq!(resp.headers()[header::CONTENT_ENCODING], "deflate");
+
+        // Below the threshold, or when the client doesn't ask, nothing changes
+        let resp = app.clone().oneshot(get("/health", "gzip")).await.unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+        let resp = app.oneshot(get("/vectors", "identity")).await.unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body.as_array().unwrap().len(), 500);
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_logged() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        // The default current-thread runtime keeps the handler on this thread
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let (app, _) = test_app();
+        let req = json_request(
+            "POST",
+            "/vectors",
+            serde_json::json!({"id": "v1", "vector": [1.0, 2.0]}),
+        );
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let resp = app
+            .oneshot(Request::builder().uri("/vectors/missing").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", output);
+        assert!(lines[0].contains("method=POST"));
+        assert!(lines[0].contains("path=/vectors"));
+        assert!(lines[0].contains("status=201"));
+        assert!(lines[0].contains("latency_ms="));
+        assert!(lines[1].contains("path=/vectors/missing"));
+        assert!(lines[1].contains("status=404"));
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_is_rejected() {
+        let (app, state) = test_app_with_config(ServerConfig {
+            max_body_bytes: Some(4096),
+            ..Default::default()
+        });
+        let batch = |n: usize| {
+            let vectors: Vec<_> = (0..n)
+                .map(|i| serde_json::json!({"id": format!("v{}", i), "vector": [1.0, 2.0, 3.0]}))
+                .collect();
+            json_request("POST", "/vectors/batch", serde_json::json!({"vectors": vectors}))
+        };
+
+        let resp = app.clone().oneshot(batch(500)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(state.store.read().unwrap().is_empty());
+
+        let resp = app.oneshot(batch(10)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_batch_search_endpoint() {
+        let (app, state) = test_app();
+
+        {
+            let mut store = state.store.write().unwrap();
+            store
+                .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
+                .unwrap();
+            store
+                .insert("v2", Vector::new(vec![0.0, 1.0, 0.0]))
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/search/batch")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "queries": [
+                        {"vector": [1.0, 0.0, 0.0], "k": 1},
+                        {"vector": [0.0, 1.0, 0.0], "k": 1}
+                    ]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0][0]["id"], "v1");
+        assert_eq!(results[1][0]["id"], "v2");
+    }
+
+    #[tokio::test]
+    async fn test_batch_search_with_filter_endpoint() {
+        let (app, state) = test_app();
+
+        {
+            let mut store = state.store.write().unwrap();
+            let mut m1 = Metadata::new();
+            m1.insert("color".to_string(), "red".to_string());
+            store
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0, 0.0]), m1)
+                .unwrap();
+
+            let mut m2 = Metadata::new();
+            m2.insert("color".to_string(), "blue".to_string());
+            store
+                .insert_with_metadata("v2", Vector::new(vec![0.0, 1.0, 0.0]), m2)
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/search/batch")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "queries": [
+                        {"vector": [1.0, 0.0, 0.0], "k": 10},
+                        {"vector": [0.0, 1.0, 0.0], "k": 10}
+                    ],
+                    "filter": {"op": "eq", "field": "color", "value": "red"}
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        // Both queries should only return v1 (the red one)
+        assert_eq!(results[0].as_array().unwrap().len(), 1);
+        assert_eq!(results[0][0]["id"], "v1");
+        assert_eq!(results[1].as_array().unwrap().len(), 1);
+        assert_eq!(results[1][0]["id"], "v1");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reports_index_memory() {
+        let (app, state) = test_app();
+
+        {
+            let mut store = state.store.write().unwrap();
+            store
+                .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
+                .unwrap();
+        }
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        assert!(body["index_memory_bytes"].as_u64().unwrap() > 0);
+        // Flat index: no graph summary
+        assert!(body.get("hnsw").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reports_hnsw_stats() {
+        let mut store = VectorStore::with_index(HnswIndex::new(DistanceMetric::Euclidean));
+        for i in 0..20 {
+            store
+                .insert(format!("v{}", i), Vector::new(vec![i as f32, 1.0]))
+                .unwrap();
+        }
+        let app = create_router(Arc::new(AppState::new(store, ServerConfig::default())));
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["hnsw"]["node_count"], 20);
+        assert_eq!(body["hnsw"]["tombstones"], 0);
+    }
+
+    fn selfbench_request() -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/admin/selfbench")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({"n": 50, "dim": 4, "k": 3, "queries": 10}).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_selfbench_endpoint() {
+        let (app, state) = test_app_with_config(ServerConfig {
+            admin_enabled: true,
+            ..Default::default()
+        });
+
+        let resp = app.oneshot(selfbench_request()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["n"], 50);
+        assert_eq!(body["queries"], 10);
+        for field in [
+            "build_time_ms",
+            "qps",
+            "avg_latency_us",
+            "p50_latency_us",
+            "p95_latency_us",
+            "p99_latency_us",
+        ] {
+            let value = body[field].as_f64().unwrap();
+            assert!(value.is_finite() && value >= 0.0, "{} = {}", field, value);
+        }
+        assert!(body["p50_latency_us"].as_f64() <= body["p99_latency_us"].as_f64());
+
+        // The live store is untouched
+        assert!(state.store.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_selfbench_disabled_by_default() {
+        let (app, _) = test_app();
+        let resp = app.oneshot(selfbench_request()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn json_request(method: &str, uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_many_vectors() {
+        let (app, state) = test_app();
+        {
+            let mut store = state.store.write().unwrap();
+            let mut meta = Metadata::new();
+            meta.insert("color".to_string(), "red".to_string());
+            store
+                .insert_with_metadata("a", Vector::new(vec![1.0, 0.0]), meta)
+                .unwrap();
+            store.insert("b", Vector::new(vec![0.0, 1.0])).unwrap();
+        }
+
+        let req = json_request(
+            "POST",
+            "/vectors/get",
+            serde_json::json!({"ids": ["b", "nope", "a", "gone"]}),
+        );
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_to_json(resp.into_body()).await;
+        let ids: Vec<&str> = body["vectors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert_eq!(body["vectors"][1]["metadata"]["color"], "red");
+        assert_eq!(body["vectors"][0]["vector"], serde_json::json!([0.0, 1.0]));
+        assert_eq!(body["missing"], serde_json::json!(["nope", "gone"]));
+    }
+
+    #[tokio::test]
+    async fn test_list_metadata_paginates() {
+        let (app, state) = test_app();
+        {
+            let mut store = state.store.write().unwrap();
+            for i in 0..5 {
+                let mut meta = Metadata::new();
+                meta.insert("label".to_string(), format!("l{}", i));
+                store
+                    .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                    .unwrap();
+            }
+        }
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let body = body_to_json(app.clone().oneshot(get("/metadata")).await.unwrap().into_body())
+            .await;
+        assert_eq!(body["total"], 5);
+        assert_eq!(body["metadata"].as_object().unwrap().len(), 5);
+        assert_eq!(body["metadata"]["v3"], serde_json::json!({"label": "l3"}));
+        assert!(body["metadata"]["v3"].get("vector").is_none());
+
+        let resp = app.oneshot(get("/metadata?offset=1&limit=2")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_to_json(resp.into_body()).await;
+        let ids: Vec<&String> = body["metadata"].as_object().unwrap().keys().collect();
+        assert_eq!(ids, vec!["v1", "v2"]);
+        assert_eq!(body["total"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_values_endpoint() {
+        let (app, state) = test_app();
+        {
+            let mut store = state.store.write().unwrap();
+            for (i, category) in ["b", "a", "b", ""].iter().enumerate() {
+                let mut meta = Metadata::new();
+                if !category.is_empty() {
+                    meta.insert("category".to_string(), category.to_string());
+                }
+                store
+                    .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                    .unwrap();
+            }
+        }
+
+        let req = Request::builder()
+            .uri("/metadata/fields/category/distinct")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["values"], serde_json::json!(["a", "b"]));
+        assert_eq!(body["counts"], serde_json::json!({"a": 1, "b": 2}));
+    }
+
+    #[tokio::test]
+    async fn test_search_similarity_scores() {
+        let (app, _) = test_app();
+        let req = json_request(
+            "POST",
+            "/collections",
+            serde_json::json!({"name": "text", "metric": "Cosine"}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let req = json_request(
+            "POST",
+            "/collections/text/vectors",
+            serde_json::json!({"id": "a", "vector": [0.6, 0.8]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+
+        let search = |score_kind: &str| {
+            json_request(
+                "POST",
+                "/collections/text/search",
+                serde_json::json!({"vector": [0.6, 0.8], "k": 1, "score_kind": score_kind}),
+            )
+        };
+        let resp = app.clone().oneshot(search("similarity")).await.unwrap();
+        let body = body_to_json(resp.into_body()).await;
+        assert!((body[0]["similarity"].as_f64().unwrap() - 1.0).abs() < 1e-6);
+        assert!(body[0].get("distance").is_none());
+
+        let body = body_to_json(app.oneshot(search("distance")).await.unwrap().into_body()).await;
+        assert!(body[0]["distance"].as_f64().unwrap().abs() < 1e-6);
+        assert!(body[0].get("similarity").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cosine_zero_query_is_bad_request() {
+        let (app, _) = test_app();
+        let req = json_request(
+            "POST",
+            "/collections",
+            serde_json::json!({"name": "text", "metric": "Cosine"}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let req = json_request(
+            "POST",
+            "/collections/text/vectors",
+            serde_json::json!({"id": "a", "vector": [0.6, 0.8]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+
+        for uri in ["/collections/text/search", "/collections/text/search/batch"] {
+            let body = if uri.ends_with("batch") {
+                serde_json::json!({"queries": [{"vector": [0.0, 0.0], "k": 1}]})
+            } else {
+                serde_json::json!({"vector": [0.0, 0.0], "k": 1})
+            };
+            let resp = app.clone().oneshot(json_request("POST", uri, body)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body = body_to_json(resp.into_body()).await;
+            assert!(body["error"].as_str().unwrap().contains("non-zero query"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collections_are_isolated() {
+        let (app, state) = test_app();
+
+        for (name, metric) in [("images", "Euclidean"), ("text", "Cosine")] {
+            let req = json_request(
+                "POST",
+                "/collections",
+                serde_json::json!({"name": name, "metric": metric}),
+            );
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::CREATED);
+        }
+
+        let req = json_request(
+            "POST",
+            "/collections/images/vectors",
+            serde_json::json!({"id": "a", "vector": [1.0, 2.0]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let req = json_request(
+            "POST",
+            "/collections/text/vectors/batch",
+            serde_json::json!({"vectors": [
+                {"id": "a", "vector": [1.0, 0.0, 0.0, 0.0]},
+                {"id": "b", "vector": [0.0, 1.0, 0.0, 0.0]}
+            ]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+
+        // Same ID, different collection, different dimension
+        let req = Request::builder()
+            .uri("/collections/text/vectors/a")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_to_json(app.clone().oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body["dimension"], 4);
+
+        let req = json_request(
+            "POST",
+            "/collections/images/search",
+            serde_json::json!({"vector": [1.0, 0.0, 0.0, 0.0], "k": 1}),
+        );
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = json_request(
+            "POST",
+            "/collections/text/search",
+            serde_json::json!({"vector": [0.0, 2.0, 0.0, 0.0], "k": 1}),
+        );
+        let body = body_to_json(app.clone().oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body[0]["id"], "b");
+
+        // The default store is untouched
+        assert!(state.store.read().unwrap().is_empty());
+
+        let req = Request::builder()
+            .uri("/collections")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body, serde_json::json!(["images", "text"]));
+    }
+
+    #[tokio::test]
+    async fn test_collection_lifecycle_errors() {
+        let (app, _) = test_app();
+
+        let req = json_request(
+            "POST",
+            "/collections/missing/vectors",
+            serde_json::json!({"id": "a", "vector": [1.0]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::NOT_FOUND);
+
+        let create = || json_request("POST", "/collections", serde_json::json!({"name": "c"}));
+        assert_eq!(app.clone().oneshot(create()).await.unwrap().status(), StatusCode::CREATED);
+        assert_eq!(app.clone().oneshot(create()).await.unwrap().status(), StatusCode::CONFLICT);
+
+        let drop = || {
+            Request::builder()
+                .method("DELETE")
+                .uri("/collections/c")
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(app.clone().oneshot(drop()).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(app.oneshot(drop()).await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    fn authed_app() -> Router {
+        test_app_with_config(ServerConfig {
+            api_key: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .0
+    }
+
+    fn list_request(auth: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().uri("/vectors");
+        if let Some(auth) = auth {
+            builder = builder.header("Authorization", auth);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_api_key_authorized() {
+        let resp = authed_app()
+            .oneshot(list_request(Some("Bearer secret")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_unauthorized() {
+        let rejected = [
+            None,
+            Some("Bearer wrong"),
+            Some("secret"),
+            Some("Bearer secre"),
+            Some("Bearer secrets"),
+            Some("Bearer sacret"),
+        ];
+        for auth in rejected {
+            let resp = authed_app().oneshot(list_request(auth)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "auth {:?}", auth);
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/collections")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"name": "c"}"#))
+            .unwrap();
+        let resp = authed_app().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // Health stays public
+        let req = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+        let resp = authed_app().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn test_insert_with_metadata() {
-        let (app, _) = test_app();
+    async fn test_health_reports_metric_and_dimension() {
+        let state = Arc::new(AppState::new(
+            VectorStore::<FlatIndex>::new(DistanceMetric::Cosine),
+            ServerConfig::default(),
+        ));
+        let app = create_router(state.clone());
+        let health = || Request::builder().uri("/health").body(Body::empty()).unwrap();
 
-        let req = Request::builder()
-            .method("POST")
-            .uri("/vectors")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "id": "v1",
-                    "vector": [1.0, 2.0, 3.0],
-                    "metadata": {"color": "red", "size": "large"}
-                })
-                .to_string(),
-            ))
-            .unwrap();
+        let body = body_to_json(app.clone().oneshot(health()).await.unwrap().into_body()).await;
+        assert_eq!(body["metric"], "Cosine");
+        assert!(body["dimension"].is_null());
 
-        let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::CREATED);
+        state
+            .store
+            .write()
+            .unwrap()
+            .insert("v1", Vector::new(vec![1.0, 2.0, 3.0]))
+            .unwrap();
+        let body = body_to_json(app.oneshot(health()).await.unwrap().into_body()).await;
+        assert_eq!(body["metric"], "Cosine");
+        assert_eq!(body["dimension"], 3);
+        assert_eq!(body["vector_count"], 1);
     }
 
     #[tokio::test]
-    async fn test_insert_without_metadata_backward_compat() {
+    async fn test_no_api_key_configured() {
         let (app, _) = test_app();
+        let resp = app.oneshot(list_request(None)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
 
-        let req = Request::builder()
-            .method("POST")
-            .uri("/vectors")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "id": "v1",
-                    "vector": [1.0, 2.0, 3.0]
-                })
-                .to_string(),
-            ))
+    #[tokio::test]
+    async fn test_metrics_prometheus() {
+        let (app, state) = test_app();
+        state
+            .store
+            .write()
+            .unwrap()
+            .insert("v1", Vector::new(vec![1.0, 2.0]))
             .unwrap();
+        state.metrics.write().unwrap().record_insert();
 
+        let req = Request::builder()
+            .uri("/metrics/prometheus")
+            .body(Body::empty())
+            .unwrap();
         let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.contains("# TYPE vectordb_inserts_total counter\nvectordb_inserts_total 1\n"));
+        assert!(text.contains("# TYPE vectordb_query_latency_seconds summary\n"));
+        assert!(text.contains("vectordb_vectors 1\n"));
     }
 
     #[tokio::test]
-    async fn test_get_vector_returns_data() {
+    async fn test_patch_metadata() {
         let (app, state) = test_app();
+        let mut meta = Metadata::new();
+        meta.insert("color".to_string(), "red".to_string());
+        meta.insert("size".to_string(), "small".to_string());
+        state
+            .store
+            .write()
+            .unwrap()
+            .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0]), meta)
+            .unwrap();
 
-        // Insert a vector with metadata
-        {
-            let mut store = state.store.write().unwrap();
-            let mut meta = Metadata::new();
-            meta.insert("color".to_string(), "red".to_string());
-            store
-                .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0, 3.0]), meta)
-                .unwrap();
-        }
+        let req = json_request(
+            "PATCH",
+            "/vectors/v1",
+            serde_json::json!({"metadata": {"color": "blue"}}),
+        );
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
 
         let req = Request::builder()
-            .method("GET")
             .uri("/vectors/v1")
             .body(Body::empty())
             .unwrap();
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(
+            body["metadata"],
+            serde_json::json!({"color": "blue", "size": "small"})
+        );
+        assert_eq!(state.metrics.read().unwrap().total_updates(), 1);
+    }
 
-        let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::OK);
+    #[cfg(feature = "graph-debug")]
+    #[tokio::test]
+    async fn test_graph_node_endpoint() {
+        let params = crate::hnsw::HnswParams::new(4, 16, 16);
+        let index = HnswIndex::with_params(DistanceMetric::Euclidean, params.clone()).unwrap();
+        let mut store = VectorStore::with_index(index);
+        for i in 0..100 {
+            store.insert(format!("v{}", i), Vector::new(vec![i as f32, 1.0])).unwrap();
+        }
+        let app = create_router(Arc::new(AppState::new(store, ServerConfig::default())));
 
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let resp = app.clone().oneshot(get("/vectors/v42/graph")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
         let body = body_to_json(resp.into_body()).await;
-        assert_eq!(body["id"], "v1");
-        assert_eq!(body["dimension"], 3);
-        assert_eq!(body["vector"], serde_json::json!([1.0, 2.0, 3.0]));
-        assert_eq!(body["metadata"]["color"], "red");
+        assert_eq!(body["id"], "v42");
+        assert_eq!(body["internal_id"], 42);
+        let level = body["level"].as_u64().unwrap() as usize;
+        assert!(level < params.max_layers);
+        let counts = body["neighbor_counts"].as_array().unwrap();
+        assert_eq!(counts.len(), level + 1);
+        assert!(counts[0].as_u64().unwrap() as usize <= params.m_max0);
+        assert!(counts[1..].iter().all(|d| d.as_u64().unwrap() as usize <= params.m));
+
+        let resp = app.oneshot(get("/vectors/missing/graph")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // The default test app serves a flat index
+        let (app, state) = test_app();
+        state.store.write().unwrap().insert("a", Vector::new(vec![1.0])).unwrap();
+        let resp = app.oneshot(get("/vectors/a/graph")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[cfg(feature = "eval-endpoint")]
     #[tokio::test]
-    async fn test_search_with_filter() {
+    async fn test_eval_recall_endpoint() {
         let (app, state) = test_app();
-
         {
             let mut store = state.store.write().unwrap();
-            let mut m1 = Metadata::new();
-            m1.insert("color".to_string(), "red".to_string());
-            store
-                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0, 0.0]), m1)
-                .unwrap();
-
-            let mut m2 = Metadata::new();
-            m2.insert("color".to_string(), "blue".to_string());
-            store
-                .insert_with_metadata("v2", Vector::new(vec![0.9, 0.1, 0.0]), m2)
-                .unwrap();
+            for (i, v) in crate::bench::random_vectors(100, 4).into_iter().enumerate() {
+                store.insert(format!("v{}", i), v).unwrap();
+            }
         }
 
-        let req = Request::builder()
-            .method("POST")
-            .uri("/search")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "vector": [1.0, 0.0, 0.0],
-                    "k": 10,
-                    "filter": {"op": "eq", "field": "color", "value": "red"}
-                })
-                .to_string(),
-            ))
-            .unwrap();
-
+        let req = json_request("POST", "/eval/recall", serde_json::json!({"queries": 20, "k": 5}));
         let resp = app.oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
-
         let body = body_to_json(resp.into_body()).await;
-        let results = body.as_array().unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0]["id"], "v1");
+        // The test app serves a flat index, which is its own ground truth
+        assert_eq!(body["recall"], 1.0);
+        assert_eq!(body["queries"], 20);
+        assert_eq!(body["vectors"], 100);
     }
 
     #[tokio::test]
-    async fn test_search_without_filter_backward_compat() {
+    async fn test_rejects_non_finite_and_empty_vectors() {
+        let (app, state) = test_app();
+        // 1e39 overflows f32 to infinity during deserialization
+        for (uri, body) in [
+            ("/vectors", serde_json::json!({"id": "inf", "vector": [1.0, 1e39]})),
+            ("/vectors", serde_json::json!({"id": "empty", "vector": []})),
+            (
+                "/vectors/batch",
+                serde_json::json!({"vectors": [
+                    {"id": "ok", "vector": [1.0, 0.0]},
+                    {"id": "inf", "vector": [-1e39, 0.0]}
+                ]}),
+            ),
+            ("/search", serde_json::json!({"vector": [1e39, 0.0]})),
+        ] {
+            let resp = app.clone().oneshot(json_request("POST", uri, body)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body = body_to_json(resp.into_body()).await;
+            assert!(body["error"].as_str().unwrap().contains("Invalid vector"));
+        }
+        assert!(state.store.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_max_dimension_rejects_oversized_vectors() {
+        let (app, state) = test_app_with_config(ServerConfig {
+            max_dimension: Some(1024),
+            ..Default::default()
+        });
+        let req = json_request("POST", "/collections", serde_json::json!({"name": "c"}));
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+
+        let req = json_request(
+            "POST",
+            "/vectors",
+            serde_json::json!({"id": "ok", "vector": vec![0.5f32; 1024]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+
+        let huge = vec![0.5f32; 100_000];
+        for (uri, body) in [
+            ("/vectors", serde_json::json!({"id": "huge", "vector": huge})),
+            ("/search", serde_json::json!({"vector": huge})),
+            ("/collections/c/vectors", serde_json::json!({"id": "huge", "vector": huge})),
+        ] {
+            let resp = app.clone().oneshot(json_request("POST", uri, body)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body = body_to_json(resp.into_body()).await;
+            assert!(body["error"].as_str().unwrap().contains("exceeds the maximum of 1024"));
+        }
+        assert_eq!(state.store.read().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reserved_metadata_key_rejected() {
+        let (app, state) = test_app();
+        let reserved = serde_json::json!({"_expires_at": "0"});
+        let insert = serde_json::json!({"id": "a", "vector": [1.0], "metadata": reserved});
+        let resp = app.clone().oneshot(json_request("POST", "/vectors", insert)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let batch = serde_json::json!({"vectors": [
+            {"id": "b", "vector": [1.0]},
+            {"id": "c", "vector": [2.0], "metadata": reserved},
+        ], "continue_on_error": true});
+        let resp = app.clone().oneshot(json_request("POST", "/vectors/batch", batch)).await;
+        let body = body_to_json(resp.unwrap().into_body()).await;
+        assert_eq!(body["inserted"], 1);
+        assert_eq!(body["errors"][0]["id"], "c");
+
+        let patch = serde_json::json!({"metadata": reserved});
+        let resp = app.oneshot(json_request("PATCH", "/vectors/b", patch)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let store = state.store.read().unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(store.get_metadata("b").unwrap().expires_at().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_insert_with_ttl() {
         let (app, state) = test_app();
+        for (id, ttl) in [("gone", Some(0)), ("kept", Some(3600)), ("plain", None)] {
+            let req = json_request(
+                "POST",
+                "/vectors",
+                serde_json::json!({"id": id, "vector": [1.0, 0.0], "ttl_seconds": ttl}),
+            );
+            assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        }
 
+        let req = json_request(
+            "POST",
+            "/search",
+            serde_json::json!({"vector": [1.0, 0.0], "k": 10}),
+        );
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        let mut ids: Vec<&str> = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["kept", "plain"]);
+
+        let mut store = state.store.write().unwrap();
+        assert!(store.get_metadata("kept").unwrap().expires_at().is_some());
+        assert_eq!(store.expire_now().unwrap(), 1);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rename_vector() {
+        let (app, state) = test_app();
         {
             let mut store = state.store.write().unwrap();
+            let mut meta = Metadata::new();
+            meta.insert("color".to_string(), "red".to_string());
             store
-                .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
+                .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0]), meta)
                 .unwrap();
+            store.insert("v2", Vector::new(vec![3.0, 4.0])).unwrap();
+        }
+        let rename = |id: &str, new_id: &str| {
+            json_request(
+                "PUT",
+                &format!("/vectors/{}/rename", id),
+                serde_json::json!({"new_id": new_id}),
+            )
+        };
+
+        let resp = app.clone().oneshot(rename("v1", "renamed")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_to_json(resp.into_body()).await;
+        assert_eq!(body["id"], "renamed");
+        assert_eq!(body["previous_id"], "v1");
+        {
+            let store = state.store.read().unwrap();
+            assert!(store.get("v1").is_none());
+            assert_eq!(store.get("renamed").unwrap().as_slice(), &[1.0, 2.0]);
+            assert_eq!(store.get_metadata("renamed").unwrap().get("color").unwrap(), "red");
         }
 
+        let resp = app.clone().oneshot(rename("v1", "other")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let resp = app.oneshot(rename("renamed", "v2")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let store = state.store.read().unwrap();
+        assert_eq!(store.get("v2").unwrap().as_slice(), &[3.0, 4.0]);
+        assert!(store.get("renamed").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_patch_metadata_missing_vector() {
+        let (app, state) = test_app();
+        let req = json_request(
+            "PATCH",
+            "/vectors/nope",
+            serde_json::json!({"metadata": {"color": "blue"}}),
+        );
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(state.metrics.read().unwrap().total_updates(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_store_survives_restart() {
+        use crate::persistence::engine::{EngineConfig, StorageEngine};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let open = || {
+            let engine = StorageEngine::open(dir.path(), EngineConfig::default()).unwrap();
+            let state = Arc::new(AppState::persistent(engine, ServerConfig::default()));
+            create_router(state)
+        };
+
+        let app = open();
+        let req = json_request(
+            "POST",
+            "/vectors",
+            serde_json::json!({"id": "v1", "vector": [1.0, 2.0], "metadata": {"k": "a"}}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
+        let req = json_request(
+            "POST",
+            "/vectors/batch",
+            serde_json::json!({"vectors": [
+                {"id": "v2", "vector": [3.0, 4.0]},
+                {"id": "v3", "vector": [5.0, 6.0]}
+            ]}),
+        );
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::CREATED);
         let req = Request::builder()
-            .method("POST")
-            .uri("/search")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "vector": [1.0, 0.0, 0.0],
-                    "k": 10
-                })
-                .to_string(),
-            ))
+            .method("DELETE")
+            .uri("/vectors/v2")
+            .body(Body::empty())
             .unwrap();
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
+        drop(app);
 
-        let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::OK);
+        // Reopen from the WAL alone, as after a restart without checkpoint
+        let app = open();
+        let req = Request::builder()
+            .uri("/vectors")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_to_json(app.clone().oneshot(req).await.unwrap().into_body()).await;
+        let mut ids: Vec<String> = serde_json::from_value(body).unwrap();
+        ids.sort();
+        assert_eq!(ids, vec!["v1", "v3"]);
 
-        let body = body_to_json(resp.into_body()).await;
-        let results = body.as_array().unwrap();
-        assert_eq!(results.len(), 1);
+        let req = Request::builder()
+            .uri("/vectors/v1")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body["vector"], serde_json::json!([1.0, 2.0]));
+        assert_eq!(body["metadata"]["k"], "a");
     }
 
     #[tokio::test]
-    async fn test_batch_insert_endpoint() {
+    async fn test_stream_insert_reports_partial_success() {
         let (app, state) = test_app();
-
+        let body = concat!(
+            "{\"id\": \"v1\", \"vector\": [1.0, 0.0]}\n",
+            "{\"id\": \"v2\", \"vector\": [0.0, 1.0], \"metadata\": {\"k\": \"b\"}}\n",
+            "\n",
+            "{not json}\n",
+            "{\"id\": \"v3\", \"vector\": [1.0, 2.0, 3.0]}\n",
+            "{\"id\": \"v4\", \"vector\": [2.0, 2.0]}",
+        );
         let req = Request::builder()
             .method("POST")
-            .uri("/vectors/batch")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "vectors": [
-                        {"id": "v1", "vector": [1.0, 0.0, 0.0]},
-                        {"id": "v2", "vector": [0.0, 1.0, 0.0], "metadata": {"color": "blue"}}
-                    ]
-                })
-                .to_string(),
-            ))
+            .uri("/vectors/stream")
+            .header("Content-Type", "application/x-ndjson")
+            .body(Body::from(body))
             .unwrap();
 
         let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::CREATED);
-
+        assert_eq!(resp.status(), StatusCode::OK);
         let body = body_to_json(resp.into_body()).await;
-        assert_eq!(body["inserted"], 2);
+        assert_eq!(body["inserted"], 3);
+        assert_eq!(body["error_count"], 2);
+        assert_eq!(body["errors"][0]["line"], 4);
+        assert_eq!(body["errors"][1]["line"], 5);
+        assert!(body["errors"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Dimension mismatch"));
 
         let store = state.store.read().unwrap();
-        assert_eq!(store.len(), 2);
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.get_metadata("v2").unwrap().get("k"), Some(&"b".to_string()));
+        assert_eq!(state.metrics.read().unwrap().total_inserts(), 3);
     }
 
     #[tokio::test]
-    async fn test_batch_search_endpoint() {
+    async fn test_stream_insert_across_chunks() {
         let (app, state) = test_app();
-
-        {
-            let mut store = state.store.write().unwrap();
-            store
-                .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
-                .unwrap();
-            store
-                .insert("v2", Vector::new(vec![0.0, 1.0, 0.0]))
-                .unwrap();
-        }
-
+        // Split lines mid-record to exercise buffering between chunks
+        let chunks: Vec<Result<&'static str, std::io::Error>> = vec![
+            Ok("{\"id\": \"a\", \"vec"),
+            Ok("tor\": [1.0]}\n{\"id\": \"b\","),
+            Ok(" \"vector\": [2.0]}\n"),
+        ];
+        let body = Body::from_stream(futures_util::stream::iter(chunks));
         let req = Request::builder()
             .method("POST")
-            .uri("/search/batch")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "queries": [
-                        {"vector": [1.0, 0.0, 0.0], "k": 1},
-                        {"vector": [0.0, 1.0, 0.0], "k": 1}
-                    ]
-                })
-                .to_string(),
-            ))
+            .uri("/vectors/stream")
+            .body(body)
             .unwrap();
 
-        let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::OK);
-
-        let body = body_to_json(resp.into_body()).await;
-        let results = body.as_array().unwrap();
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0][0]["id"], "v1");
-        assert_eq!(results[1][0]["id"], "v2");
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body["inserted"], 2);
+        assert_eq!(body["error_count"], 0);
+        assert_eq!(state.store.read().unwrap().len(), 2);
     }
 
     #[tokio::test]
-    async fn test_batch_search_with_filter_endpoint() {
+    async fn test_metrics_reset_endpoint() {
         let (app, state) = test_app();
-
         {
-            let mut store = state.store.write().unwrap();
-            let mut m1 = Metadata::new();
-            m1.insert("color".to_string(), "red".to_string());
-            store
-                .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0, 0.0]), m1)
-                .unwrap();
-
-            let mut m2 = Metadata::new();
-            m2.insert("color".to_string(), "blue".to_string());
-            store
-                .insert_with_metadata("v2", Vector::new(vec![0.0, 1.0, 0.0]), m2)
-                .unwrap();
+            let mut metrics = state.metrics.write().unwrap();
+            metrics.record_query(std::time::Duration::from_millis(50));
+            metrics.record_insert();
         }
 
         let req = Request::builder()
             .method("POST")
-            .uri("/search/batch")
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({
-                    "queries": [
-                        {"vector": [1.0, 0.0, 0.0], "k": 10},
-                        {"vector": [0.0, 1.0, 0.0], "k": 10}
-                    ],
-                    "filter": {"op": "eq", "field": "color", "value": "red"}
-                })
-                .to_string(),
-            ))
+            .uri("/metrics/reset")
+            .body(Body::empty())
             .unwrap();
+        assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
 
-        let resp = app.oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::OK);
-
-        let body = body_to_json(resp.into_body()).await;
-        let results = body.as_array().unwrap();
-        assert_eq!(results.len(), 2);
-        // Both queries should only return v1 (the red one)
-        assert_eq!(results[0].as_array().unwrap().len(), 1);
-        assert_eq!(results[0][0]["id"], "v1");
-        assert_eq!(results[1].as_array().unwrap().len(), 1);
-        assert_eq!(results[1][0]["id"], "v1");
+        let req = Request::builder()
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_to_json(app.oneshot(req).await.unwrap().into_body()).await;
+        assert_eq!(body["total_queries"], 0);
+        assert_eq!(body["total_inserts"], 0);
+        assert_eq!(body["p99_query_latency_us"], 0.0);
     }
 }
@@ -0,0 +1,161 @@
+//! The server's default store: in memory, or backed by a `StorageEngine`.
+
+use std::ops::Deref;
+
+use crate::error::Result;
+use crate::index::Index;
+use crate::persistence::engine::StorageEngine;
+use crate::storage::{BatchInsertItem, Metadata, VectorStore};
+use crate::vector::Vector;
+
+/// Store behind the unprefixed routes.
+///
+/// Reads go straight to the `VectorStore` via `Deref`; writes go through the
+/// methods below so that in persistent mode they are WAL-logged first.
+pub enum ServerStore<I: Index> {
+    Memory(VectorStore<I>),
+    Persistent(StorageEngine<I>),
+}
+
+impl<I: Index + 'static> Deref for ServerStore<I> {
+    type Target = VectorStore<I>;
+
+    fn deref(&self) -> &VectorStore<I> {
+        match self {
+            ServerStore::Memory(store) => store,
+            ServerStore::Persistent(engine) => engine.store(),
+        }
+    }
+}
+
+/// Write operations shared by `VectorStore` and `ServerStore`, so route
+/// handlers can serve collections and the default store alike.
+pub trait StoreWrite {
+    fn insert_with_metadata(
+        &mut self,
+        id: impl Into<String>,
+        vector: Vector,
+        metadata: Metadata,
+    ) -> Result<()>;
+    fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()>;
+    /// Insert items one at a time, returning a result for each instead of
+    /// stopping at the first error.
+    fn insert_batch_lenient(&mut self, items: Vec<BatchInsertItem>) -> Vec<Result<()>> {
+        items
+            .into_iter()
+            .map(|item| self.insert_with_metadata(item.id, item.vector, item.metadata))
+            .collect()
+    }
+    fn delete(&mut self, id: &str) -> Result<Vector>;
+    fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()>;
+    fn rename(&mut self, old_id: &str, new_id: &str) -> Result<()>;
+}
+
+impl<I: Index> StoreWrite for VectorStore<I> {
+    fn insert_with_metadata(
+        &mut self,
+        id: impl Into<String>,
+        vector: Vector,
+        metadata: Metadata,
+    ) -> Result<()> {
+        VectorStore::insert_with_metadata(self, id, vector, metadata)
+    }
+
+    fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
+        VectorStore::insert_batch(self, items)
+    }
+
+    fn insert_batch_lenient(&mut self, items: Vec<BatchInsertItem>) -> Vec<Result<()>> {
+        VectorStore::insert_batch_lenient(self, items)
+    }
+
+    fn delete(&mut self, id: &str) -> Result<Vector> {
+        VectorStore::delete(self, id)
+    }
+
+    fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
+        VectorStore::update_metadata(self, id, metadata)
+    }
+
+    fn rename(&mut self, old_id: &str, new_id: &str) -> Result<()> {
+        VectorStore::rename(self, old_id, new_id)
+    }
+}
+
+impl<I: Index + 'static> StoreWrite for ServerStore<I> {
+    fn insert_with_metadata(
+        &mut self,
+        id: impl Into<String>,
+        vector: Vector,
+        metadata: Metadata,
+    ) -> Result<()> {
+        match self {
+            ServerStore::Memory(store) => store.insert_with_metadata(id, vector, metadata),
+            ServerStore::Persistent(engine) => engine.insert_with_metadata(id, vector, metadata),
+        }
+    }
+
+    fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
+        match self {
+            ServerStore::Memory(store) => store.insert_batch(items),
+            ServerStore::Persistent(engine) => engine.insert_batch(items),
+        }
+    }
+
+    fn delete(&mut self, id: &str) -> Result<Vector> {
+        match self {
+            ServerStore::Memory(store) => store.delete(id),
+            ServerStore::Persistent(engine) => engine.delete(id),
+        }
+    }
+
+    fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
+        match self {
+            ServerStore::Memory(store) => store.update_metadata(id, metadata),
+            ServerStore::Persistent(engine) => engine.update_metadata(id, metadata),
+        }
+    }
+
+    fn rename(&mut self, old_id: &str, new_id: &str) -> Result<()> {
+        match self {
+            ServerStore::Memory(store) => store.rename(old_id, new_id),
+            ServerStore::Persistent(engine) => engine.rename(old_id, new_id),
+        }
+    }
+}
+
+impl<I: Index + 'static> ServerStore<I> {
+    /// Insert a vector without metadata.
+    pub fn insert(&mut self, id: impl Into<String>, vector: Vector) -> Result<()> {
+        StoreWrite::insert_with_metadata(self, id, vector, Metadata::new())
+    }
+
+    /// Snapshot and truncate the WAL; a no-op for an in-memory store.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        match self {
+            ServerStore::Memory(_) => Ok(()),
+            ServerStore::Persistent(engine) => engine.checkpoint(),
+        }
+    }
+
+    /// Delete expired vectors, returning how many were removed.
+    pub fn expire_now(&mut self) -> Result<usize> {
+        match self {
+            ServerStore::Memory(store) => Ok(store.expire_now()),
+            ServerStore::Persistent(engine) => engine.expire_now(),
+        }
+    }
+
+    /// Set or clear the dimension limit for inserts and queries.
+    pub fn set_max_dimension(&mut self, max: Option<usize>) {
+        match self {
+            ServerStore::Memory(store) => store.set_max_dimension(max),
+            ServerStore::Persistent(engine) => engine.set_max_dimension(max),
+        }
+    }
+
+    /// Whether writes are persisted to disk.
+    pub fn is_persistent(&self) -> bool {
+        matches!(self, ServerStore::Persistent(_))
+    }
+}
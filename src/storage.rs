@@ -3,10 +3,15 @@
 use crate::distance::DistanceMetric;
 use crate::error::{Result, VectorDbError};
 use crate::flat_index::FlatIndex;
-use crate::index::Index;
+use crate::hnsw::{HnswIndex, HnswParams};
+use crate::index::{Index, DEFAULT_OVER_FETCH};
+use crate::persistence::serialization::DatabaseSnapshot;
 use crate::vector::Vector;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// A search result containing the vector ID and distance
 #[derive(Debug, Clone)]
@@ -15,6 +20,62 @@ pub struct SearchResult {
     pub distance: f32,
 }
 
+/// One window of a ranked search, from `VectorStore::search_page`.
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Offset of the following page, or None once a page comes up short. A
+    /// full page that happens to end the results still gets one; the page
+    /// after it is empty.
+    pub next_offset: Option<usize>,
+}
+
+impl SearchPage {
+    /// The `offset..offset + limit` window of `ranked`, a ranking fetched for
+    /// `offset + limit` results by any search method.
+    pub fn from_ranking(mut ranked: Vec<SearchResult>, offset: usize, limit: usize) -> Self {
+        let fetch = offset.saturating_add(limit);
+        let next_offset = (limit > 0 && ranked.len() == fetch).then_some(fetch);
+        Self {
+            results: ranked.drain(offset.min(ranked.len())..).collect(),
+            next_offset,
+        }
+    }
+}
+
+/// What the `distance` field of rescored search results holds (see
+/// `VectorStore::rescore`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreKind {
+    /// The metric's distance: lower is closer.
+    #[default]
+    Distance,
+    /// A similarity, higher is closer: cosine similarity (`1 - distance`),
+    /// the raw dot product, `1 / (1 + distance)` for Euclidean, Hamming and
+    /// Canberra, or `1 - distance / ln 2` for Jensen–Shannon.
+    Similarity,
+}
+
+impl ScoreKind {
+    /// Convert a distance computed under `metric` to this kind of score.
+    pub fn score(self, metric: DistanceMetric, distance: f32) -> f32 {
+        match (self, metric) {
+            (ScoreKind::Distance, _) => distance,
+            (
+                ScoreKind::Similarity,
+                DistanceMetric::Euclidean | DistanceMetric::Hamming | DistanceMetric::Canberra,
+            ) => 1.0 / (1.0 + distance),
+            (ScoreKind::Similarity, DistanceMetric::Cosine) => 1.0 - distance,
+            (ScoreKind::Similarity, DistanceMetric::DotProduct) => -distance,
+            (ScoreKind::Similarity, DistanceMetric::InnerProduct) => distance,
+            (ScoreKind::Similarity, DistanceMetric::JensenShannon) => {
+                1.0 - distance / std::f32::consts::LN_2
+            }
+        }
+    }
+}
+
 /// Metadata associated with a vector
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Metadata {
@@ -39,6 +100,48 @@ impl Metadata {
     pub fn fields(&self) -> &HashMap<String, String> {
         &self.fields
     }
+
+    /// Expiry time in unix seconds, if one was set.
+    pub fn expires_at(&self) -> Option<u64> {
+        self.get(EXPIRES_AT_KEY)?.parse().ok()
+    }
+
+    /// Mark the vector to expire at `unix_secs`.
+    pub fn set_expires_at(&mut self, unix_secs: u64) {
+        self.insert(EXPIRES_AT_KEY.to_string(), unix_secs.to_string());
+    }
+}
+
+/// Metadata key holding a vector's expiry time (unix seconds). Keeping it in
+/// the metadata means it is persisted and exported along with everything else.
+pub const EXPIRES_AT_KEY: &str = "_expires_at";
+
+/// Current unix time in seconds; the default clock for TTL checks.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Current unix time in milliseconds; the default clock for `Timestamps`.
+pub fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// When a vector was first inserted and last changed, in unix milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timestamps {
+    pub created_at: u64,
+    /// Bumped by re-inserting the ID and by metadata updates.
+    pub updated_at: u64,
+}
+
+impl From<HashMap<String, String>> for Metadata {
+    fn from(fields: HashMap<String, String>) -> Self {
+        Self { fields }
+    }
 }
 
 /// A filter for metadata-based search narrowing.
@@ -70,6 +173,44 @@ impl MetadataFilter {
     }
 }
 
+/// How `VectorStore::search_fused_with` combines several weighted queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FusionMode {
+    /// Search once with the weighted average of the query vectors.
+    /// Cheap (a single index search) and reported distances are true distances
+    /// to the fused query.
+    #[default]
+    Centroid,
+    /// Search each query separately and merge the ranked lists with weighted
+    /// reciprocal rank fusion: `score(id) = sum_i w_i / (RRF_K + rank_i(id))`.
+    /// Costs one search per query; the reported `distance` is the negated fused
+    /// score so that results stay sorted ascending.
+    Rank,
+}
+
+/// Damping constant for reciprocal rank fusion (the value from Cormack et al., 2009).
+const RRF_K: f32 = 60.0;
+
+/// Metadata field that `VectorStore::hybrid_search` matches keywords against.
+pub const HYBRID_TEXT_FIELD: &str = "text";
+
+/// Lowercased alphanumeric words of `text`.
+fn keyword_terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Result of `VectorStore::insert_dedup`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// No stored vector was within the radius, so the vector was inserted.
+    Inserted,
+    /// The vector was not inserted because this stored vector is within the radius.
+    Duplicate(String),
+}
+
 /// An item for batch insertion.
 #[derive(Debug, Clone)]
 pub struct BatchInsertItem {
@@ -78,6 +219,79 @@ pub struct BatchInsertItem {
     pub metadata: Metadata,
 }
 
+/// One line of a JSON Lines dump.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonlRecord {
+    id: String,
+    vector: Vec<f32>,
+    #[serde(default)]
+    metadata: HashMap<String, String>,
+}
+
+/// Parse a JSON Lines dump into batch items. Blank lines are skipped.
+pub(crate) fn read_jsonl<R: Read>(reader: R) -> Result<Vec<BatchInsertItem>> {
+    let mut items = Vec::new();
+    for (line_no, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: JsonlRecord = serde_json::from_str(&line).map_err(|e| {
+            VectorDbError::SerializationError(format!("line {}: {}", line_no + 1, e))
+        })?;
+        items.push(BatchInsertItem {
+            id: record.id,
+            vector: Vector::new(record.vector),
+            metadata: Metadata::from(record.metadata),
+        });
+    }
+    Ok(items)
+}
+
+/// Expiry times by internal ID, also ordered by time so checking for and
+/// collecting due vectors doesn't walk every expiry.
+#[derive(Debug, Default)]
+struct ExpiryIndex {
+    by_id: HashMap<usize, u64>,
+    by_time: BTreeSet<(u64, usize)>,
+}
+
+impl ExpiryIndex {
+    fn set(&mut self, internal_id: usize, expires_at: Option<u64>) {
+        self.remove(internal_id);
+        if let Some(t) = expires_at {
+            self.by_id.insert(internal_id, t);
+            self.by_time.insert((t, internal_id));
+        }
+    }
+
+    fn remove(&mut self, internal_id: usize) {
+        if let Some(t) = self.by_id.remove(&internal_id) {
+            self.by_time.remove(&(t, internal_id));
+        }
+    }
+
+    fn is_due(&self, internal_id: usize, now: u64) -> bool {
+        self.by_id.get(&internal_id).is_some_and(|&t| t <= now)
+    }
+
+    fn any_due(&self, now: u64) -> bool {
+        self.by_time.first().is_some_and(|&(t, _)| t <= now)
+    }
+
+    /// Internal IDs whose expiry is at or before `now`, earliest first.
+    fn due(&self, now: u64) -> impl Iterator<Item = usize> + '_ {
+        self.by_time.range(..=(now, usize::MAX)).map(|&(_, id)| id)
+    }
+
+    fn remap(&mut self, new_id: impl Fn(usize) -> usize) {
+        let by_id: HashMap<usize, u64> =
+            self.by_id.drain().map(|(internal_id, t)| (new_id(internal_id), t)).collect();
+        self.by_time = by_id.iter().map(|(&internal_id, &t)| (t, internal_id)).collect();
+        self.by_id = by_id;
+    }
+}
+
 /// In-memory vector storage with a pluggable search index.
 #[derive(Debug)]
 pub struct VectorStore<I: Index> {
@@ -92,6 +306,16 @@ pub struct VectorStore<I: Index> {
     next_id: usize,
     /// Enforced vector dimension
     dimension: Option<usize>,
+    /// Largest dimension accepted for inserts and queries
+    max_dimension: Option<usize>,
+    /// Expiry times by internal ID, mirrored from metadata for cheap checks
+    expires_at: ExpiryIndex,
+    /// Source of the current unix time for TTL checks
+    clock: fn() -> u64,
+    /// Creation and last-update times by internal ID
+    timestamps: HashMap<usize, Timestamps>,
+    /// Source of the current unix time in milliseconds for `timestamps`
+    millis_clock: fn() -> u64,
 }
 
 impl VectorStore<FlatIndex> {
@@ -109,8 +333,29 @@ impl VectorStore<FlatIndex> {
             metadata: HashMap::new(),
             next_id: 0,
             dimension: None,
+            max_dimension: None,
+            expires_at: ExpiryIndex::default(),
+            clock: unix_now,
+            timestamps: HashMap::new(),
+            millis_clock: unix_now_millis,
         }
     }
+
+    /// Promote to an HNSW index built with `params`, keeping IDs and
+    /// metadata (see `rebuild_index`).
+    pub fn to_hnsw(self, params: HnswParams) -> Result<VectorStore<HnswIndex>> {
+        let metric = self.metric();
+        let index = HnswIndex::with_params(metric, params)?;
+        self.rebuild_index(|_| index)
+    }
+}
+
+impl VectorStore<HnswIndex> {
+    /// Move back to exact brute-force search, keeping IDs and metadata (see
+    /// `rebuild_index`).
+    pub fn to_flat(self) -> Result<VectorStore<FlatIndex>> {
+        self.rebuild_index(FlatIndex::new)
+    }
 }
 
 impl<I: Index> VectorStore<I> {
@@ -123,6 +368,139 @@ impl<I: Index> VectorStore<I> {
             metadata: HashMap::new(),
             next_id: 0,
             dimension: None,
+            max_dimension: None,
+            expires_at: ExpiryIndex::default(),
+            clock: unix_now,
+            timestamps: HashMap::new(),
+            millis_clock: unix_now_millis,
+        }
+    }
+
+    /// Rebuild a store from a snapshot into the index `index_builder` makes for
+    /// `metric`, so any index type can be loaded from the same snapshot.
+    ///
+    /// Internal IDs, metadata (including expiry), timestamps and the
+    /// dimension are kept as recorded; vectors are added to the index in
+    /// internal ID order. Entries with empty data are deleted slots and are
+    /// skipped.
+    pub fn from_snapshot(
+        snapshot: &DatabaseSnapshot,
+        metric: DistanceMetric,
+        index_builder: impl FnOnce(DistanceMetric) -> I,
+    ) -> Result<Self> {
+        Self::restore(snapshot, index_builder(metric), true)
+    }
+
+    /// `from_snapshot` around an index that already holds the snapshot's
+    /// vectors under their internal IDs (e.g. an HNSW graph saved with it), so
+    /// nothing is added to it. The caller is responsible for the index
+    /// actually matching the snapshot.
+    pub fn from_snapshot_prebuilt(snapshot: &DatabaseSnapshot, index: I) -> Result<Self> {
+        Self::restore(snapshot, index, false)
+    }
+
+    fn restore(snapshot: &DatabaseSnapshot, index: I, add_vectors: bool) -> Result<Self> {
+        let mut store = Self::with_index(index);
+        store.dimension = snapshot.dimension;
+
+        let mut vectors: Vec<_> =
+            snapshot.vectors.iter().filter(|sv| !sv.data.is_empty()).collect();
+        vectors.sort_by_key(|sv| sv.internal_id);
+        for sv in vectors {
+            store.check_insert_dimension(sv.data.len())?;
+            if store.internal_to_id.contains_key(&sv.internal_id)
+                || store.id_to_internal.contains_key(&sv.string_id)
+            {
+                return Err(VectorDbError::SerializationError(format!(
+                    "snapshot lists '{}' (internal ID {}) more than once",
+                    sv.string_id, sv.internal_id
+                )));
+            }
+
+            if add_vectors {
+                store.index.add(sv.internal_id, Vector::new(sv.data.clone()))?;
+            }
+            store.id_to_internal.insert(sv.string_id.clone(), sv.internal_id);
+            store.internal_to_id.insert(sv.internal_id, sv.string_id.clone());
+            let metadata = snapshot
+                .metadata
+                .get(&sv.internal_id)
+                .cloned()
+                .map(Metadata::from)
+                .unwrap_or_default();
+            store.set_metadata(sv.internal_id, metadata);
+            if let Some(&ts) = snapshot.timestamps.get(&sv.internal_id) {
+                store.timestamps.insert(sv.internal_id, ts);
+            }
+            store.next_id = store.next_id.max(sv.internal_id + 1);
+        }
+        store.next_id = store.next_id.max(snapshot.next_id);
+        Ok(store)
+    }
+
+    /// Reject inserts and queries with more than `max` components.
+    pub fn with_max_dimension(mut self, max: usize) -> Self {
+        self.max_dimension = Some(max);
+        self
+    }
+
+    /// Set or clear the dimension limit (see `with_max_dimension`).
+    pub fn set_max_dimension(&mut self, max: Option<usize>) {
+        self.max_dimension = max;
+    }
+
+    /// The dimension limit, if any.
+    pub fn max_dimension(&self) -> Option<usize> {
+        self.max_dimension
+    }
+
+    /// Fail with `InvalidVector` if `dim` exceeds the dimension limit.
+    pub(crate) fn check_max_dimension(&self, dim: usize) -> Result<()> {
+        match self.max_dimension {
+            Some(max) if dim > max => Err(VectorDbError::InvalidVector {
+                reason: format!("dimension {} exceeds the maximum of {}", dim, max),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Use `clock` instead of the system time when checking expiry.
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Use `clock` (unix milliseconds) instead of the system time when
+    /// stamping inserts and updates.
+    pub fn with_millis_clock(mut self, clock: fn() -> u64) -> Self {
+        self.millis_clock = clock;
+        self
+    }
+
+    /// When the vector `id` was inserted and last updated. `None` for unknown
+    /// IDs and for vectors loaded from data written before timestamps were
+    /// recorded.
+    pub fn get_timestamps(&self, id: &str) -> Option<Timestamps> {
+        let internal_id = self.id_to_internal.get(id)?;
+        self.timestamps.get(internal_id).copied()
+    }
+
+    /// The timestamps an insert or update of `id` made now would get: a new
+    /// ID is created now, an existing one keeps its creation time.
+    pub(crate) fn next_timestamps(&self, id: &str) -> Timestamps {
+        let now = (self.millis_clock)();
+        let created_at = self.get_timestamps(id).map_or(now, |ts| ts.created_at);
+        Timestamps {
+            created_at,
+            updated_at: now,
+        }
+    }
+
+    /// Overwrite the timestamps of `id`, e.g. with ones replayed from the WAL.
+    /// Does nothing for unknown IDs.
+    pub(crate) fn set_timestamps(&mut self, id: &str, timestamps: Timestamps) {
+        if let Some(&internal_id) = self.id_to_internal.get(id) {
+            self.timestamps.insert(internal_id, timestamps);
         }
     }
 
@@ -139,24 +517,13 @@ impl<I: Index> VectorStore<I> {
         metadata: Metadata,
     ) -> Result<()> {
         let id = id.into();
-        let dim = vector.dimension();
-
-        // Check dimension consistency
-        if let Some(expected_dim) = self.dimension {
-            if dim != expected_dim {
-                return Err(VectorDbError::DimensionMismatch {
-                    expected: expected_dim,
-                    actual: dim,
-                });
-            }
-        } else {
-            self.dimension = Some(dim);
-        }
+        self.check_insert(&vector)?;
+        let timestamps = self.next_timestamps(&id);
 
         // If this string ID already exists, remove the old entry first
         if let Some(&old_internal) = self.id_to_internal.get(&id) {
             self.index.remove(old_internal)?;
-            self.metadata.remove(&old_internal);
+            self.remove_metadata(old_internal);
             self.internal_to_id.remove(&old_internal);
         }
 
@@ -166,8 +533,67 @@ impl<I: Index> VectorStore<I> {
         self.index.add(internal_id, vector)?;
         self.id_to_internal.insert(id.clone(), internal_id);
         self.internal_to_id.insert(internal_id, id);
+        self.set_metadata(internal_id, metadata);
+        self.timestamps.insert(internal_id, timestamps);
+
+        Ok(())
+    }
+
+    /// Insert unless an existing vector lies within `radius` (inclusive, in
+    /// the store's metric) of `vector`, in which case nothing is inserted and
+    /// the nearest such ID is returned. Under `InnerProduct`, where higher is
+    /// closer, `radius` is a minimum score instead.
+    ///
+    /// Only the nearest neighbor is checked, so with an approximate index a
+    /// duplicate the index fails to surface can slip through.
+    pub fn insert_dedup(
+        &mut self,
+        id: impl Into<String>,
+        vector: Vector,
+        metadata: Metadata,
+        radius: f32,
+    ) -> Result<InsertOutcome> {
+        if let Some(nearest) = self.search(&vector, 1)?.into_iter().next() {
+            if !self.metric().is_better_than(radius, nearest.distance) {
+                return Ok(InsertOutcome::Duplicate(nearest.id));
+            }
+        }
+        self.insert_with_metadata(id, vector, metadata)?;
+        Ok(InsertOutcome::Inserted)
+    }
+
+    fn set_metadata(&mut self, internal_id: usize, metadata: Metadata) {
+        self.expires_at.set(internal_id, metadata.expires_at());
         self.metadata.insert(internal_id, metadata);
+    }
+
+    /// Drop everything kept alongside the vector at `internal_id`.
+    fn remove_metadata(&mut self, internal_id: usize) {
+        self.expires_at.remove(internal_id);
+        self.metadata.remove(&internal_id);
+        self.timestamps.remove(&internal_id);
+    }
+
+    /// Check an incoming vector against the metric (see
+    /// `DistanceMetric::validate`) and the store dimension.
+    fn check_insert(&mut self, vector: &Vector) -> Result<()> {
+        self.metric().validate(vector)?;
+        self.check_insert_dimension(vector.dimension())
+    }
 
+    /// Check an incoming vector's dimension, fixing the store dimension on first insert.
+    fn check_insert_dimension(&mut self, dim: usize) -> Result<()> {
+        self.check_max_dimension(dim)?;
+        if let Some(expected_dim) = self.dimension {
+            if dim != expected_dim {
+                return Err(VectorDbError::DimensionMismatch {
+                    expected: expected_dim,
+                    actual: dim,
+                });
+            }
+        } else {
+            self.dimension = Some(dim);
+        }
         Ok(())
     }
 
@@ -180,23 +606,101 @@ impl<I: Index> VectorStore<I> {
 
         let vector = self
             .index
-            .get_vector(internal_id)
-            .cloned()
+            .reconstruct_vector(internal_id)
             .unwrap_or_else(|| Vector::new(vec![]));
 
         self.internal_to_id.remove(&internal_id);
-        self.metadata.remove(&internal_id);
+        self.remove_metadata(internal_id);
         self.index.remove(internal_id)?;
 
         Ok(vector)
     }
 
+    /// Merge `metadata` into a vector's existing metadata; keys already
+    /// present are overwritten, others are left as they were.
+    pub fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
+        let internal_id = *self
+            .id_to_internal
+            .get(id)
+            .ok_or_else(|| VectorDbError::VectorNotFound { id: id.to_string() })?;
+
+        let mut merged = self.metadata.remove(&internal_id).unwrap_or_default();
+        for (key, value) in metadata.fields {
+            merged.insert(key, value);
+        }
+        self.set_metadata(internal_id, merged);
+        let timestamps = self.next_timestamps(id);
+        self.timestamps.insert(internal_id, timestamps);
+        Ok(())
+    }
+
+    /// Change a vector's ID. The index entry, metadata and expiry stay where
+    /// they are; only the ID mapping changes.
+    ///
+    /// Fails with `VectorNotFound` if `old_id` isn't stored, and with
+    /// `VectorExists` if `new_id` already is: renaming never replaces another
+    /// vector. Renaming an ID to itself is a no-op.
+    pub fn rename(&mut self, old_id: &str, new_id: &str) -> Result<()> {
+        let internal_id = self.check_rename(old_id, new_id)?;
+        if old_id != new_id {
+            self.id_to_internal.remove(old_id);
+            self.id_to_internal.insert(new_id.to_string(), internal_id);
+            self.internal_to_id.insert(internal_id, new_id.to_string());
+        }
+        Ok(())
+    }
+
+    /// The internal ID behind `old_id`, if `rename(old_id, new_id)` would succeed.
+    pub(crate) fn check_rename(&self, old_id: &str, new_id: &str) -> Result<usize> {
+        let internal_id = *self
+            .id_to_internal
+            .get(old_id)
+            .ok_or_else(|| VectorDbError::VectorNotFound { id: old_id.to_string() })?;
+        if old_id != new_id && self.id_to_internal.contains_key(new_id) {
+            return Err(VectorDbError::VectorExists { id: new_id.to_string() });
+        }
+        Ok(internal_id)
+    }
+
     /// Get a vector by ID.
+    ///
+    /// Always `None` on indexes that don't hold full-precision vectors in
+    /// memory (quantized, binary, mmap); use `reconstruct` there.
     pub fn get(&self, id: &str) -> Option<&Vector> {
         let &internal_id = self.id_to_internal.get(id)?;
         self.index.get_vector(internal_id)
     }
 
+    /// An owned copy of a vector by ID, decoded by the index if it stores
+    /// vectors in another form (see `Index::reconstruct_vector`).
+    pub fn reconstruct(&self, id: &str) -> Option<Vector> {
+        let &internal_id = self.id_to_internal.get(id)?;
+        self.index.reconstruct_vector(internal_id)
+    }
+
+    /// Look up several vectors at once, in the order of `ids`; IDs that aren't
+    /// stored come back with `None`.
+    pub fn get_many(&self, ids: &[String]) -> Vec<(String, Option<Vector>)> {
+        ids.iter()
+            .map(|id| (id.clone(), self.reconstruct(id)))
+            .collect()
+    }
+
+    /// Element-wise mean of the vectors with the given IDs.
+    ///
+    /// Fails with `InvalidVector` if `ids` is empty and `VectorNotFound` for
+    /// the first ID that isn't stored.
+    pub fn centroid(&self, ids: &[String]) -> Result<Vector> {
+        let vectors = ids
+            .iter()
+            .map(|id| {
+                self.reconstruct(id)
+                    .ok_or_else(|| VectorDbError::VectorNotFound { id: id.clone() })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Vector::mean_of(&vectors)
+    }
+
     /// Get metadata for a vector by ID.
     pub fn get_metadata(&self, id: &str) -> Option<&Metadata> {
         let &internal_id = self.id_to_internal.get(id)?;
@@ -219,7 +723,28 @@ impl<I: Index> VectorStore<I> {
             return Ok(vec![]);
         }
 
-        // Check dimension
+        self.check_query(query)?;
+        self.search_unchecked(query, k)
+    }
+
+    /// Results `offset..offset + limit` of the ranking `search` would give,
+    /// for paging through results without slicing them by hand.
+    ///
+    /// Each page still searches for `offset + limit` neighbors, so deep pages
+    /// cost as much as one big search. Pages are taken from independent
+    /// searches: writes between two calls can shift results across the
+    /// boundary, and an approximate index may rank a little differently for a
+    /// larger `k`.
+    pub fn search_page(&self, query: &Vector, offset: usize, limit: usize) -> Result<SearchPage> {
+        let ranked = self.search(query, offset.saturating_add(limit))?;
+        Ok(SearchPage::from_ranking(ranked, offset, limit))
+    }
+
+    /// Reject a query over the dimension limit or whose dimension differs from
+    /// the store's, or a zero query under cosine, which has no direction to
+    /// compare against.
+    fn check_query(&self, query: &Vector) -> Result<()> {
+        self.check_max_dimension(query.dimension())?;
         if let Some(expected_dim) = self.dimension {
             if query.dimension() != expected_dim {
                 return Err(VectorDbError::DimensionMismatch {
@@ -228,10 +753,139 @@ impl<I: Index> VectorStore<I> {
                 });
             }
         }
+        if self.metric() == DistanceMetric::Cosine && query.norm() == 0.0 {
+            return Err(VectorDbError::InvalidVector {
+                reason: "Cosine search needs a non-zero query vector".to_string(),
+            });
+        }
+        self.metric().validate(query)
+    }
+
+    /// Search without re-validating the query; callers must have run
+    /// `check_query` already.
+    fn search_unchecked(&self, query: &Vector, k: usize) -> Result<Vec<SearchResult>> {
+        let index_results = self.search_index(query, k)?;
+        Ok(self.to_search_results(index_results))
+    }
+
+    /// Search the index for internal IDs, skipping expired entries.
+    fn search_index(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+        let now = (self.clock)();
+        if self.any_expired(now) {
+            self.index
+                .search_filtered(query, k, &|id| !self.is_expired(id, now), DEFAULT_OVER_FETCH)
+        } else {
+            self.index.search(query, k)
+        }
+    }
+
+    /// Report results from any search method as `kind` scores. Order is
+    /// unchanged (closest first), so similarities come out descending.
+    pub fn rescore(&self, results: Vec<SearchResult>, kind: ScoreKind) -> Vec<SearchResult> {
+        let metric = self.metric();
+        results
+            .into_iter()
+            .map(|r| SearchResult {
+                distance: kind.score(metric, r.distance),
+                ..r
+            })
+            .collect()
+    }
+
+    /// Fetch `fetch` candidates from the index, recompute each one's distance
+    /// from the stored vector, and return the best `k`.
+    ///
+    /// Over-fetching widens an approximate index's search, so more of the true
+    /// neighbors make it into the candidate set. Distances are recomputed from
+    /// `Index::reconstruct_vector`, so they are exact only where the index can
+    /// return the original vector; candidates it can't return at all keep the
+    /// index's own distance.
+    pub fn search_rerank(
+        &self,
+        query: &Vector,
+        k: usize,
+        fetch: usize,
+    ) -> Result<Vec<SearchResult>> {
+        if self.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.check_query(query)?;
+        let metric = self.metric();
+        let mut candidates = self
+            .search_index(query, fetch.max(k))?
+            .into_iter()
+            .map(|(id, approx)| match self.index.reconstruct_vector(id) {
+                Some(vector) => Ok((id, metric.distance(query, &vector)?)),
+                None => Ok((id, approx)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        candidates.sort_by(|a, b| metric.compare(a.1, b.1));
+        candidates.truncate(k);
+        Ok(self.to_search_results(candidates))
+    }
+
+    /// Search, then nudge items whose metadata matches a boost filter ahead
+    /// of equally close competitors.
+    ///
+    /// Scoring model: each result's ranking distance is its metric distance
+    /// minus the sum of the amounts of every filter in `boosts` it matches
+    /// (under `InnerProduct`, where higher is closer, the amounts are added
+    /// instead). A negative amount demotes. Results come back ordered by the
+    /// adjusted value, which is also what `distance` reports, so a boost is
+    /// in the metric's own units: a boost of 0.1 lets a match overtake
+    /// anything less than 0.1 closer.
+    ///
+    /// Boosts only reorder candidates: `k * DEFAULT_OVER_FETCH` nearest
+    /// neighbors are fetched first, so a match further out than that can't
+    /// be pulled in.
+    pub fn search_with_boost(
+        &self,
+        query: &Vector,
+        k: usize,
+        boosts: &[(MetadataFilter, f32)],
+    ) -> Result<Vec<SearchResult>> {
+        if self.is_empty() {
+            return Ok(vec![]);
+        }
+        self.check_query(query)?;
+
+        let metric = self.metric();
+        let empty = Metadata::new();
+        let mut candidates: Vec<(usize, f32)> = self
+            .search_index(query, k.saturating_mul(DEFAULT_OVER_FETCH))?
+            .into_iter()
+            .map(|(internal_id, distance)| {
+                let metadata = self.metadata.get(&internal_id).unwrap_or(&empty);
+                let boost: f32 = boosts
+                    .iter()
+                    .filter(|(filter, _)| filter.matches(metadata))
+                    .map(|(_, amount)| amount)
+                    .sum();
+                let adjusted = if metric.higher_is_better() {
+                    distance + boost
+                } else {
+                    distance - boost
+                };
+                (internal_id, adjusted)
+            })
+            .collect();
+        candidates.sort_by(|a, b| metric.compare(a.1, b.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(k);
+        Ok(self.to_search_results(candidates))
+    }
+
+    fn is_expired(&self, internal_id: usize, now: u64) -> bool {
+        self.expires_at.is_due(internal_id, now)
+    }
 
-        let index_results = self.index.search(query, k)?;
+    fn any_expired(&self, now: u64) -> bool {
+        self.expires_at.any_due(now)
+    }
 
-        let results = index_results
+    /// Map index `(internal_id, distance)` pairs to string-ID results.
+    fn to_search_results(&self, index_results: Vec<(usize, f32)>) -> Vec<SearchResult> {
+        index_results
             .into_iter()
             .filter_map(|(internal_id, distance)| {
                 self.internal_to_id.get(&internal_id).map(|id| SearchResult {
@@ -239,73 +893,417 @@ impl<I: Index> VectorStore<I> {
                     distance,
                 })
             })
-            .collect();
+            .collect()
+    }
 
-        Ok(results)
+    /// Search for the k nearest neighbors with a per-query search-beam width.
+    ///
+    /// Forwarded to `Index::search_with_ef`: HNSW uses `ef` instead of its
+    /// configured `ef_search`, while flat indexes ignore it. While any entry
+    /// has expired, `DEFAULT_OVER_FETCH` times `k` candidates are fetched with
+    /// `ef` and the expired ones dropped, as `search_filtered` would.
+    pub fn search_with_ef(&self, query: &Vector, k: usize, ef: usize) -> Result<Vec<SearchResult>> {
+        if self.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.check_query(query)?;
+        let now = (self.clock)();
+        if !self.any_expired(now) {
+            let index_results = self.index.search_with_ef(query, k, ef)?;
+            return Ok(self.to_search_results(index_results));
+        }
+        if self.index.prefilters() {
+            // Pre-filtering indexes skip expired IDs exactly and ignore ef anyway
+            return self.search_unchecked(query, k);
+        }
+        let fetch_k = k.saturating_mul(DEFAULT_OVER_FETCH).max(k).min(self.len());
+        let index_results = self
+            .index
+            .search_with_ef(query, fetch_k, ef)?
+            .into_iter()
+            .filter(|&(id, _)| !self.is_expired(id, now))
+            .take(k)
+            .collect();
+        Ok(self.to_search_results(index_results))
     }
 
     /// Search for the k nearest neighbors that match the given metadata filter.
-    /// Uses post-filtering with 3x over-fetch to compensate for filtered-out results.
+    ///
+    /// The filter is passed to `Index::search_filtered` as an ID predicate. Indexes
+    /// that pre-filter (e.g. `FlatIndex`) return exact results; others fall back to
+    /// post-filtering with `DEFAULT_OVER_FETCH` (3x) over-fetch.
     pub fn search_with_filter(
         &self,
         query: &Vector,
         k: usize,
         filter: &MetadataFilter,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_with_filter_over_fetch(query, k, filter, DEFAULT_OVER_FETCH)
+    }
+
+    /// `search_with_filter` with an explicit over-fetch multiplier: indexes
+    /// that post-filter rank `over_fetch * k` candidates before filtering.
+    /// Raise it for selective filters; pre-filtering indexes ignore it.
+    pub fn search_with_filter_over_fetch(
+        &self,
+        query: &Vector,
+        k: usize,
+        filter: &MetadataFilter,
+        over_fetch: usize,
     ) -> Result<Vec<SearchResult>> {
         if self.is_empty() {
             return Ok(vec![]);
         }
 
-        if let Some(expected_dim) = self.dimension {
-            if query.dimension() != expected_dim {
-                return Err(VectorDbError::DimensionMismatch {
-                    expected: expected_dim,
-                    actual: query.dimension(),
-                });
-            }
-        }
+        self.check_query(query)?;
 
-        // Over-fetch 3x to compensate for filtered-out results
-        let fetch_k = (k * 3).max(k).min(self.len());
-        let index_results = self.index.search(query, fetch_k)?;
+        let now = (self.clock)();
+        let predicate = |internal_id: usize| {
+            !self.is_expired(internal_id, now)
+                && self
+                    .metadata
+                    .get(&internal_id)
+                    .is_some_and(|meta| filter.matches(meta))
+        };
+        let index_results = self.index.search_filtered(query, k, &predicate, over_fetch)?;
 
         let results: Vec<SearchResult> = index_results
             .into_iter()
             .filter_map(|(internal_id, distance)| {
-                let string_id = self.internal_to_id.get(&internal_id)?;
-                let meta = self.metadata.get(&internal_id)?;
-                if filter.matches(meta) {
-                    Some(SearchResult {
-                        id: string_id.clone(),
-                        distance,
-                    })
-                } else {
-                    None
-                }
+                self.internal_to_id.get(&internal_id).map(|id| SearchResult {
+                    id: id.clone(),
+                    distance,
+                })
             })
-            .take(k)
             .collect();
 
         Ok(results)
     }
 
-    /// Insert a batch of vectors. Stops at the first error and returns it.
-    pub fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
-        for item in items {
-            self.insert_with_metadata(item.id, item.vector, item.metadata)?;
+    /// Filtered search that keeps widening the candidate pool until it has `k`
+    /// matches or the whole store has been ranked.
+    ///
+    /// Starts at `DEFAULT_OVER_FETCH` and doubles the multiplier after each
+    /// short result. Worst case (fewer than `k` matches in total) is about
+    /// `log2(len / k)` index searches, the last ranking every vector, so it
+    /// costs more than a brute-force scan. Indexes that pre-filter (see
+    /// `Index::prefilters`, e.g. `FlatIndex`) ignore the over-fetch, so they
+    /// get a single pass.
+    pub fn search_with_filter_adaptive(
+        &self,
+        query: &Vector,
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> Result<Vec<SearchResult>> {
+        let mut over_fetch = DEFAULT_OVER_FETCH;
+        loop {
+            let results = self.search_with_filter_over_fetch(query, k, filter, over_fetch)?;
+            let exhausted = k.saturating_mul(over_fetch) >= self.len();
+            if results.len() >= k || exhausted || self.index.prefilters() {
+                return Ok(results);
+            }
+            over_fetch = over_fetch.saturating_mul(2);
         }
-        Ok(())
+    }
+
+    /// Search with several weighted query vectors fused into one result list,
+    /// using the default `FusionMode::Centroid`.
+    pub fn search_fused(&self, queries: &[(Vector, f32)], k: usize) -> Result<Vec<SearchResult>> {
+        self.search_fused_with(queries, k, FusionMode::default())
+    }
+
+    /// Search with several weighted query vectors fused according to `mode`.
+    pub fn search_fused_with(
+        &self,
+        queries: &[(Vector, f32)],
+        k: usize,
+        mode: FusionMode,
+    ) -> Result<Vec<SearchResult>> {
+        if queries.is_empty() {
+            return Err(VectorDbError::InvalidVector {
+                reason: "Fused search requires at least one query".to_string(),
+            });
+        }
+
+        match mode {
+            FusionMode::Centroid => {
+                let dim = queries[0].0.dimension();
+                let mut sum = vec![0.0f32; dim];
+                let mut total_weight = 0.0f32;
+                for (query, weight) in queries {
+                    if query.dimension() != dim {
+                        return Err(VectorDbError::DimensionMismatch {
+                            expected: dim,
+                            actual: query.dimension(),
+                        });
+                    }
+                    for (acc, x) in sum.iter_mut().zip(query.as_slice()) {
+                        *acc += weight * x;
+                    }
+                    total_weight += weight;
+                }
+                if total_weight == 0.0 {
+                    return Err(VectorDbError::InvalidVector {
+                        reason: "Fusion weights must not sum to zero".to_string(),
+                    });
+                }
+                let centroid = Vector::new(sum.into_iter().map(|x| x / total_weight).collect());
+                self.search(&centroid, k)
+            }
+            FusionMode::Rank => {
+                let mut scores: HashMap<String, f32> = HashMap::new();
+                for (query, weight) in queries {
+                    for (rank, result) in self.search(query, k)?.into_iter().enumerate() {
+                        *scores.entry(result.id).or_insert(0.0) +=
+                            weight / (RRF_K + rank as f32 + 1.0);
+                    }
+                }
+
+                let mut results: Vec<SearchResult> = scores
+                    .into_iter()
+                    .map(|(id, score)| SearchResult {
+                        id,
+                        distance: -score,
+                    })
+                    .collect();
+                results.sort_by(|a, b| {
+                    a.distance
+                        .partial_cmp(&b.distance)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| a.id.cmp(&b.id))
+                });
+                results.truncate(k);
+                Ok(results)
+            }
+        }
+    }
+
+    /// Rank by a blend of vector similarity and keyword overlap:
+    /// `alpha * vector_score + (1 - alpha) * keyword_score`, with `alpha`
+    /// clamped to [0, 1].
+    ///
+    /// `vector_score` is the `ScoreKind::Similarity` score for the store's
+    /// metric. `keyword_score` is the fraction of `text_query`'s distinct
+    /// words that appear in the `HYBRID_TEXT_FIELD` metadata field. Candidates
+    /// are the over-fetched (3x `k`) vector neighbors plus every entry with a
+    /// keyword match. As with rank fusion, `distance` holds the negated score.
+    pub fn hybrid_search(
+        &self,
+        query: &Vector,
+        text_query: &str,
+        k: usize,
+        alpha: f32,
+    ) -> Result<Vec<SearchResult>> {
+        if self.is_empty() {
+            return Ok(vec![]);
+        }
+        self.check_query(query)?;
+
+        let alpha = alpha.clamp(0.0, 1.0);
+        let metric = self.metric();
+        let query_terms = keyword_terms(text_query);
+        let keyword_score = |internal_id: usize| -> f32 {
+            let Some(text) = self
+                .metadata
+                .get(&internal_id)
+                .and_then(|meta| meta.get(HYBRID_TEXT_FIELD))
+            else {
+                return 0.0;
+            };
+            if query_terms.is_empty() {
+                return 0.0;
+            }
+            let matched = keyword_terms(text).intersection(&query_terms).count();
+            matched as f32 / query_terms.len() as f32
+        };
+
+        let mut candidates: HashMap<usize, f32> =
+            self.search_index(query, k.saturating_mul(3))?.into_iter().collect();
+        let now = (self.clock)();
+        for &internal_id in self.internal_to_id.keys() {
+            if candidates.contains_key(&internal_id)
+                || self.is_expired(internal_id, now)
+                || keyword_score(internal_id) == 0.0
+            {
+                continue;
+            }
+            if let Some(vector) = self.index.reconstruct_vector(internal_id) {
+                candidates.insert(internal_id, metric.distance(query, &vector)?);
+            }
+        }
+
+        let mut scored: Vec<(usize, f32)> = candidates
+            .into_iter()
+            .map(|(internal_id, distance)| {
+                let vector_score = ScoreKind::Similarity.score(metric, distance);
+                let score = alpha * vector_score + (1.0 - alpha) * keyword_score(internal_id);
+                (internal_id, -score)
+            })
+            .collect();
+        scored.sort_by(|a, b| crate::distance::cmp_distance(a.1, b.1).then(a.0.cmp(&b.0)));
+        scored.truncate(k);
+        Ok(self.to_search_results(scored))
+    }
+
+    /// Insert a batch of vectors. Stops at the first error and returns it;
+    /// items before the failing one are still inserted (see
+    /// `insert_batch_atomic` for all-or-nothing).
+    ///
+    /// Vectors are handed to the index in a single `Index::add_batch` call so
+    /// indexes with a bulk-loading path can use it. The ID maps and metadata
+    /// are only updated once that call succeeds; if it fails, the store is
+    /// left as it was.
+    pub fn insert_batch(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
+        let dimension_before = self.dimension;
+        let mut next_id = self.next_id;
+        let mut staged: Vec<(usize, Vector)> = Vec::with_capacity(items.len());
+        let mut entries: Vec<(String, usize, Metadata, Timestamps)> =
+            Vec::with_capacity(items.len());
+        let mut batch_ids: HashMap<String, usize> = HashMap::new();
+        let mut failure = None;
+
+        for item in items {
+            if let Err(e) = self.check_insert(&item.vector) {
+                failure = Some(e);
+                break;
+            }
+
+            let internal_id = next_id;
+            next_id += 1;
+            if let Some(earlier) = batch_ids.insert(item.id.clone(), internal_id) {
+                // Duplicate ID within this batch: only the last copy is indexed
+                staged.retain(|(id, _)| *id != earlier);
+                entries.retain(|(_, id, _, _)| *id != earlier);
+            }
+            let timestamps = self.next_timestamps(&item.id);
+            staged.push((internal_id, item.vector));
+            entries.push((item.id, internal_id, item.metadata, timestamps));
+        }
+
+        if let Err(e) = self.index.add_batch(staged) {
+            // The index may have taken part of the batch before failing
+            for (_, internal_id, _, _) in &entries {
+                let _ = self.index.remove(*internal_id);
+            }
+            self.dimension = dimension_before;
+            return Err(e);
+        }
+
+        self.next_id = next_id;
+        for (id, internal_id, metadata, timestamps) in entries {
+            if let Some(&old_internal) = self.id_to_internal.get(&id) {
+                self.index.remove(old_internal)?;
+                self.remove_metadata(old_internal);
+                self.internal_to_id.remove(&old_internal);
+            }
+            self.id_to_internal.insert(id.clone(), internal_id);
+            self.internal_to_id.insert(internal_id, id);
+            self.set_metadata(internal_id, metadata);
+            self.timestamps.insert(internal_id, timestamps);
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Insert a batch all-or-nothing: every item is validated before the store
+    /// is touched, so on error nothing has been inserted or replaced.
+    ///
+    /// Besides dimensions (against the store's, or the first item's when the
+    /// store is empty), each vector is measured against itself under the
+    /// store's metric, which catches what the index would reject later, such
+    /// as a zero vector under `Cosine`. Duplicate IDs within the batch are
+    /// allowed; the last one wins, as with `insert_batch`.
+    pub fn insert_batch_atomic(&mut self, items: Vec<BatchInsertItem>) -> Result<()> {
+        self.validate_batch(&items)?;
+        self.insert_batch(items)
+    }
+
+    /// The checks `insert_batch_atomic` runs before touching the store.
+    pub(crate) fn validate_batch(&self, items: &[BatchInsertItem]) -> Result<()> {
+        let mut expected = self.dimension;
+        for item in items {
+            self.validate_vector(&item.vector, expected)?;
+            expected = Some(item.vector.dimension());
+        }
+        Ok(())
+    }
+
+    /// `validate_batch` for a single vector.
+    pub(crate) fn validate_insert(&self, vector: &Vector) -> Result<()> {
+        self.validate_vector(vector, self.dimension)
+    }
+
+    fn validate_vector(&self, vector: &Vector, expected: Option<usize>) -> Result<()> {
+        let dim = vector.dimension();
+        self.check_max_dimension(dim)?;
+        if let Some(expected) = expected.filter(|&expected| expected != dim) {
+            return Err(VectorDbError::DimensionMismatch {
+                expected,
+                actual: dim,
+            });
+        }
+        let metric = self.index.metric();
+        metric.validate(vector)?;
+        metric.distance(vector, vector)?;
+        Ok(())
+    }
+
+    /// Insert a batch item by item, carrying on past failures. Returns one
+    /// result per item, in order.
+    ///
+    /// Unlike `insert_batch`, each vector goes to the index on its own, so
+    /// there is no bulk-loading path.
+    pub fn insert_batch_lenient(&mut self, items: Vec<BatchInsertItem>) -> Vec<Result<()>> {
+        items
+            .into_iter()
+            .map(|item| self.insert_with_metadata(item.id, item.vector, item.metadata))
+            .collect()
     }
 
     /// Search for k nearest neighbors for multiple queries at once.
     /// Returns one result set per query.
+    ///
+    /// All query dimensions are validated up front, so a mismatched query fails
+    /// the batch before any search runs and the per-query loop skips the check.
     pub fn search_batch(
         &self,
         queries: &[(Vector, usize)],
     ) -> Result<Vec<Vec<SearchResult>>> {
+        if self.is_empty() {
+            return Ok(vec![Vec::new(); queries.len()]);
+        }
+
+        for (query, _) in queries {
+            self.check_query(query)?;
+        }
+
+        queries
+            .iter()
+            .map(|(query, k)| self.search_unchecked(query, *k))
+            .collect()
+    }
+
+    /// Search for k nearest neighbors for multiple queries, reporting each query's
+    /// outcome independently. A failing query yields a `BatchQueryError` tagged with
+    /// its position in `queries` and does not affect the others.
+    pub fn search_batch_each(
+        &self,
+        queries: &[(Vector, usize)],
+    ) -> Vec<Result<Vec<SearchResult>>> {
         queries
             .iter()
-            .map(|(query, k)| self.search(query, *k))
+            .enumerate()
+            .map(|(index, (query, k))| {
+                self.search(query, *k)
+                    .map_err(|e| VectorDbError::BatchQueryError {
+                        index,
+                        source: Box::new(e),
+                    })
+            })
             .collect()
     }
 
@@ -321,11 +1319,189 @@ impl<I: Index> VectorStore<I> {
             .collect()
     }
 
+    /// Iterate over all `(id, vector, metadata)` entries in the store.
+    /// Iteration order is unspecified.
+    ///
+    /// Vectors are borrowed from the index where it keeps them in memory and
+    /// reconstructed otherwise (see `Index::reconstruct_vector`).
+    pub fn iter(&self) -> impl Iterator<Item = (&String, Cow<'_, Vector>, &Metadata)> + '_ {
+        self.internal_to_id.iter().filter_map(|(internal_id, id)| {
+            let vector = match self.index.get_vector(*internal_id) {
+                Some(vector) => Cow::Borrowed(vector),
+                None => Cow::Owned(self.index.reconstruct_vector(*internal_id)?),
+            };
+            let metadata = self.metadata.get(internal_id)?;
+            Some((id, vector, metadata))
+        })
+    }
+
+    /// Write every vector as a JSON object per line:
+    /// `{"id": ..., "vector": [...], "metadata": {...}}`, in insertion order.
+    pub fn export_jsonl<W: Write>(&self, writer: W) -> Result<()> {
+        let mut internal_ids: Vec<usize> = self.internal_to_id.keys().copied().collect();
+        internal_ids.sort_unstable();
+
+        let mut writer = BufWriter::new(writer);
+        for internal_id in internal_ids {
+            let Some(vector) = self.index.reconstruct_vector(internal_id) else {
+                continue;
+            };
+            let record = JsonlRecord {
+                id: self.internal_to_id[&internal_id].clone(),
+                vector: vector.as_slice().to_vec(),
+                metadata: self
+                    .metadata
+                    .get(&internal_id)
+                    .map(|m| m.fields().clone())
+                    .unwrap_or_default(),
+            };
+            serde_json::to_writer(&mut writer, &record)
+                .map_err(|e| VectorDbError::SerializationError(e.to_string()))?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Insert every record from a JSON Lines dump written by `export_jsonl`.
+    /// Returns the number of records imported.
+    pub fn import_jsonl<R: Read>(&mut self, reader: R) -> Result<usize> {
+        let items = read_jsonl(reader)?;
+        let count = items.len();
+        self.insert_batch(items)?;
+        Ok(count)
+    }
+
+    /// Let the index reclaim space left by deletes and apply any internal ID
+    /// renumbering it reports to the ID maps and metadata.
+    pub fn compact(&mut self) {
+        let remap = self.index.compact();
+        if remap.is_empty() {
+            return;
+        }
+        let new_id = |old: usize| remap.get(&old).copied().unwrap_or(old);
+
+        self.internal_to_id = self
+            .internal_to_id
+            .drain()
+            .map(|(internal_id, id)| (new_id(internal_id), id))
+            .collect();
+        for internal_id in self.id_to_internal.values_mut() {
+            *internal_id = new_id(*internal_id);
+        }
+        self.metadata = self
+            .metadata
+            .drain()
+            .map(|(internal_id, meta)| (new_id(internal_id), meta))
+            .collect();
+        self.expires_at.remap(new_id);
+        self.timestamps = self
+            .timestamps
+            .drain()
+            .map(|(internal_id, ts)| (new_id(internal_id), ts))
+            .collect();
+        self.next_id = self.internal_to_id.keys().max().map_or(0, |&max| max + 1);
+    }
+
+    /// Move every vector into a new index from `make_index` (given this
+    /// store's metric), e.g. to switch a loaded store from flat to HNSW.
+    ///
+    /// Internal IDs are kept, so the ID maps, metadata, expiry times,
+    /// timestamps and dimension settings carry over untouched. Vectors are added with one
+    /// `add_batch` in internal ID order. An index that only keeps an
+    /// approximation (e.g. `QuantizedFlatIndex`) hands over its reconstruction;
+    /// fails with `IndexError` if the current index can't return vectors at all.
+    pub fn rebuild_index<J: Index>(
+        self,
+        make_index: impl FnOnce(DistanceMetric) -> J,
+    ) -> Result<VectorStore<J>> {
+        let mut internal_ids: Vec<usize> = self.internal_to_id.keys().copied().collect();
+        internal_ids.sort_unstable();
+        let items = internal_ids
+            .into_iter()
+            .map(|internal_id| match self.index.reconstruct_vector(internal_id) {
+                Some(vector) => Ok((internal_id, vector)),
+                None => Err(VectorDbError::IndexError(
+                    "the current index can't return its vectors".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut index = make_index(self.index.metric());
+        index.add_batch(items)?;
+        Ok(VectorStore {
+            index,
+            id_to_internal: self.id_to_internal,
+            internal_to_id: self.internal_to_id,
+            metadata: self.metadata,
+            next_id: self.next_id,
+            dimension: self.dimension,
+            max_dimension: self.max_dimension,
+            expires_at: self.expires_at,
+            clock: self.clock,
+            timestamps: self.timestamps,
+            millis_clock: self.millis_clock,
+        })
+    }
+
+    /// IDs of vectors whose expiry time has passed. They are already hidden
+    /// from searches but stay stored until `expire_now` removes them.
+    pub fn expired_ids(&self) -> Vec<String> {
+        let now = (self.clock)();
+        self.expires_at
+            .due(now)
+            .filter_map(|internal_id| self.internal_to_id.get(&internal_id).cloned())
+            .collect()
+    }
+
+    /// Delete every expired vector, returning how many were removed.
+    pub fn expire_now(&mut self) -> usize {
+        let expired = self.expired_ids();
+        for id in &expired {
+            let _ = self.delete(id);
+        }
+        expired.len()
+    }
+
     /// List all vector IDs
     pub fn list_ids(&self) -> Vec<String> {
         self.id_to_internal.keys().cloned().collect()
     }
 
+    /// The distinct values of metadata `field` across all vectors, sorted.
+    /// Vectors without the field are skipped.
+    pub fn distinct_values(&self, field: &str) -> Vec<String> {
+        let mut values: Vec<String> = self.value_counts(field).into_keys().collect();
+        values.sort();
+        values
+    }
+
+    /// How many vectors have each value of metadata `field`.
+    pub fn value_counts(&self, field: &str) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for metadata in self.metadata.values() {
+            if let Some(value) = metadata.get(field) {
+                *counts.entry(value.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Every vector's ID with its metadata (empty if it has none), without
+    /// the vectors themselves. Sorted by ID so callers can page through it.
+    pub fn all_metadata(&self) -> Vec<(String, Metadata)> {
+        let mut entries: Vec<(String, Metadata)> = self
+            .id_to_internal
+            .iter()
+            .map(|(id, internal_id)| {
+                let metadata = self.metadata.get(internal_id).cloned().unwrap_or_default();
+                (id.clone(), metadata)
+            })
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
     /// Get the distance metric used by this store
     pub fn metric(&self) -> DistanceMetric {
         self.index.metric()
@@ -336,11 +1512,22 @@ impl<I: Index> VectorStore<I> {
         self.dimension
     }
 
+    /// Approximate heap memory held by the underlying index, in bytes.
+    /// The string ID maps and metadata are not included.
+    pub fn memory_usage_bytes(&self) -> usize {
+        self.index.memory_usage_bytes()
+    }
+
     /// Get a reference to the underlying index.
     pub fn index(&self) -> &I {
         &self.index
     }
 
+    /// The internal ID the index knows the vector `id` by.
+    pub fn internal_id(&self, id: &str) -> Option<usize> {
+        self.id_to_internal.get(id).copied()
+    }
+
     /// Get a reference to the internal ID mapping (internal_id -> string_id).
     pub fn internal_to_string_ids(&self) -> &HashMap<usize, String> {
         &self.internal_to_id
@@ -425,17 +1612,459 @@ mod tests {
     }
 
     #[test]
-    fn test_get_metadata() {
+    fn test_get_metadata() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let mut meta = Metadata::new();
+        meta.insert("color".to_string(), "red".to_string());
+        store
+            .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0, 3.0]), meta)
+            .unwrap();
+
+        let m = store.get_metadata("v1").unwrap();
+        assert_eq!(m.get("color"), Some(&"red".to_string()));
+        assert!(store.get_metadata("nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_ttl_with_injected_clock() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NOW: AtomicU64 = AtomicU64::new(1_000);
+        fn clock() -> u64 {
+            NOW.load(Ordering::SeqCst)
+        }
+
+        let mut store = VectorStore::new(DistanceMetric::Euclidean).with_clock(clock);
+        let mut short = Metadata::new();
+        short.set_expires_at(1_010);
+        short.insert("kind".to_string(), "session".to_string());
+        store
+            .insert_with_metadata("short", Vector::new(vec![0.0, 0.0]), short)
+            .unwrap();
+        store.insert("forever", Vector::new(vec![5.0, 5.0])).unwrap();
+
+        let query = Vector::new(vec![0.0, 0.0]);
+        assert_eq!(store.search(&query, 1).unwrap()[0].id, "short");
+        assert!(store.expired_ids().is_empty());
+
+        NOW.store(1_010, Ordering::SeqCst);
+        let results = store.search(&query, 2).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "forever");
+        let filter = MetadataFilter::Eq {
+            field: "kind".to_string(),
+            value: "session".to_string(),
+        };
+        assert!(store.search_with_filter(&query, 2, &filter).unwrap().is_empty());
+        assert_eq!(store.search_with_ef(&query, 2, 10).unwrap().len(), 1);
+        // Still stored until swept
+        assert_eq!(store.len(), 2);
+
+        assert_eq!(store.expire_now(), 1);
+        assert_eq!(store.len(), 1);
+        assert!(store.get("short").is_none());
+        assert_eq!(store.expire_now(), 0);
+    }
+
+    #[test]
+    fn test_expiry_follows_updates_and_deletes() {
+        fn clock() -> u64 {
+            2_000
+        }
+        let expiring = |t: u64| {
+            let mut meta = Metadata::new();
+            meta.set_expires_at(t);
+            meta
+        };
+
+        let mut store = VectorStore::new(DistanceMetric::Euclidean).with_clock(clock);
+        for (i, t) in [1_500, 1_000, 3_000].into_iter().enumerate() {
+            let v = Vector::new(vec![i as f32]);
+            store.insert_with_metadata(format!("v{}", i), v, expiring(t)).unwrap();
+        }
+        assert_eq!(store.expired_ids(), vec!["v1", "v0"]);
+
+        // Pushing an expiry out drops the old entry from the ordering
+        store.update_metadata("v0", expiring(2_500)).unwrap();
+        store.delete("v1").unwrap();
+        assert!(store.expired_ids().is_empty());
+        assert_eq!(store.search(&Vector::new(vec![0.0]), 3).unwrap().len(), 2);
+
+        store.update_metadata("v2", expiring(1_999)).unwrap();
+        store.compact();
+        assert_eq!(store.expired_ids(), vec!["v2"]);
+        assert_eq!(store.expire_now(), 1);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_search_rerank_improves_hnsw_top1() {
+        use crate::bench::random_vectors;
+        use crate::hnsw::{HnswIndex, HnswParams};
+
+        // A search beam of 1 makes plain HNSW top-1 results unreliable
+        let params = HnswParams::new(4, 16, 1);
+        let index = HnswIndex::with_params(DistanceMetric::Euclidean, params).unwrap();
+        let mut hnsw = VectorStore::with_index(index);
+        let mut flat = VectorStore::new(DistanceMetric::Euclidean);
+        for (i, v) in random_vectors(1000, 16).into_iter().enumerate() {
+            hnsw.insert(format!("v{}", i), v.clone()).unwrap();
+            flat.insert(format!("v{}", i), v).unwrap();
+        }
+
+        let (mut plain_hits, mut rerank_hits) = (0, 0);
+        for query in random_vectors(100, 16) {
+            let truth = &flat.search(&query, 1).unwrap()[0].id;
+            plain_hits += (&hnsw.search(&query, 1).unwrap()[0].id == truth) as usize;
+            let reranked = hnsw.search_rerank(&query, 1, 64).unwrap();
+            rerank_hits += (&reranked[0].id == truth) as usize;
+        }
+        assert!(
+            rerank_hits > plain_hits,
+            "rerank {} vs plain {}",
+            rerank_hits,
+            plain_hits
+        );
+
+        // On a flat index re-ranking changes nothing
+        let query = Vector::new(vec![0.5; 16]);
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+        assert_eq!(
+            ids(flat.search(&query, 5).unwrap()),
+            ids(flat.search_rerank(&query, 5, 20).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_insert_dedup_radius() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let insert = |store: &mut VectorStore<FlatIndex>, id: &str, x: f32| {
+            store
+                .insert_dedup(id, Vector::new(vec![x, 0.0]), Metadata::new(), 0.5)
+                .unwrap()
+        };
+
+        assert_eq!(insert(&mut store, "a", 0.0), InsertOutcome::Inserted);
+        assert_eq!(
+            insert(&mut store, "inside", 0.49),
+            InsertOutcome::Duplicate("a".to_string())
+        );
+        assert_eq!(insert(&mut store, "outside", 0.51), InsertOutcome::Inserted);
+        assert_eq!(store.len(), 2);
+        assert!(store.get("inside").is_none());
+    }
+
+    #[test]
+    fn test_rescore_similarity() {
+        let mut store = VectorStore::new(DistanceMetric::Cosine);
+        store.insert("same", Vector::new(vec![1.0, 1.0])).unwrap();
+        store.insert("orthogonal", Vector::new(vec![1.0, -1.0])).unwrap();
+        let query = Vector::new(vec![2.0, 2.0]);
+
+        let results = store.search(&query, 2).unwrap();
+        let scored = store.rescore(results.clone(), ScoreKind::Similarity);
+        assert_eq!(scored[0].id, "same");
+        assert_relative_eq!(scored[0].distance, 1.0, epsilon = 1e-6);
+        assert_relative_eq!(scored[1].distance, 0.0, epsilon = 1e-6);
+        assert_eq!(store.rescore(results, ScoreKind::Distance)[1].distance, 1.0);
+
+        let mut store = VectorStore::new(DistanceMetric::DotProduct);
+        store.insert("v", Vector::new(vec![3.0, 1.0])).unwrap();
+        let results = store.search(&query, 1).unwrap();
+        assert_eq!(store.rescore(results, ScoreKind::Similarity)[0].distance, 8.0);
+    }
+
+    #[test]
+    fn test_max_dimension() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean).with_max_dimension(3);
+        let err = store.insert("big", Vector::new(vec![1.0; 4])).unwrap_err();
+        assert!(matches!(err, VectorDbError::InvalidVector { .. }));
+        let items = vec![BatchInsertItem {
+            id: "big".to_string(),
+            vector: Vector::new(vec![1.0; 4]),
+            metadata: Metadata::new(),
+        }];
+        assert!(store.insert_batch(items).is_err());
+        assert!(store.is_empty());
+        assert_eq!(store.dimension(), None);
+
+        store.insert("ok", Vector::new(vec![1.0; 3])).unwrap();
+        let err = store.search(&Vector::new(vec![1.0; 4]), 1).unwrap_err();
+        assert!(matches!(err, VectorDbError::InvalidVector { .. }));
+
+        store.set_max_dimension(None);
+        assert!(store.max_dimension().is_none());
+    }
+
+    #[test]
+    fn test_all_metadata_sorted_by_id() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let mut meta = Metadata::new();
+        meta.insert("color".to_string(), "red".to_string());
+        store
+            .insert_with_metadata("b", Vector::new(vec![1.0]), meta)
+            .unwrap();
+        store.insert("a", Vector::new(vec![2.0])).unwrap();
+        store.insert("c", Vector::new(vec![3.0])).unwrap();
+        store.delete("c").unwrap();
+
+        let all = store.all_metadata();
+        let ids: Vec<&str> = all.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert!(all[0].1.fields().is_empty());
+        assert_eq!(all[1].1.get("color").map(String::as_str), Some("red"));
+    }
+
+    #[test]
+    fn test_distinct_values_and_counts() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let categories = [Some("books"), Some("music"), None, Some("books"), Some("film")];
+        for (i, category) in categories.iter().enumerate() {
+            let mut meta = Metadata::new();
+            if let Some(category) = category {
+                meta.insert("category".to_string(), category.to_string());
+            }
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+        store.delete("v4").unwrap();
+
+        assert_eq!(store.distinct_values("category"), vec!["books", "music"]);
+        let counts = store.value_counts("category");
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["books"], 2);
+        assert_eq!(counts["music"], 1);
+        assert!(store.distinct_values("missing").is_empty());
+    }
+
+    #[test]
+    fn test_get_many_preserves_order() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("a", Vector::new(vec![1.0])).unwrap();
+        store.insert("b", Vector::new(vec![2.0])).unwrap();
+
+        let ids: Vec<String> = ["b", "missing", "a"].iter().map(|s| s.to_string()).collect();
+        let found = store.get_many(&ids);
+        assert_eq!(
+            found,
+            vec![
+                ("b".to_string(), Some(Vector::new(vec![2.0]))),
+                ("missing".to_string(), None),
+                ("a".to_string(), Some(Vector::new(vec![1.0]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cosine_rejects_zero_query() {
+        let mut store = VectorStore::new(DistanceMetric::Cosine);
+        store.insert("a", Vector::new(vec![1.0, 0.0])).unwrap();
+        let zero = Vector::new(vec![0.0, 0.0]);
+
+        let err = store.search(&zero, 1).unwrap_err();
+        assert!(err.to_string().contains("non-zero query"), "{}", err);
+        assert!(store.search_batch(&[(zero.clone(), 1)]).is_err());
+        let filter = MetadataFilter::Exists {
+            field: "k".to_string(),
+        };
+        assert!(store.search_with_filter(&zero, 1, &filter).is_err());
+
+        // Zero is a fine query under other metrics
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("a", Vector::new(vec![1.0, 0.0])).unwrap();
+        assert_eq!(store.search(&zero, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_jensen_shannon_validates_inserts_and_queries() {
+        let mut store = VectorStore::new(DistanceMetric::JensenShannon);
+        store.insert("a", Vector::new(vec![0.5, 0.5])).unwrap();
+
+        let bad = Vector::new(vec![2.0, 0.0]);
+        assert!(matches!(
+            store.insert("bad", bad.clone()),
+            Err(VectorDbError::InvalidVector { .. })
+        ));
+        let batch = vec![BatchInsertItem {
+            id: "bad".to_string(),
+            vector: bad.clone(),
+            metadata: Metadata::new(),
+        }];
+        assert!(store.insert_batch(batch).is_err());
+        assert_eq!(store.len(), 1);
+
+        // The rejected vectors never reached the index, so searches still work
+        let results = store.search(&Vector::new(vec![1.0, 0.0]), 1).unwrap();
+        assert_eq!(results[0].id, "a");
+        assert!(matches!(store.search(&bad, 1), Err(VectorDbError::InvalidVector { .. })));
+    }
+
+    #[test]
+    fn test_hybrid_search_alpha_flips_ranking() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let with_text = |text: &str| {
+            let mut meta = Metadata::new();
+            meta.insert(HYBRID_TEXT_FIELD.to_string(), text.to_string());
+            meta
+        };
+        store
+            .insert_with_metadata("close", Vector::new(vec![1.0, 0.0]), with_text("cooking tips"))
+            .unwrap();
+        store
+            .insert_with_metadata(
+                "lexical",
+                // Farther than all the noise, so only the keyword match makes it a candidate
+                Vector::new(vec![0.0, 30.0]),
+                with_text("Building a Rust vector database"),
+            )
+            .unwrap();
+        for i in 0..20 {
+            let far = Vector::new(vec![-5.0 - i as f32, -5.0]);
+            store.insert_with_metadata(format!("noise{}", i), far, with_text("rust")).unwrap();
+        }
+
+        let query = Vector::new(vec![1.0, 0.0]);
+        let top = |alpha: f32| {
+            store.hybrid_search(&query, "rust vector database", 1, alpha).unwrap()[0].clone()
+        };
+        assert_eq!(top(1.0).id, "close");
+        assert_eq!(top(0.9).id, "close");
+        assert_eq!(top(0.2).id, "lexical");
+        assert_eq!(top(0.0).id, "lexical");
+        // Distances carry the negated blended score
+        assert_relative_eq!(top(0.0).distance, -1.0, epsilon = 1e-6);
+        assert_relative_eq!(top(1.0).distance, -1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_inner_product_ranks_by_largest_dot() {
+        use crate::hnsw::HnswIndex;
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let vectors: Vec<Vector> = (0..300)
+            .map(|_| Vector::new((0..8).map(|_| rng.gen_range(-1.0..1.0)).collect()))
+            .collect();
+        let query = Vector::new((0..8).map(|_| rng.gen_range(-1.0..1.0)).collect());
+        let dots: Vec<f32> = vectors
+            .iter()
+            .map(|v| crate::distance::dot_product(&query, v))
+            .collect();
+        let best = (0..dots.len())
+            .max_by(|&a, &b| dots[a].total_cmp(&dots[b]))
+            .unwrap();
+
+        let mut flat = VectorStore::new(DistanceMetric::InnerProduct);
+        let mut hnsw = VectorStore::with_index(HnswIndex::new(DistanceMetric::InnerProduct));
+        for (i, v) in vectors.iter().enumerate() {
+            flat.insert(format!("v{}", i), v.clone()).unwrap();
+            hnsw.insert(format!("v{}", i), v.clone()).unwrap();
+        }
+
+        let results = flat.search(&query, 10).unwrap();
+        assert_eq!(results[0].id, format!("v{}", best));
+        // Raw scores, not negated, in descending order
+        assert_relative_eq!(results[0].distance, dots[best], epsilon = 1e-5);
+        assert!(results.windows(2).all(|w| w[0].distance >= w[1].distance));
+
+        let results = hnsw.search_with_ef(&query, 10, 200).unwrap();
+        assert_eq!(results[0].id, format!("v{}", best));
+        assert!(results.windows(2).all(|w| w[0].distance >= w[1].distance));
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("a", Vector::new(vec![0.0, 0.0, 3.0])).unwrap();
+        store.insert("b", Vector::new(vec![2.0, 4.0, 3.0])).unwrap();
+        store.insert("c", Vector::new(vec![100.0, 100.0, 100.0])).unwrap();
+
+        let ids = ["a".to_string(), "b".to_string()];
+        assert_eq!(store.centroid(&ids).unwrap().as_slice(), &[1.0, 2.0, 3.0]);
+
+        assert!(matches!(
+            store.centroid(&[]),
+            Err(VectorDbError::InvalidVector { .. })
+        ));
+        assert!(matches!(
+            store.centroid(&["a".to_string(), "zzz".to_string()]),
+            Err(VectorDbError::VectorNotFound { id }) if id == "zzz"
+        ));
+    }
+
+    #[test]
+    fn test_centroid_on_quantized_index() {
+        use crate::quantized_index::QuantizedFlatIndex;
+
+        let mut store = VectorStore::with_index(QuantizedFlatIndex::new(DistanceMetric::Euclidean));
+        store.insert("a", Vector::new(vec![0.0, 1.0, 3.0])).unwrap();
+        store.insert("b", Vector::new(vec![2.0, 4.0, 3.0])).unwrap();
+
+        let ids = ["a".to_string(), "b".to_string()];
+        let expected = Vector::mean_of([
+            &store.reconstruct("a").unwrap(),
+            &store.reconstruct("b").unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(store.centroid(&ids).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rename_keeps_vector_and_metadata() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let mut meta = Metadata::new();
+        meta.insert("color".to_string(), "red".to_string());
+        store
+            .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0]), meta)
+            .unwrap();
+        store.insert("v2", Vector::new(vec![5.0, 5.0])).unwrap();
+
+        store.rename("v1", "a").unwrap();
+        assert!(store.get("v1").is_none());
+        assert_eq!(store.get("a").unwrap().as_slice(), &[1.0, 2.0]);
+        assert_eq!(store.get_metadata("a").unwrap().get("color").unwrap(), "red");
+        assert_eq!(store.len(), 2);
+        let results = store.search(&Vector::new(vec![1.0, 2.0]), 1).unwrap();
+        assert_eq!(results[0].id, "a");
+        store.rename("a", "a").unwrap();
+
+        assert!(matches!(
+            store.rename("v1", "b"),
+            Err(VectorDbError::VectorNotFound { id }) if id == "v1"
+        ));
+        assert!(matches!(
+            store.rename("a", "v2"),
+            Err(VectorDbError::VectorExists { id }) if id == "v2"
+        ));
+        assert_eq!(store.get("v2").unwrap().as_slice(), &[5.0, 5.0]);
+        assert!(store.get("a").is_some());
+    }
+
+    #[test]
+    fn test_update_metadata_merges() {
         let mut store = VectorStore::new(DistanceMetric::Euclidean);
         let mut meta = Metadata::new();
         meta.insert("color".to_string(), "red".to_string());
+        meta.insert("size".to_string(), "small".to_string());
         store
-            .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0, 3.0]), meta)
+            .insert_with_metadata("v1", Vector::new(vec![1.0, 2.0]), meta)
             .unwrap();
 
+        let mut patch = Metadata::new();
+        patch.insert("color".to_string(), "blue".to_string());
+        patch.insert("shape".to_string(), "round".to_string());
+        store.update_metadata("v1", patch).unwrap();
+
         let m = store.get_metadata("v1").unwrap();
-        assert_eq!(m.get("color"), Some(&"red".to_string()));
-        assert!(store.get_metadata("nonexistent").is_none());
+        assert_eq!(m.get("color"), Some(&"blue".to_string()));
+        assert_eq!(m.get("size"), Some(&"small".to_string()));
+        assert_eq!(m.get("shape"), Some(&"round".to_string()));
+        assert!(matches!(
+            store.update_metadata("missing", Metadata::new()),
+            Err(VectorDbError::VectorNotFound { .. })
+        ));
     }
 
     #[test]
@@ -451,6 +2080,88 @@ mod tests {
         assert_eq!(store.len(), 1);
     }
 
+    #[test]
+    fn test_iter_yields_all_entries() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let mut meta = Metadata::new();
+        meta.insert("color".to_string(), "red".to_string());
+        store
+            .insert_with_metadata("v1", Vector::new(vec![1.0, 0.0]), meta)
+            .unwrap();
+        store.insert("v2", Vector::new(vec![0.0, 1.0])).unwrap();
+        store.insert("v3", Vector::new(vec![1.0, 1.0])).unwrap();
+        store.delete("v3").unwrap();
+
+        let entries: Vec<_> = store.iter().collect();
+        assert_eq!(entries.len(), store.len());
+
+        let mut ids: Vec<&str> = entries.iter().map(|(id, _, _)| id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["v1", "v2"]);
+
+        for (id, vector, metadata) in entries {
+            assert_eq!(Some(vector.as_ref()), store.get(id));
+            if id == "v1" {
+                assert_eq!(metadata.get("color"), Some(&"red".to_string()));
+            }
+        }
+    }
+
+    // --- Query fusion tests ---
+
+    fn fusion_store() -> VectorStore<FlatIndex> {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("a", Vector::new(vec![1.0, 0.0])).unwrap();
+        store.insert("b", Vector::new(vec![0.0, 1.0])).unwrap();
+        store.insert("c", Vector::new(vec![0.5, 0.5])).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_search_fused_centroid() {
+        let store = fusion_store();
+        let queries = vec![
+            (Vector::new(vec![1.0, 0.0]), 1.0),
+            (Vector::new(vec![0.0, 1.0]), 1.0),
+        ];
+        let results = store.search_fused(&queries, 1).unwrap();
+        assert_eq!(results[0].id, "c");
+        assert_relative_eq!(results[0].distance, 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_search_fused_rank_blends_lists() {
+        let store = fusion_store();
+
+        let favor_a = vec![
+            (Vector::new(vec![1.0, 0.0]), 3.0),
+            (Vector::new(vec![0.0, 1.0]), 1.0),
+        ];
+        let results = store
+            .search_fused_with(&favor_a, 3, FusionMode::Rank)
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].id, "a");
+
+        let favor_b = vec![
+            (Vector::new(vec![1.0, 0.0]), 1.0),
+            (Vector::new(vec![0.0, 1.0]), 3.0),
+        ];
+        let results = store
+            .search_fused_with(&favor_b, 3, FusionMode::Rank)
+            .unwrap();
+        assert_eq!(results[0].id, "b");
+    }
+
+    #[test]
+    fn test_search_fused_empty_queries() {
+        let store = fusion_store();
+        assert!(matches!(
+            store.search_fused(&[], 1),
+            Err(VectorDbError::InvalidVector { .. })
+        ));
+    }
+
     // --- MetadataFilter tests ---
 
     #[test]
@@ -610,6 +2321,225 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_search_with_filter_selective_returns_k() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        for i in 0..100 {
+            let mut meta = Metadata::new();
+            let tag = if i >= 90 { "rare" } else { "common" };
+            meta.insert("tag".to_string(), tag.to_string());
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+
+        // The 10 "rare" vectors are the furthest from the query
+        let filter = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "rare".to_string(),
+        };
+        let results = store
+            .search_with_filter(&Vector::new(vec![0.0]), 5, &filter)
+            .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["v90", "v91", "v92", "v93", "v94"]);
+    }
+
+    #[test]
+    fn test_search_with_filter_over_fetch_on_post_filtering_index() {
+        use crate::hnsw::HnswIndex;
+
+        let mut store = VectorStore::with_index(HnswIndex::new(DistanceMetric::Euclidean));
+        for i in 0..100 {
+            let mut meta = Metadata::new();
+            let tag = if i >= 90 { "rare" } else { "common" };
+            meta.insert("tag".to_string(), tag.to_string());
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+
+        let filter = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "rare".to_string(),
+        };
+        let query = Vector::new(vec![0.0]);
+        // 3x over-fetch only reaches v0..v14, none of which match
+        assert!(store.search_with_filter(&query, 5, &filter).unwrap().is_empty());
+
+        let results = store
+            .search_with_filter_over_fetch(&query, 5, &filter, 20)
+            .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["v90", "v91", "v92", "v93", "v94"]);
+    }
+
+    #[test]
+    fn test_search_with_filter_adaptive_finds_deep_matches() {
+        use crate::hnsw::HnswIndex;
+
+        let mut store = VectorStore::with_index(HnswIndex::new(DistanceMetric::Euclidean));
+        for i in 0..400 {
+            let mut meta = Metadata::new();
+            let tag = if i >= 390 { "rare" } else { "common" };
+            meta.insert("tag".to_string(), tag.to_string());
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+
+        let filter = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "rare".to_string(),
+        };
+        let query = Vector::new(vec![0.0]);
+        assert!(store.search_with_filter(&query, 10, &filter).unwrap().is_empty());
+
+        let results = store.search_with_filter_adaptive(&query, 10, &filter).unwrap();
+        let ids: Vec<String> = results.iter().map(|r| r.id.clone()).collect();
+        let expected: Vec<String> = (390..400).map(|i| format!("v{}", i)).collect();
+        assert_eq!(ids, expected);
+
+        // Fewer matches than k: stops once everything has been ranked
+        let results = store.search_with_filter_adaptive(&query, 20, &filter).unwrap();
+        assert_eq!(results.len(), 10);
+    }
+
+    #[test]
+    fn test_search_with_ef_keeps_ef_when_entries_expired() {
+        use crate::hnsw::HnswIndex;
+        use std::cell::Cell;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        /// `HnswIndex` that records the last `ef` it was searched with.
+        #[derive(Debug)]
+        struct RecordEf(HnswIndex, Cell<Option<usize>>);
+
+        impl Index for RecordEf {
+            fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+                self.0.add(id, vector)
+            }
+            fn remove(&mut self, id: usize) -> Result<()> {
+                self.0.remove(id)
+            }
+            fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+                self.1.set(None);
+                self.0.search(query, k)
+            }
+            fn search_with_ef(
+                &self,
+                query: &Vector,
+                k: usize,
+                ef: usize,
+            ) -> Result<Vec<(usize, f32)>> {
+                self.1.set(Some(ef));
+                Index::search_with_ef(&self.0, query, k, ef)
+            }
+            fn get_vector(&self, id: usize) -> Option<&Vector> {
+                self.0.get_vector(id)
+            }
+            fn metric(&self) -> DistanceMetric {
+                self.0.metric()
+            }
+            fn memory_usage_bytes(&self) -> usize {
+                self.0.memory_usage_bytes()
+            }
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        static NOW: AtomicU64 = AtomicU64::new(1_000);
+        fn clock() -> u64 {
+            NOW.load(Ordering::SeqCst)
+        }
+        let index = RecordEf(HnswIndex::new(DistanceMetric::Euclidean), Cell::new(None));
+        let mut store = VectorStore::with_index(index).with_clock(clock);
+        let mut short = Metadata::new();
+        short.set_expires_at(1_010);
+        store
+            .insert_with_metadata("short", Vector::new(vec![0.0, 0.0]), short)
+            .unwrap();
+        for i in 1..10 {
+            store
+                .insert(format!("v{}", i), Vector::new(vec![i as f32, 0.0]))
+                .unwrap();
+        }
+
+        NOW.store(1_010, Ordering::SeqCst);
+        let results = store.search_with_ef(&Vector::new(vec![0.0, 0.0]), 2, 77).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["v1", "v2"]);
+        assert_eq!(store.index().1.get(), Some(77));
+    }
+
+    #[test]
+    fn test_search_with_filter_adaptive_single_pass_when_prefiltering() {
+        use std::cell::Cell;
+
+        /// `FlatIndex` that counts filtered searches.
+        #[derive(Debug)]
+        struct Counting(FlatIndex, Cell<usize>);
+
+        impl Index for Counting {
+            fn add(&mut self, id: usize, vector: Vector) -> Result<()> {
+                self.0.add(id, vector)
+            }
+            fn remove(&mut self, id: usize) -> Result<()> {
+                self.0.remove(id)
+            }
+            fn search(&self, query: &Vector, k: usize) -> Result<Vec<(usize, f32)>> {
+                self.0.search(query, k)
+            }
+            fn search_filtered(
+                &self,
+                query: &Vector,
+                k: usize,
+                predicate: &dyn Fn(usize) -> bool,
+                over_fetch: usize,
+            ) -> Result<Vec<(usize, f32)>> {
+                self.1.set(self.1.get() + 1);
+                self.0.search_filtered(query, k, predicate, over_fetch)
+            }
+            fn prefilters(&self) -> bool {
+                self.0.prefilters()
+            }
+            fn get_vector(&self, id: usize) -> Option<&Vector> {
+                self.0.get_vector(id)
+            }
+            fn metric(&self) -> DistanceMetric {
+                self.0.metric()
+            }
+            fn memory_usage_bytes(&self) -> usize {
+                self.0.memory_usage_bytes()
+            }
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        let index = Counting(FlatIndex::new(DistanceMetric::Euclidean), Cell::new(0));
+        let mut store = VectorStore::with_index(index);
+        for i in 0..400 {
+            let mut meta = Metadata::new();
+            let tag = if i % 100 == 0 { "rare" } else { "common" };
+            meta.insert("tag".to_string(), tag.to_string());
+            store
+                .insert_with_metadata(format!("v{}", i), Vector::new(vec![i as f32]), meta)
+                .unwrap();
+        }
+
+        let filter = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "rare".to_string(),
+        };
+        let results = store
+            .search_with_filter_adaptive(&Vector::new(vec![0.0]), 10, &filter)
+            .unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(store.index().1.get(), 1);
+    }
+
     #[test]
     fn test_search_with_filter_none_matching() {
         let mut store = VectorStore::new(DistanceMetric::Euclidean);
@@ -676,6 +2606,165 @@ mod tests {
         assert_eq!(store.len(), 1);
     }
 
+    #[test]
+    fn test_batch_insert_atomic_leaves_store_untouched_on_error() {
+        use crate::hnsw::HnswIndex;
+
+        let item = |id: &str, data: Vec<f32>| BatchInsertItem {
+            id: id.to_string(),
+            vector: Vector::new(data),
+            metadata: Metadata::new(),
+        };
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("v0", Vector::new(vec![9.0, 9.0, 9.0])).unwrap();
+
+        // Mismatch deep in the batch, after an overwrite of an existing ID
+        let mut items: Vec<_> = (1..50)
+            .map(|i| item(&format!("v{}", i), vec![i as f32, 0.0, 0.0]))
+            .collect();
+        items.insert(10, item("v0", vec![0.0, 0.0, 0.0]));
+        items.push(item("bad", vec![1.0, 2.0]));
+        assert!(matches!(
+            store.insert_batch_atomic(items),
+            Err(VectorDbError::DimensionMismatch { expected: 3, actual: 2 })
+        ));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("v0").unwrap().as_slice(), &[9.0, 9.0, 9.0]);
+
+        // An empty store takes its dimension from the first item
+        let mut empty = VectorStore::new(DistanceMetric::Euclidean);
+        let items = vec![item("a", vec![1.0]), item("b", vec![1.0, 2.0])];
+        assert!(empty.insert_batch_atomic(items).is_err());
+        assert!(empty.is_empty());
+        assert_eq!(empty.dimension(), None);
+
+        // Metric-specific failures are caught up front too
+        let mut cosine = VectorStore::with_index(HnswIndex::new(DistanceMetric::Cosine));
+        let items = vec![item("a", vec![1.0, 0.0]), item("zero", vec![0.0, 0.0])];
+        assert!(matches!(
+            cosine.insert_batch_atomic(items),
+            Err(VectorDbError::InvalidVector { .. })
+        ));
+        assert!(cosine.is_empty());
+
+        let items = (0..5).map(|i| item(&format!("v{}", i), vec![1.0, i as f32, 0.0])).collect();
+        store.insert_batch_atomic(items).unwrap();
+        assert_eq!(store.len(), 5);
+        assert_eq!(store.get("v0").unwrap().as_slice(), &[1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_batch_insert_lenient_continues_past_errors() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let item = |id: &str, data: Vec<f32>| BatchInsertItem {
+            id: id.to_string(),
+            vector: Vector::new(data),
+            metadata: Metadata::new(),
+        };
+        let items = vec![
+            item("v1", vec![1.0, 0.0, 0.0]),
+            item("bad1", vec![0.0, 1.0]),
+            item("v2", vec![0.0, 1.0, 0.0]),
+            item("bad2", vec![0.0, 0.0, 1.0, 0.0]),
+            item("v3", vec![0.0, 0.0, 1.0]),
+        ];
+        let results = store.insert_batch_lenient(items);
+
+        let ok: Vec<bool> = results.iter().map(|r| r.is_ok()).collect();
+        assert_eq!(ok, vec![true, false, true, false, true]);
+        assert!(matches!(results[1], Err(VectorDbError::DimensionMismatch { .. })));
+        assert_eq!(store.len(), 3);
+        assert!(store.get("v3").is_some());
+        assert!(store.get("bad2").is_none());
+    }
+
+    #[test]
+    fn test_batch_insert_matches_sequential() {
+        let vectors: Vec<Vector> = (0..20)
+            .map(|i| Vector::new(vec![i as f32, (i * 3 % 7) as f32, (i % 5) as f32]))
+            .collect();
+
+        let mut sequential = VectorStore::new(DistanceMetric::Euclidean);
+        for (i, v) in vectors.iter().enumerate() {
+            sequential.insert(format!("v{}", i), v.clone()).unwrap();
+        }
+
+        let mut batched = VectorStore::new(DistanceMetric::Euclidean);
+        batched
+            .insert_batch(
+                vectors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| BatchInsertItem {
+                        id: format!("v{}", i),
+                        vector: v.clone(),
+                        metadata: Metadata::new(),
+                    })
+                    .collect(),
+            )
+            .unwrap();
+
+        assert_eq!(batched.len(), sequential.len());
+
+        let query = Vector::new(vec![4.2, 1.0, 3.0]);
+        let a = sequential.search(&query, 5).unwrap();
+        let b = batched.search(&query, 5).unwrap();
+        let a: Vec<(&str, f32)> = a.iter().map(|r| (r.id.as_str(), r.distance)).collect();
+        let b: Vec<(&str, f32)> = b.iter().map(|r| (r.id.as_str(), r.distance)).collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_batch_insert_duplicate_ids() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store.insert("v1", Vector::new(vec![9.0, 9.0])).unwrap();
+
+        let items = vec![
+            BatchInsertItem {
+                id: "v1".to_string(),
+                vector: Vector::new(vec![1.0, 0.0]),
+                metadata: Metadata::new(),
+            },
+            BatchInsertItem {
+                id: "v2".to_string(),
+                vector: Vector::new(vec![0.0, 1.0]),
+                metadata: Metadata::new(),
+            },
+            BatchInsertItem {
+                id: "v2".to_string(),
+                vector: Vector::new(vec![0.0, 2.0]),
+                metadata: Metadata::new(),
+            },
+        ];
+        store.insert_batch(items).unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get("v1"), Some(&Vector::new(vec![1.0, 0.0])));
+        assert_eq!(store.get("v2"), Some(&Vector::new(vec![0.0, 2.0])));
+    }
+
+    #[test]
+    fn test_batch_insert_index_failure_leaves_maps_consistent() {
+        use crate::hnsw::HnswIndex;
+
+        let item = |id: &str, data: Vec<f32>| BatchInsertItem {
+            id: id.to_string(),
+            vector: Vector::new(data),
+            metadata: Metadata::new(),
+        };
+        let mut store = VectorStore::with_index(HnswIndex::new(DistanceMetric::Cosine));
+        store.insert("a", Vector::new(vec![1.0, 0.0])).unwrap();
+
+        // The zero vector passes the dimension check but the index rejects it
+        let items = vec![item("a", vec![0.0, 1.0]), item("zero", vec![0.0, 0.0])];
+        assert!(store.insert_batch(items).is_err());
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("a").unwrap().as_slice(), &[1.0, 0.0]);
+        assert!(store.get("zero").is_none());
+        let results = store.search(&Vector::new(vec![0.0, 1.0]), 5).unwrap();
+        assert_eq!(results.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["a"]);
+    }
+
     #[test]
     fn test_batch_search() {
         let mut store = VectorStore::new(DistanceMetric::Euclidean);
@@ -696,6 +2785,73 @@ mod tests {
         assert_eq!(results[1][0].id, "v2");
     }
 
+    #[test]
+    fn test_batch_search_validates_up_front() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        for i in 0..10 {
+            store
+                .insert(format!("v{}", i), Vector::new(vec![i as f32, 1.0, 0.5]))
+                .unwrap();
+        }
+
+        let valid = vec![
+            (Vector::new(vec![2.2, 1.0, 0.5]), 3),
+            (Vector::new(vec![7.6, 0.0, 0.0]), 2),
+        ];
+        let batch = store.search_batch(&valid).unwrap();
+        for ((query, k), results) in valid.iter().zip(&batch) {
+            let single = store.search(query, *k).unwrap();
+            let a: Vec<&str> = single.iter().map(|r| r.id.as_str()).collect();
+            let b: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+            assert_eq!(a, b);
+        }
+
+        let mut invalid = valid.clone();
+        invalid.push((Vector::new(vec![1.0, 2.0]), 1));
+        assert!(matches!(
+            store.search_batch(&invalid),
+            Err(VectorDbError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_batch_search_each_isolates_bad_query() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        store
+            .insert("v1", Vector::new(vec![1.0, 0.0, 0.0]))
+            .unwrap();
+        store
+            .insert("v2", Vector::new(vec![0.0, 1.0, 0.0]))
+            .unwrap();
+
+        let queries = vec![
+            (Vector::new(vec![1.0, 0.0, 0.0]), 1),
+            (Vector::new(vec![1.0, 0.0]), 1), // wrong dimension
+            (Vector::new(vec![0.0, 1.0, 0.0]), 1),
+        ];
+        let results = store.search_batch_each(&queries);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()[0].id, "v1");
+        assert_eq!(results[2].as_ref().unwrap()[0].id, "v2");
+
+        match &results[1] {
+            Err(VectorDbError::BatchQueryError { index, source }) => {
+                assert_eq!(*index, 1);
+                assert!(matches!(
+                    **source,
+                    VectorDbError::DimensionMismatch {
+                        expected: 3,
+                        actual: 2
+                    }
+                ));
+            }
+            other => panic!("expected BatchQueryError, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_batch_search_with_filter() {
         let mut store = VectorStore::new(DistanceMetric::Euclidean);
@@ -753,4 +2909,263 @@ mod tests {
         let results = store.search_with_filter(&query, 10, &filter).unwrap();
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_from_snapshot_rebuilds_any_index() {
+        use crate::hnsw::HnswIndex;
+        use crate::persistence::serialization::SerializedVector;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut snapshot = DatabaseSnapshot {
+            vectors: Vec::new(),
+            metadata: HashMap::new(),
+            next_id: 0,
+            dimension: Some(8),
+            timestamps: HashMap::new(),
+        };
+        // Sparse internal IDs, as left behind by deletes
+        for i in 0..200 {
+            snapshot.vectors.push(SerializedVector {
+                internal_id: i * 2,
+                string_id: format!("v{}", i),
+                data: (0..8).map(|_| rng.gen_range(-1.0..1.0)).collect(),
+            });
+            let fields = HashMap::from([("n".to_string(), i.to_string())]);
+            snapshot.metadata.insert(i * 2, fields);
+        }
+        let query = Vector::new((0..8).map(|_| rng.gen_range(-1.0..1.0)).collect());
+
+        let metric = DistanceMetric::Euclidean;
+        let flat = VectorStore::from_snapshot(&snapshot, metric, FlatIndex::new).unwrap();
+        let hnsw = VectorStore::from_snapshot(&snapshot, metric, HnswIndex::new).unwrap();
+        assert_eq!(flat.len(), 200);
+        assert_eq!(hnsw.len(), 200);
+        assert_eq!(hnsw.dimension(), Some(8));
+        assert_eq!(hnsw.get_metadata("v17").unwrap().get("n").unwrap(), "17");
+        assert_eq!(hnsw.internal_to_string_ids()[&34], "v17");
+
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+        let expected = ids(flat.search(&query, 5).unwrap());
+        assert_eq!(ids(hnsw.search_with_ef(&query, 5, 200).unwrap()), expected);
+
+        // New inserts get fresh internal IDs instead of reusing snapshot ones
+        let mut flat = flat;
+        flat.insert("new", Vector::new(vec![0.0; 8])).unwrap();
+        assert_eq!(flat.len(), 201);
+        assert_eq!(flat.get_metadata("v199").unwrap().get("n").unwrap(), "199");
+
+        snapshot.vectors.push(SerializedVector {
+            internal_id: 1,
+            string_id: "v0".to_string(),
+            data: vec![0.0; 8],
+        });
+        assert!(VectorStore::from_snapshot(&snapshot, metric, FlatIndex::new).is_err());
+    }
+
+    #[test]
+    fn test_search_with_boost() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let items = [("near", 1.0, "plain"), ("runner_up", 1.2, "featured"), ("far", 5.0, "plain")];
+        for (id, x, tag) in items {
+            let mut meta = Metadata::new();
+            meta.insert("tag".to_string(), tag.to_string());
+            store.insert_with_metadata(id, Vector::new(vec![x, 0.0]), meta).unwrap();
+        }
+        let query = Vector::new(vec![0.0, 0.0]);
+        let featured = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "featured".to_string(),
+        };
+
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+        let unboosted = store.search_with_boost(&query, 2, &[]).unwrap();
+        assert_eq!(ids(unboosted), ["near", "runner_up"]);
+
+        let boosted = store.search_with_boost(&query, 3, &[(featured.clone(), 0.5)]).unwrap();
+        assert_eq!(ids(boosted.clone()), ["runner_up", "near", "far"]);
+        assert_relative_eq!(boosted[0].distance, 0.7, epsilon = 1e-6);
+        assert_relative_eq!(boosted[1].distance, 1.0, epsilon = 1e-6);
+
+        // Too small a boost to close the gap, and a negative one demotes
+        let small = store.search_with_boost(&query, 2, &[(featured.clone(), 0.1)]).unwrap();
+        assert_eq!(ids(small), ["near", "runner_up"]);
+        let plain = MetadataFilter::Eq {
+            field: "tag".to_string(),
+            value: "plain".to_string(),
+        };
+        let demoted = store.search_with_boost(&query, 3, &[(plain, -10.0)]).unwrap();
+        assert_eq!(ids(demoted)[0], "runner_up");
+
+        // Higher-is-better metrics add the boost
+        let mut ip = VectorStore::new(DistanceMetric::InnerProduct);
+        let mut meta = Metadata::new();
+        meta.insert("tag".to_string(), "featured".to_string());
+        ip.insert("top", Vector::new(vec![1.0, 0.0])).unwrap();
+        ip.insert_with_metadata("second", Vector::new(vec![0.8, 0.0]), meta).unwrap();
+        let results = ip.search_with_boost(&Vector::new(vec![1.0, 0.0]), 1, &[(featured, 0.5)]);
+        assert_eq!(ids(results.unwrap()), ["second"]);
+    }
+
+    #[test]
+    fn test_switch_between_flat_and_hnsw() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut flat = VectorStore::new(DistanceMetric::Cosine);
+        for i in 0..200 {
+            let mut meta = Metadata::new();
+            meta.insert("n".to_string(), i.to_string());
+            let v = Vector::new((0..6).map(|_| rng.gen_range(-1.0..1.0)).collect());
+            flat.insert_with_metadata(format!("v{}", i), v, meta).unwrap();
+        }
+        flat.delete("v5").unwrap();
+        let query = Vector::new((0..6).map(|_| rng.gen_range(-1.0..1.0)).collect());
+        let nearest = flat.search(&query, 1).unwrap()[0].id.clone();
+        let internal = flat.internal_id("v42");
+
+        let mut hnsw = flat.to_hnsw(HnswParams::default()).unwrap();
+        assert_eq!(hnsw.len(), 199);
+        assert_eq!(hnsw.metric(), DistanceMetric::Cosine);
+        assert_eq!(hnsw.search_with_ef(&query, 1, 200).unwrap()[0].id, nearest);
+        assert_eq!(hnsw.internal_id("v42"), internal);
+        assert_eq!(hnsw.get_metadata("v42").unwrap().get("n").unwrap(), "42");
+        assert!(hnsw.get("v5").is_none());
+        assert!(hnsw.insert("bad", Vector::new(vec![1.0])).is_err());
+
+        hnsw.insert("extra", Vector::new(vec![0.5; 6])).unwrap();
+        let flat = hnsw.to_flat().unwrap();
+        assert_eq!(flat.len(), 200);
+        assert_eq!(flat.search(&query, 1).unwrap()[0].id, nearest);
+        assert_eq!(flat.get("extra").unwrap().as_slice(), &[0.5; 6]);
+
+        // Invalid parameters are caught before anything moves
+        let params = HnswParams {
+            max_layers: 0,
+            ..HnswParams::default()
+        };
+        assert!(flat.to_hnsw(params).is_err());
+    }
+
+    #[test]
+    fn test_timestamps_track_inserts_and_updates() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NOW_MS: AtomicU64 = AtomicU64::new(1_000);
+        fn clock() -> u64 {
+            NOW_MS.load(Ordering::SeqCst)
+        }
+        let advance = |to: u64| NOW_MS.store(to, Ordering::SeqCst);
+
+        let mut store = VectorStore::new(DistanceMetric::Euclidean).with_millis_clock(clock);
+        store.insert("a", Vector::new(vec![1.0])).unwrap();
+        let inserted = Timestamps {
+            created_at: 1_000,
+            updated_at: 1_000,
+        };
+        assert_eq!(store.get_timestamps("a"), Some(inserted));
+
+        advance(2_000);
+        let mut meta = Metadata::new();
+        meta.insert("k".to_string(), "v".to_string());
+        store.update_metadata("a", meta).unwrap();
+        let updated = Timestamps {
+            created_at: 1_000,
+            updated_at: 2_000,
+        };
+        assert_eq!(store.get_timestamps("a"), Some(updated));
+
+        // Re-inserting the ID is an update too; renaming isn't
+        advance(3_000);
+        store.insert("a", Vector::new(vec![2.0])).unwrap();
+        store.rename("a", "b").unwrap();
+        assert_eq!(store.get_timestamps("b").unwrap().created_at, 1_000);
+        assert_eq!(store.get_timestamps("b").unwrap().updated_at, 3_000);
+
+        store
+            .insert_batch(vec![BatchInsertItem {
+                id: "c".to_string(),
+                vector: Vector::new(vec![3.0]),
+                metadata: Metadata::new(),
+            }])
+            .unwrap();
+        assert_eq!(store.get_timestamps("c").unwrap().created_at, 3_000);
+
+        store.delete("b").unwrap();
+        assert!(store.get_timestamps("b").is_none());
+        assert!(store.get_timestamps("missing").is_none());
+    }
+
+    #[test]
+    fn test_search_page_windows() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        for i in 0..30 {
+            store.insert(format!("v{}", i), Vector::new(vec![i as f32, 0.5])).unwrap();
+        }
+        let query = Vector::new(vec![7.2, 0.0]);
+        let ids = |results: &[SearchResult]| -> Vec<String> {
+            results.iter().map(|r| r.id.clone()).collect()
+        };
+
+        let first = store.search_page(&query, 0, 5).unwrap();
+        let second = store.search_page(&query, 5, 5).unwrap();
+        assert_eq!(first.next_offset, Some(5));
+        assert_eq!(second.next_offset, Some(10));
+        let (a, b) = (ids(&first.results), ids(&second.results));
+        assert!(a.iter().all(|id| !b.contains(id)));
+        let joined: Vec<String> = a.into_iter().chain(b).collect();
+        assert_eq!(joined, ids(&store.search(&query, 10).unwrap()));
+
+        // The last page is short and has no successor; past the end is empty
+        let last = store.search_page(&query, 25, 10).unwrap();
+        assert_eq!(last.results.len(), 5);
+        assert_eq!(last.next_offset, None);
+        let past = store.search_page(&query, 40, 10).unwrap();
+        assert!(past.results.is_empty() && past.next_offset.is_none());
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let mut store = VectorStore::new(DistanceMetric::Cosine);
+        for i in 0..40 {
+            let mut meta = Metadata::new();
+            meta.insert("bucket".to_string(), (i % 3).to_string());
+            let v = Vector::new(vec![i as f32, (i * 7 % 13) as f32, 1.0]);
+            store.insert_with_metadata(format!("v{}", i), v, meta).unwrap();
+        }
+        store.delete("v5").unwrap();
+
+        let mut dump = Vec::new();
+        store.export_jsonl(&mut dump).unwrap();
+        assert_eq!(dump.iter().filter(|&&b| b == b'\n').count(), 39);
+
+        let mut restored = VectorStore::new(DistanceMetric::Cosine);
+        assert_eq!(restored.import_jsonl(dump.as_slice()).unwrap(), 39);
+        assert_eq!(
+            restored.get_metadata("v7").unwrap().get("bucket"),
+            Some(&"1".to_string())
+        );
+        assert!(restored.get("v5").is_none());
+
+        let query = Vector::new(vec![10.0, 3.0, 1.0]);
+        let before: Vec<_> = store.search(&query, 10).unwrap();
+        let after: Vec<_> = restored.search(&query, 10).unwrap();
+        assert_eq!(
+            before.iter().map(|r| (&r.id, r.distance)).collect::<Vec<_>>(),
+            after.iter().map(|r| (&r.id, r.distance)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_import_jsonl_reports_bad_line() {
+        let mut store = VectorStore::new(DistanceMetric::Euclidean);
+        let dump = "{\"id\": \"a\", \"vector\": [1.0]}\n\nnot json\n";
+        let err = store.import_jsonl(dump.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+    }
 }
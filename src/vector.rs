@@ -16,6 +16,32 @@ impl Vector {
         Self { data }
     }
 
+    /// Create a vector from untrusted input, rejecting empty data and
+    /// NaN or infinite components.
+    pub fn try_new(data: Vec<f32>) -> Result<Self> {
+        if data.is_empty() {
+            return Err(VectorDbError::InvalidVector {
+                reason: "Vector must not be empty".to_string(),
+            });
+        }
+        if let Some(i) = data.iter().position(|x| !x.is_finite()) {
+            return Err(VectorDbError::InvalidVector {
+                reason: format!("Component {} is not finite: {}", i, data[i]),
+            });
+        }
+        Ok(Self { data })
+    }
+
+    /// Create a vector from f64 components, narrowing each to f32.
+    pub fn from_f64(data: &[f64]) -> Self {
+        Self::new(data.iter().map(|&x| x as f32).collect())
+    }
+
+    /// Copy the components out widened to f64.
+    pub fn as_f64(&self) -> Vec<f64> {
+        self.data.iter().map(|&x| f64::from(x)).collect()
+    }
+
     /// Get the dimension of the vector
     pub fn dimension(&self) -> usize {
         self.data.len()
@@ -33,7 +59,16 @@ impl Vector {
 
     /// Compute the L2 norm (magnitude) of the vector
     pub fn norm(&self) -> f32 {
-        self.data.iter().map(|x| x * x).sum::<f32>().sqrt()
+        crate::distance::pairwise_sum(&self.data, &self.data, |x, _| x * x).sqrt()
+    }
+
+    /// L2 norm accumulated in f64.
+    pub fn norm_f64(&self) -> f64 {
+        self.data
+            .iter()
+            .map(|&x| f64::from(x) * f64::from(x))
+            .sum::<f64>()
+            .sqrt()
     }
 
     /// Normalize the vector to unit length
@@ -57,7 +92,39 @@ impl Vector {
         Ok(v)
     }
 
+    /// Element-wise mean of `vectors`, which must be non-empty and share a dimension.
+    pub fn mean(vectors: &[Vector]) -> Result<Vector> {
+        Self::mean_of(vectors)
+    }
+
+    /// `mean` over borrowed vectors, so callers needn't clone them into a slice.
+    pub(crate) fn mean_of<'a>(vectors: impl IntoIterator<Item = &'a Vector>) -> Result<Vector> {
+        let mut vectors = vectors.into_iter();
+        let Some(first) = vectors.next() else {
+            return Err(VectorDbError::InvalidVector {
+                reason: "Cannot average an empty set of vectors".to_string(),
+            });
+        };
+
+        let mut sum = first.data.clone();
+        let mut count = 1usize;
+        for v in vectors {
+            if !v.has_same_dimension(first) {
+                return Err(VectorDbError::DimensionMismatch {
+                    expected: first.dimension(),
+                    actual: v.dimension(),
+                });
+            }
+            for (acc, x) in sum.iter_mut().zip(&v.data) {
+                *acc += x;
+            }
+            count += 1;
+        }
+        Ok(Vector::new(sum.into_iter().map(|x| x / count as f32).collect()))
+    }
+
     /// Parse a vector from a comma-separated string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self> {
         let data: Result<Vec<f32>> = s
             .split(',')
@@ -133,6 +200,43 @@ mod tests {
         assert_eq!(v.as_slice(), &[1.0, 2.0, 3.0]);
     }
 
+    #[test]
+    fn test_try_new_rejects_bad_input() {
+        assert!(Vector::try_new(vec![1.0, -2.0]).is_ok());
+        let bad = [
+            vec![],
+            vec![1.0, f32::NAN],
+            vec![f32::INFINITY],
+            vec![0.0, f32::NEG_INFINITY],
+        ];
+        for data in bad {
+            assert!(matches!(
+                Vector::try_new(data),
+                Err(VectorDbError::InvalidVector { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vector_mean() {
+        let mean = Vector::mean(&[
+            Vector::new(vec![1.0, 2.0]),
+            Vector::new(vec![3.0, 6.0]),
+            Vector::new(vec![5.0, -2.0]),
+        ])
+        .unwrap();
+        assert_eq!(mean.as_slice(), &[3.0, 2.0]);
+
+        assert!(matches!(
+            Vector::mean(&[]),
+            Err(VectorDbError::InvalidVector { .. })
+        ));
+        assert!(matches!(
+            Vector::mean(&[Vector::new(vec![1.0]), Vector::new(vec![1.0, 2.0])]),
+            Err(VectorDbError::DimensionMismatch { .. })
+        ));
+    }
+
     #[test]
     fn test_vector_norm() {
         let v = Vector::new(vec![3.0, 4.0]);
@@ -0,0 +1,115 @@
+//! End-to-end tests that drive the `vectordb` command-line binary.
+
+use std::path::Path;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(data_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vectordb_from_scratch"))
+        .arg("--data-dir")
+        .arg(data_dir)
+        .args(args)
+        .output()
+        .expect("failed to run CLI")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_cosine_metric_orders_by_angle() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = dir.path().join("db");
+
+    // "far" points the same way as the query but is far away in L2 terms;
+    // "near" is close in L2 but at a wider angle.
+    stdout(&run(&db, &["--metric", "cosine", "insert", "far", "--vector", "10.0,10.0"]));
+    stdout(&run(&db, &["--metric", "cosine", "insert", "near", "--vector", "1.0,0.2"]));
+
+    let out = stdout(&run(&db, &["--metric", "cosine", "search", "1.0,1.0", "-k", "2"]));
+    let far = out.find("far").unwrap();
+    let near = out.find("near").unwrap();
+    assert!(far < near, "unexpected ordering:\n{}", out);
+}
+
+#[test]
+fn test_reopen_with_conflicting_metric_fails() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = dir.path().join("db");
+
+    stdout(&run(&db, &["--metric", "cosine", "insert", "v1", "--vector", "1.0,0.0"]));
+    let output = run(&db, &["--metric", "euclidean", "list"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Metric mismatch"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_stats_reports_count() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = dir.path().join("db");
+
+    for (id, vector) in [("a", "1.0,0.0,0.0"), ("b", "0.0,1.0,0.0"), ("c", "0.0,0.0,1.0")] {
+        stdout(&run(&db, &["insert", id, "--vector", vector]));
+    }
+    let out = stdout(&run(&db, &["stats"]));
+    assert!(out.contains("Vectors:    3"), "stats output:\n{}", out);
+    assert!(out.contains("Dimension:  3"), "stats output:\n{}", out);
+    assert!(out.contains("Metric:     euclidean"), "stats output:\n{}", out);
+}
+
+#[test]
+fn test_bench_smoke() {
+    let output = Command::new(env!("CARGO_BIN_EXE_vectordb_from_scratch"))
+        .args(["bench", "--n", "200", "--dim", "8", "-k", "5", "--queries", "10"])
+        .output()
+        .expect("failed to run CLI");
+    let out = stdout(&output);
+    assert!(out.contains("flat"), "bench output:\n{}", out);
+    let hnsw = out.lines().find(|l| l.starts_with("hnsw")).unwrap();
+    let recall: f64 = hnsw.split_whitespace().last().unwrap().parse().unwrap();
+    assert!((0.0..=1.0).contains(&recall));
+}
+
+#[test]
+fn test_repl_runs_piped_script() {
+    let script = "\
+insert a --vector 1.0,0.0
+insert b --vector 0.0,1.0
+search 1.0,0.1 -k 1
+bogus
+delete a
+list
+quit
+insert c --vector 1.0,1.0
+";
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vectordb_from_scratch"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run CLI");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let out = stdout(&child.wait_with_output().unwrap());
+    let lines: Vec<&str> = out.lines().collect();
+
+    assert!(lines.contains(&"Inserted vector with ID: a"), "output:\n{}", out);
+    assert!(lines.contains(&"1. a (distance: 0.1000)"), "output:\n{}", out);
+    assert!(out.contains("error: unrecognized subcommand 'bogus'"), "output:\n{}", out);
+    assert!(lines.contains(&"Deleted vector with ID: a"), "output:\n{}", out);
+    assert!(lines.contains(&"Vector IDs (1 total):"), "output:\n{}", out);
+    // Nothing after `quit` runs
+    assert!(!out.contains("ID: c"), "output:\n{}", out);
+}
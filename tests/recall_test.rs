@@ -1,32 +1,25 @@
 //! Recall tests: verify HNSW finds a high percentage of true nearest neighbors.
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use vectordb_from_scratch::eval::recall_at_k;
 use vectordb_from_scratch::{
-    DistanceMetric, FlatIndex, HnswIndex, HnswParams, Index, Vector,
+    DistanceMetric, FlatIndex, HnswIndex, HnswParams, Index, IvfIndex, IvfParams, LshIndex,
+    LshParams, QuantizedFlatIndex, Vector,
 };
 
-fn random_vectors(n: usize, dim: usize) -> Vec<Vector> {
-    let mut rng = rand::thread_rng();
+/// `n` vectors with components uniformly drawn from [0, 1), reproducible
+/// from the caller's seeded RNG so a failing threshold can be rerun.
+fn random_vectors(rng: &mut StdRng, n: usize, dim: usize) -> Vec<Vector> {
     (0..n)
-        .map(|_| {
-            let data: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-            Vector::new(data)
-        })
+        .map(|_| Vector::new((0..dim).map(|_| rng.gen::<f32>()).collect()))
         .collect()
 }
 
-fn recall_at_k(flat_results: &[(usize, f32)], hnsw_results: &[(usize, f32)]) -> f64 {
-    let ground_truth: std::collections::HashSet<usize> =
-        flat_results.iter().map(|(id, _)| *id).collect();
-    let found: usize = hnsw_results
-        .iter()
-        .filter(|(id, _)| ground_truth.contains(id))
-        .count();
-    found as f64 / flat_results.len() as f64
-}
-
 fn test_recall(n: usize, dim: usize, k: usize, num_queries: usize, min_recall: f64) {
-    let vectors = random_vectors(n, dim);
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    let vectors = random_vectors(&mut rng, n, dim);
 
     // Build flat index (ground truth)
     let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
@@ -36,13 +29,13 @@ fn test_recall(n: usize, dim: usize, k: usize, num_queries: usize, min_recall: f
 
     // Build HNSW index
     let params = HnswParams::new(16, 200, 50);
-    let mut hnsw = HnswIndex::with_params(DistanceMetric::Euclidean, params);
+    let mut hnsw = HnswIndex::with_seed(DistanceMetric::Euclidean, params, 42).unwrap();
     for (i, v) in vectors.iter().enumerate() {
         hnsw.add(i, v.clone()).unwrap();
     }
 
     // Run queries
-    let queries = random_vectors(num_queries, dim);
+    let queries = random_vectors(&mut rng, num_queries, dim);
     let mut total_recall = 0.0;
 
     for query in &queries {
@@ -78,3 +71,195 @@ fn test_recall_1000_vectors() {
 fn test_recall_5000_vectors() {
     test_recall(5000, 128, 10, 20, 0.85);
 }
+
+/// Int8 quantization perturbs each component by at most half a level
+/// ((max - min) / 510), which only reorders near-ties: recall@10 for
+/// Euclidean should stay at or above 0.9.
+#[test]
+fn test_quantized_flat_recall() {
+    let (n, dim, k, num_queries) = (2000, 64, 10, 50);
+    let mut rng = StdRng::seed_from_u64(70);
+    let vectors = random_vectors(&mut rng, n, dim);
+
+    let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+    let mut quantized = QuantizedFlatIndex::new(DistanceMetric::Euclidean);
+    for (i, v) in vectors.iter().enumerate() {
+        flat.add(i, v.clone()).unwrap();
+        quantized.add(i, v.clone()).unwrap();
+    }
+
+    let queries = random_vectors(&mut rng, num_queries, dim);
+    let total_recall: f64 = queries
+        .iter()
+        .map(|q| {
+            let truth = flat.search(q, k).unwrap();
+            recall_at_k(&truth, &quantized.search(q, k).unwrap())
+        })
+        .sum();
+
+    let avg_recall = total_recall / num_queries as f64;
+    assert!(
+        avg_recall >= 0.9,
+        "Quantized recall {:.3} is below threshold 0.900",
+        avg_recall
+    );
+    assert!(quantized.memory_usage_bytes() < flat.memory_usage_bytes() / 2);
+}
+
+/// Probing a quarter of the lists trades some recall for scanning ~25% of the
+/// data; probing every list must match brute force exactly.
+#[test]
+fn test_ivf_recall() {
+    let (n, dim, k, num_queries) = (4000, 16, 10, 50);
+    let mut rng = StdRng::seed_from_u64(102);
+    let vectors = random_vectors(&mut rng, n, dim);
+
+    let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+    let mut ivf =
+        IvfIndex::with_params(DistanceMetric::Euclidean, IvfParams::new(32, 8)).unwrap();
+    for (i, v) in vectors.iter().enumerate() {
+        flat.add(i, v.clone()).unwrap();
+        ivf.add(i, v.clone()).unwrap();
+    }
+    assert!(ivf.is_trained());
+
+    let queries = random_vectors(&mut rng, num_queries, dim);
+    let recall = |ivf: &IvfIndex| {
+        queries
+            .iter()
+            .map(|q| recall_at_k(&flat.search(q, k).unwrap(), &ivf.search(q, k).unwrap()))
+            .sum::<f64>()
+            / num_queries as f64
+    };
+
+    let avg_recall = recall(&ivf);
+    assert!(
+        avg_recall >= 0.75,
+        "IVF recall {:.3} is below threshold 0.750",
+        avg_recall
+    );
+
+    ivf.set_nprobe(32);
+    assert_eq!(recall(&ivf), 1.0);
+}
+
+#[test]
+fn test_lsh_cosine_recall() {
+    let (n, dim, k, num_queries) = (2000, 32, 10, 50);
+    let mut rng = StdRng::seed_from_u64(136);
+    let mut unit_vectors = |count: usize| -> Vec<Vector> {
+        (0..count)
+            .map(|_| {
+                let data: Vec<f32> = (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect();
+                Vector::new(data).normalized().unwrap()
+            })
+            .collect()
+    };
+    let vectors = unit_vectors(n);
+
+    let mut flat = FlatIndex::new(DistanceMetric::Cosine);
+    let mut lsh = LshIndex::with_params(DistanceMetric::Cosine, LshParams::new(32, 6)).unwrap();
+    for (i, v) in vectors.iter().enumerate() {
+        flat.add(i, v.clone()).unwrap();
+        lsh.add(i, v.clone()).unwrap();
+    }
+
+    let queries = unit_vectors(num_queries);
+    let total_recall: f64 = queries
+        .iter()
+        .map(|q| recall_at_k(&flat.search(q, k).unwrap(), &lsh.search(q, k).unwrap()))
+        .sum();
+
+    let avg_recall = total_recall / num_queries as f64;
+    assert!(
+        avg_recall >= 0.8,
+        "LSH recall {:.3} is below threshold 0.800",
+        avg_recall
+    );
+}
+
+/// Deleting half the graph exercises neighborhood repair; without it the
+/// survivors' lists thin out and recall drops well below the insert-only case.
+#[test]
+fn test_hnsw_recall_after_deleting_half() {
+    let (n, dim, k, num_queries) = (1000, 32, 10, 50);
+    let mut rng = StdRng::seed_from_u64(173);
+    let vectors = random_vectors(&mut rng, n, dim);
+
+    let params = HnswParams::new(16, 200, 50);
+    let mut hnsw = HnswIndex::with_seed(DistanceMetric::Euclidean, params, 42).unwrap();
+    for (i, v) in vectors.iter().enumerate() {
+        hnsw.add(i, v.clone()).unwrap();
+    }
+
+    let mut ids: Vec<usize> = (0..n).collect();
+    ids.shuffle(&mut rng);
+    let (deleted, kept) = ids.split_at(n / 2);
+    for &id in deleted {
+        hnsw.remove(id).unwrap();
+    }
+
+    let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+    for &id in kept {
+        flat.add(id, vectors[id].clone()).unwrap();
+    }
+
+    let queries = random_vectors(&mut rng, num_queries, dim);
+    let total_recall: f64 = queries
+        .iter()
+        .map(|q| {
+            let truth = flat.search(q, k).unwrap();
+            recall_at_k(&truth, &hnsw.search_with_ef(q, k, 100).unwrap())
+        })
+        .sum();
+
+    let avg_recall = total_recall / num_queries as f64;
+    assert!(
+        avg_recall >= 0.9,
+        "Recall after deletes {:.3} is below threshold 0.900",
+        avg_recall
+    );
+}
+
+/// `add_batch` searches neighbors in parallel against a chunk-stale graph;
+/// merging intra-chunk distances should keep its recall level with serial inserts.
+#[test]
+fn test_parallel_build_recall_matches_serial() {
+    let (n, dim, k, num_queries) = (2000, 32, 10, 50);
+    let mut rng = StdRng::seed_from_u64(216);
+    let vectors = random_vectors(&mut rng, n, dim);
+    let params = HnswParams::new(16, 200, 50);
+
+    let mut serial = HnswIndex::with_seed(DistanceMetric::Euclidean, params.clone(), 42).unwrap();
+    for (i, v) in vectors.iter().enumerate() {
+        serial.add(i, v.clone()).unwrap();
+    }
+
+    let mut parallel = HnswIndex::with_seed(DistanceMetric::Euclidean, params, 42).unwrap();
+    parallel
+        .add_batch(vectors.iter().cloned().enumerate().collect())
+        .unwrap();
+    assert_eq!(parallel.len(), n);
+
+    let mut flat = FlatIndex::new(DistanceMetric::Euclidean);
+    for (i, v) in vectors.iter().enumerate() {
+        flat.add(i, v.clone()).unwrap();
+    }
+
+    let queries = random_vectors(&mut rng, num_queries, dim);
+    let (mut serial_recall, mut parallel_recall) = (0.0, 0.0);
+    for q in &queries {
+        let truth = flat.search(q, k).unwrap();
+        serial_recall += recall_at_k(&truth, &serial.search_with_ef(q, k, 100).unwrap());
+        parallel_recall += recall_at_k(&truth, &parallel.search_with_ef(q, k, 100).unwrap());
+    }
+    serial_recall /= num_queries as f64;
+    parallel_recall /= num_queries as f64;
+
+    assert!(
+        parallel_recall >= 0.9 && parallel_recall >= serial_recall - 0.03,
+        "Parallel build recall {:.3} vs serial {:.3}",
+        parallel_recall,
+        serial_recall
+    );
+}